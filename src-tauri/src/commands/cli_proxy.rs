@@ -1,133 +1,267 @@
 //! CLI Proxy server management commands
 
-use std::process::Command;
-use tauri::command;
+use std::path::PathBuf;
+use std::time::Duration;
+use serde::Serialize;
+use tauri::{command, AppHandle, State};
 
 use crate::error::{CommandError, CommandResult};
-use crate::state::CLI_PROXY_PROCESS;
+use crate::install::config_merge;
+use crate::proxy::{
+    self,
+    config,
+    discovery,
+    health::{self, Readiness},
+    instance::{ManagedChild, ProxyInstanceInfo},
+    output::OutputLine,
+    port::{self, PortStatus},
+    profile::{self, LaunchProfile},
+    shutdown::{self, StopResult},
+    stats::ProxyStats,
+};
+use crate::state::{self, AppState};
 
-#[cfg(windows)]
-use std::os::windows::process::CommandExt;
+/// Result of `start_cli_proxy`
+#[derive(Serialize)]
+pub struct ProxyStartInfo {
+    pub pid: u32,
+    pub port: u16,
+    pub api_base: String,
+    /// Set when the configured port was taken and config.yaml was rewritten
+    pub port_changed: bool,
+}
+
+impl ProxyStartInfo {
+    fn new(pid: u32, port: Option<u16>, port_changed: bool) -> Self {
+        let port = port.unwrap_or(config::DEFAULT_PORT);
+        Self { pid, port, api_base: api_base(port), port_changed }
+    }
+}
 
-// Windows: CREATE_NO_WINDOW flag to hide console
-#[cfg(windows)]
-const CREATE_NO_WINDOW: u32 = 0x08000000;
+/// Result of `get_proxy_config_path`
+#[derive(Serialize)]
+pub struct ProxyConfigPaths {
+    /// File the proxy reads, in the install root for versioned installs
+    pub config_path: String,
+    /// Example config shipped next to the binary
+    pub example_path: String,
+}
+
+fn api_base(port: u16) -> String {
+    format!("http://localhost:{}", port)
+}
 
 /// Start CLI Proxy server
+///
+/// The instance's launch profile supplies the config path, arguments and
+/// environment. The configured port is probed first. If it is taken, the
+/// start fails with `PortInUse` unless `auto_port` is set, in which case the
+/// config file is rewritten to a free port. The spawned child is handed to the supervisor,
+/// which restarts it if it exits without `stop_cli_proxy` being called.
 #[command]
-pub async fn start_cli_proxy(exe_path: String) -> CommandResult<u32> {
-    let mut guard = CLI_PROXY_PROCESS.lock()
+pub async fn start_cli_proxy(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    exe_path: String,
+    instance_id: Option<String>,
+    auto_port: Option<bool>,
+) -> CommandResult<ProxyStartInfo> {
+    let instance = state.instance(&state::instance_id(instance_id));
+
+    let mut guard = instance.process.lock()
         .map_err(|e| CommandError::General(e.to_string()))?;
 
     // Check if already running
-    if let Some(ref mut child) = *guard {
-        if child.try_wait().ok().flatten().is_none() {
-            return Ok(child.id());
+    if let Some(ref mut managed) = *guard {
+        if managed.child.try_wait().ok().flatten().is_none() {
+            return Ok(ProxyStartInfo::new(managed.child.id(), managed.port, false));
         }
         *guard = None;
     }
 
-    // Get working directory from exe path
-    let exe = std::path::PathBuf::from(&exe_path);
-    let work_dir = exe.parent()
+    // A proxy left running by a previous session already holds the port
+    let adopted = instance.adopted_pid().or_else(|| {
+        let pid = discovery::adopt_from_pidfile(&instance)?;
+        proxy::supervisor::watch_adopted(app.clone(), instance.clone());
+        Some(pid)
+    });
+    if let Some(pid) = adopted {
+        drop(guard);
+        return Ok(ProxyStartInfo::new(pid, instance.info().port, false));
+    }
+
+    let exe = PathBuf::from(&exe_path);
+    let exe_dir = exe.parent()
         .ok_or_else(|| CommandError::General("Invalid path".into()))?;
+    let profile = instance.launch_profile();
+    let config_file = profile.config_file(exe_dir);
+
+    let status = port::probe(config::effective_port(&config_file));
+    let mut port_changed = false;
+    if status.in_use {
+        if !auto_port.unwrap_or(false) {
+            return Err(CommandError::PortInUse { port: status.port, holder: status.holder() });
+        }
+        let free = port::find_free(status.port)
+            .ok_or_else(|| CommandError::General("No free port available".into()))?;
+        config::write_port(&config_file, free, &exe_dir.join(config_merge::EXAMPLE_FILE_NAME))
+            .map_err(|e| CommandError::General(format!("Failed to update {}: {}", config_file.display(), e)))?;
+        port_changed = true;
+    }
 
     // Spawn process
-    #[cfg(windows)]
-    let child = Command::new(&exe_path)
-        .current_dir(work_dir)
-        .creation_flags(CREATE_NO_WINDOW)
-        .spawn()
+    let child = proxy::spawn(&app, &instance, &exe_path, &profile)
         .map_err(|e| CommandError::General(format!("Failed: {}", e)))?;
 
-    #[cfg(not(windows))]
-    let child = Command::new(&exe_path)
-        .current_dir(work_dir)
-        .spawn()
-        .map_err(|e| CommandError::General(format!("Failed: {}", e)))?;
+    let managed = ManagedChild::new(child, exe, &profile);
+    let info = ProxyStartInfo::new(managed.child.id(), managed.port, port_changed);
+    *guard = Some(managed);
 
-    let pid = child.id();
-    *guard = Some(child);
+    let generation = instance.next_generation();
+    proxy::supervisor::spawn(app, instance.clone(), exe_path, profile, generation);
 
-    // Store executable name for cleanup
-    if let Ok(mut name_guard) = crate::state::CLI_PROXY_NAME.lock() {
-        if let Some(name) = exe.file_name().and_then(|n| n.to_str()) {
-            *name_guard = Some(name.to_string());
-        }
-    }
+    Ok(info)
+}
 
-    Ok(pid)
+/// Check whether the port the instance would listen on is free
+#[command]
+pub async fn check_proxy_port(
+    state: State<'_, AppState>,
+    exe_path: String,
+    instance_id: Option<String>,
+) -> CommandResult<PortStatus> {
+    let exe_dir = PathBuf::from(&exe_path).parent()
+        .map(|dir| dir.to_path_buf())
+        .ok_or_else(|| CommandError::General("Invalid path".into()))?;
+    let profile = state.instance(&state::instance_id(instance_id)).launch_profile();
+    Ok(port::probe(config::effective_port(&profile.config_file(&exe_dir))))
 }
 
-/// Stop CLI Proxy server
+/// Config file a proxy started from `exe_path` will read, for editing before the first start
 #[command]
-pub async fn stop_cli_proxy() -> CommandResult<()> {
-    let mut guard = CLI_PROXY_PROCESS.lock()
-        .map_err(|e| CommandError::General(e.to_string()))?;
+pub async fn get_proxy_config_path(
+    state: State<'_, AppState>,
+    exe_path: String,
+    instance_id: Option<String>,
+) -> CommandResult<ProxyConfigPaths> {
+    let exe_dir = PathBuf::from(&exe_path).parent()
+        .map(|dir| dir.to_path_buf())
+        .ok_or_else(|| CommandError::General("Invalid path".into()))?;
+    let profile = state.instance(&state::instance_id(instance_id)).launch_profile();
+    Ok(ProxyConfigPaths {
+        config_path: profile.config_file(&exe_dir).to_string_lossy().to_string(),
+        example_path: exe_dir.join(config_merge::EXAMPLE_FILE_NAME).to_string_lossy().to_string(),
+    })
+}
 
-    if let Some(ref mut child) = *guard {
-        // On Windows, use taskkill for reliable termination
-        #[cfg(windows)]
-        {
-            let pid = child.id();
-            let _ = Command::new("taskkill")
-                .args(["/F", "/T", "/PID", &pid.to_string()])
-                .creation_flags(CREATE_NO_WINDOW)
-                .output();
-        }
+/// Get the launch profile (config path, args, env) of an instance
+#[command]
+pub async fn get_launch_profile(state: State<'_, AppState>, instance_id: Option<String>) -> CommandResult<LaunchProfile> {
+    Ok(state.instance(&state::instance_id(instance_id)).launch_profile())
+}
 
-        #[cfg(not(windows))]
-        {
-            let _ = child.kill();
-        }
+/// Save the launch profile of an instance
+///
+/// Takes effect on the next `start_cli_proxy`; a running proxy keeps its profile.
+#[command]
+pub async fn set_launch_profile(
+    state: State<'_, AppState>,
+    instance_id: Option<String>,
+    profile: LaunchProfile,
+) -> CommandResult<()> {
+    profile.validate().map_err(CommandError::General)?;
+    let instance = state.instance(&state::instance_id(instance_id));
+    profile::save(&instance.id, &profile)
+        .map_err(|e| CommandError::General(format!("Failed to save launch profile: {}", e)))?;
+    instance.set_launch_profile(profile);
+    Ok(())
+}
 
-        let _ = child.wait();
-    }
+/// Wait until the proxy at `api_base` answers, typically right after `start_cli_proxy`
+///
+/// If the instance is running when called, its exit is reported as
+/// `process_exited` instead of waiting out the timeout.
+#[command]
+pub async fn wait_for_proxy_ready(
+    state: State<'_, AppState>,
+    api_base: String,
+    management_key: Option<String>,
+    timeout_ms: Option<u64>,
+    instance_id: Option<String>,
+) -> CommandResult<Readiness> {
+    let instance = state
+        .find_instance(&state::instance_id(instance_id))
+        .filter(|instance| instance.is_running());
+    let timeout = timeout_ms
+        .map(Duration::from_millis)
+        .unwrap_or(health::DEFAULT_READY_TIMEOUT);
+    let management_key = management_key.filter(|key| !key.is_empty());
 
-    *guard = None;
-
-    // Fallback: kill by name if available
-    if let Ok(mut name_guard) = crate::state::CLI_PROXY_NAME.lock() {
-        if let Some(ref name) = *name_guard {
-             if name.to_lowercase().contains("cliproxy") {
-                #[cfg(windows)]
-                {
-                    use std::process::Command;
-                    use std::os::windows::process::CommandExt;
-                    const CREATE_NO_WINDOW: u32 = 0x08000000;
-
-                    let _ = Command::new("taskkill")
-                        .args(["/F", "/T", "/IM", name])
-                        .creation_flags(CREATE_NO_WINDOW)
-                        .output();
-                }
-
-                #[cfg(not(windows))]
-                {
-                    use std::process::Command;
-                    let _ = Command::new("pkill")
-                        .args(["-f", name])
-                        .output();
-                }
-            }
-        }
-        *name_guard = None;
-    }
-    Ok(())
+    let client = state.http().client();
+
+    Ok(health::wait_until_ready(&client, &api_base, management_key.as_deref(), instance, timeout).await)
+}
+
+/// Stop CLI Proxy server
+///
+/// The proxy is asked to exit first and only force killed once
+/// `grace_period_ms` (default 5s) has passed.
+#[command]
+pub async fn stop_cli_proxy(
+    state: State<'_, AppState>,
+    instance_id: Option<String>,
+    grace_period_ms: Option<u64>,
+) -> CommandResult<StopResult> {
+    let Some(instance) = state.find_instance(&state::instance_id(instance_id)) else {
+        return Ok(StopResult::not_running());
+    };
+    let grace = grace_period_ms
+        .map(Duration::from_millis)
+        .unwrap_or(shutdown::DEFAULT_GRACE_PERIOD);
+
+    tauri::async_runtime::spawn_blocking(move || instance.stop(grace))
+        .await
+        .map_err(|e| CommandError::General(e.to_string()))
 }
 
 /// Check if CLI Proxy is running
 #[command]
-pub async fn is_cli_proxy_running() -> CommandResult<bool> {
-    let mut guard = CLI_PROXY_PROCESS.lock()
-        .map_err(|e| CommandError::General(e.to_string()))?;
+pub async fn is_cli_proxy_running(state: State<'_, AppState>, instance_id: Option<String>) -> CommandResult<bool> {
+    Ok(state
+        .find_instance(&state::instance_id(instance_id))
+        .is_some_and(|instance| instance.is_running()))
+}
 
-    if let Some(ref mut child) = *guard {
-        if child.try_wait().ok().flatten().is_none() {
-            return Ok(true);
-        }
-        *guard = None;
-    }
+/// List managed CLI Proxy instances with pid, exe path, port and uptime
+#[command]
+pub async fn list_proxy_instances(state: State<'_, AppState>) -> CommandResult<Vec<ProxyInstanceInfo>> {
+    Ok(state.instances().iter().map(|instance| instance.info()).collect())
+}
+
+/// Get captured CLI Proxy stdout/stderr lines newer than `since_seq`
+#[command]
+pub async fn get_proxy_output(
+    state: State<'_, AppState>,
+    since_seq: Option<u64>,
+    instance_id: Option<String>,
+) -> CommandResult<Vec<OutputLine>> {
+    let Some(instance) = state.find_instance(&state::instance_id(instance_id)) else {
+        return Ok(Vec::new());
+    };
+    let buffer = instance.output.lock()
+        .map_err(|e| CommandError::General(e.to_string()))?;
+    Ok(buffer.since(since_seq))
+}
 
-    Ok(false)
+/// CPU, memory, descriptor and thread usage of a running instance
+#[command]
+pub async fn get_proxy_stats(state: State<'_, AppState>, instance_id: Option<String>) -> CommandResult<Option<ProxyStats>> {
+    let Some(instance) = state.find_instance(&state::instance_id(instance_id)) else {
+        return Ok(None);
+    };
+    let info = instance.info();
+    let Some(pid) = info.pid.filter(|_| info.running) else {
+        return Ok(None);
+    };
+    Ok(state.stats().sample(&info.id, pid, info.uptime_secs))
 }
@@ -0,0 +1,190 @@
+//! Installed proxy inventory commands
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{command, State};
+
+use crate::error::{CommandError, CommandResult};
+use crate::install::{
+    self,
+    inventory::{self, Edition, InstalledProxy},
+    versions::{self, InstalledBuild},
+    InstallOutcome,
+};
+use crate::proxy;
+use crate::state::AppState;
+
+/// Find an installed build of the other edition to switch to
+///
+/// The newest build of `target_version` in the inventory wins; other
+/// binaries next to the current exe are the fallback for installs outside
+/// the versioned layout. Only looks: switching the current version is up to
+/// `activate_installed_proxy`.
+#[command]
+pub async fn find_alternate_proxy_exe(current_exe_path: String, target_version: String) -> CommandResult<Option<String>> {
+    let current = PathBuf::from(&current_exe_path);
+    let parent = current.parent()
+        .ok_or_else(|| CommandError::General("Cannot determine parent directory".to_string()))?
+        .to_path_buf();
+    let edition = if target_version == "plus" { Edition::Plus } else { Edition::Standard };
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = versions::install_root(&parent);
+        let candidate = inventory::list(&root)
+            .into_iter()
+            .find(|build| build.edition == edition && Path::new(&build.exe_path) != current)
+            .map(|build| build.exe_path);
+
+        candidate.or_else(|| {
+            fs::read_dir(&parent)
+                .into_iter()
+                .flatten()
+                .flatten()
+                .map(|entry| entry.path())
+                .find(|path| {
+                    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+                    path.is_file() && *path != current && proxy::is_proxy_exe(path) && Edition::from_name(name) == edition
+                })
+                .map(|path| path.to_string_lossy().to_string())
+        })
+    })
+    .await
+    .map_err(|e| CommandError::General(e.to_string()))
+}
+
+/// Install a proxy from a local `.zip`/`.tar.gz` release archive or a bare binary
+///
+/// For machines without internet access. The file goes through the same
+/// extraction, executable discovery and config merge as a download and ends
+/// up as a new current version. Without `tag` the version is taken from the
+/// file name, or from the binary's `--version` output.
+#[command]
+pub async fn install_proxy_from_file(
+    path: String,
+    target_dir: Option<String>,
+    tag: Option<String>,
+) -> CommandResult<InstallOutcome> {
+    let root = install_root(target_dir)?;
+    let file = PathBuf::from(&path);
+    if !file.is_file() {
+        return Err(CommandError::General(format!("File not found: {}", path)));
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let name = file.file_name().and_then(|n| n.to_str()).unwrap_or_default().to_string();
+        let is_archive = install::is_archive(&name);
+        let tag = tag.unwrap_or_else(|| local_tag(&file, &name, is_archive));
+
+        println!("Installing proxy {} from: {}", tag, file.display());
+        let outcome = if is_archive {
+            install::install_archive(&root, &file, &name, &tag)
+        } else {
+            install::install_binary(&root, &file, &tag)
+        }?;
+        println!("Installed {} at: {}", outcome.tag, outcome.exe_path);
+        Ok(outcome)
+    })
+    .await
+    .map_err(|e| CommandError::General(e.to_string()))?
+}
+
+/// Tag for a local file: the version in its name (`CLIProxyAPI_6.1.2_linux_amd64.tar.gz`),
+/// what a bare binary reports, or a timestamp
+fn local_tag(file: &Path, name: &str, is_archive: bool) -> String {
+    let from_name = name.split(['_', '-']).find_map(inventory::version_from_tag);
+    let version = from_name.or_else(|| (!is_archive).then(|| inventory::probe_version(file)).flatten());
+    match version {
+        Some(version) => format!("v{}", version),
+        None => {
+            let secs = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            format!("local-{}", secs)
+        }
+    }
+}
+
+/// Installed builds under the install root, newest version first
+#[command]
+pub async fn list_installed_proxies(target_dir: Option<String>) -> CommandResult<Vec<InstalledProxy>> {
+    let root = install_root(target_dir)?;
+    tauri::async_runtime::spawn_blocking(move || inventory::list(&root))
+        .await
+        .map_err(|e| CommandError::General(e.to_string()))
+}
+
+/// Delete an installed build; the current one and running ones are refused
+#[command]
+pub async fn remove_installed_proxy(
+    state: State<'_, AppState>,
+    id: String,
+    target_dir: Option<String>,
+) -> CommandResult<()> {
+    let root = install_root(target_dir)?;
+    let build = find_installed(root.clone(), id.clone()).await?;
+
+    // Any binary inside a version dir counts, the legacy build is just its exe
+    let build_dir = (build.id != versions::LEGACY_TAG).then(|| versions::versions_dir(&root).join(&build.id));
+    let running = state.instances().iter().map(|instance| instance.info()).any(|info| {
+        info.running
+            && info.exe_path.is_some_and(|exe| {
+                exe == build.exe_path || build_dir.as_ref().is_some_and(|dir| Path::new(&exe).starts_with(dir))
+            })
+    });
+    if running {
+        return Err(CommandError::General(format!("{} is running; stop it first", id)));
+    }
+
+    tauri::async_runtime::spawn_blocking(move || inventory::remove(&root, &build))
+        .await
+        .map_err(|e| CommandError::General(e.to_string()))?
+        .map_err(|e| CommandError::General(format!("Failed to remove {}: {}", id, e)))?;
+    println!("Removed installed proxy {}", id);
+    Ok(())
+}
+
+/// Make an installed build the current version and return its exe path
+#[command]
+pub async fn activate_installed_proxy(id: String, target_dir: Option<String>) -> CommandResult<String> {
+    let root = install_root(target_dir)?;
+    let build = find_installed(root.clone(), id).await?;
+    tauri::async_runtime::spawn_blocking(move || activate(&root, &build).map(|()| build.exe_path))
+        .await
+        .map_err(|e| CommandError::General(e.to_string()))?
+}
+
+/// `inventory::find` off the async runtime; it probes binaries for their version
+async fn find_installed(root: PathBuf, id: String) -> CommandResult<InstalledProxy> {
+    tauri::async_runtime::spawn_blocking(move || {
+        inventory::find(&root, &id).ok_or_else(|| CommandError::General(format!("No installed proxy {}", id)))
+    })
+    .await
+    .map_err(|e| CommandError::General(e.to_string()))?
+}
+
+/// Point the install back at the previous version, e.g. after a failed health check
+///
+/// Returns the restored build; the caller restarts the proxy with its exe.
+#[command]
+pub async fn rollback_proxy(target_dir: Option<String>) -> CommandResult<InstalledBuild> {
+    let root = install_root(target_dir)?;
+    let build = versions::rollback(&root)
+        .map_err(|e| CommandError::General(format!("Failed to roll back: {}", e)))?;
+    println!("Rolled back to {} at: {}", build.tag, build.exe_path);
+    Ok(build)
+}
+
+fn activate(root: &Path, build: &InstalledProxy) -> CommandResult<()> {
+    versions::activate(root, build.build())
+        .map_err(|e| CommandError::General(format!("Failed to switch to {}: {}", build.id, e)))
+}
+
+/// The install root for an optional directory from the frontend
+pub(super) fn install_root(target_dir: Option<String>) -> CommandResult<PathBuf> {
+    match target_dir {
+        Some(dir) => Ok(versions::install_root(&PathBuf::from(dir))),
+        None => proxy::default_install_dir()
+            .ok_or_else(|| CommandError::General("Could not determine user home directory".to_string())),
+    }
+}
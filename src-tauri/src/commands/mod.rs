@@ -5,9 +5,13 @@
 mod cli_proxy;
 mod utils;
 mod download;
+mod install;
+mod quota;
 mod version;
 
 pub use cli_proxy::*;
 pub use utils::*;
 pub use download::*;
+pub use install::*;
+pub use quota::*;
 pub use version::*;
@@ -0,0 +1,143 @@
+//! Provider quota commands
+
+use tauri::{command, AppHandle, Manager, State};
+
+use crate::error::{CommandError, CommandResult};
+use crate::quota::{self, management::{ManagementClient, ManagementConnection}, Provider, QuotaSnapshot};
+use crate::quota::alerts::{self, AlertSettings};
+use crate::quota::forecast::{self, QuotaForecast};
+use crate::quota::history::{self, Bucket, HistoryPoint, HistoryRange, HistorySettings};
+use crate::quota::poller::{self, PollSettings};
+use crate::state::AppState;
+
+/// Remember the management API the frontend logged in to; `None` on logout
+#[command]
+pub async fn set_management_connection(
+    state: State<'_, AppState>,
+    connection: Option<ManagementConnection>,
+) -> CommandResult<()> {
+    state.set_management(connection.filter(|c| !c.api_base.trim().is_empty()));
+    Ok(())
+}
+
+/// Fetch the quota of one account through the proxy
+///
+/// Upstream failures such as an expired token are reported in the
+/// snapshot's `error`; only a missing login is an error of the command.
+/// The snapshot is also cached and emitted like the poller's.
+#[command]
+pub async fn fetch_quota(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    auth_index: String,
+    provider: Provider,
+) -> CommandResult<QuotaSnapshot> {
+    let connection = state
+        .management()
+        .ok_or_else(|| CommandError::General("Not connected to the management API".to_string()))?;
+    let client = ManagementClient::new(state.http().client(), &connection);
+    let snapshot = quota::fetch(&client, &auth_index, provider).await;
+    poller::publish(&app, snapshot.clone()).await;
+    Ok(snapshot)
+}
+
+/// Latest snapshot of every account, so the quota page shows data right away
+#[command]
+pub async fn get_cached_quotas(state: State<'_, AppState>) -> CommandResult<Vec<QuotaSnapshot>> {
+    Ok(state.quota().snapshots())
+}
+
+#[command]
+pub async fn get_quota_poll_settings(state: State<'_, AppState>) -> CommandResult<PollSettings> {
+    Ok(state.quota().settings())
+}
+
+/// Save the background polling settings; they apply from the next tick
+#[command]
+pub async fn set_quota_poll_settings(state: State<'_, AppState>, settings: PollSettings) -> CommandResult<()> {
+    settings.validate().map_err(CommandError::General)?;
+    poller::save(&settings)
+        .map_err(|e| CommandError::General(format!("Failed to save quota polling settings: {}", e)))?;
+    state.quota().apply(settings);
+    Ok(())
+}
+
+/// Stored quota of a provider account over a time range, one point per model and bucket
+///
+/// Without `model` every model of the account is returned; `bucket`
+/// defaults to `hour`.
+#[command]
+pub async fn query_quota_history(
+    app: AppHandle,
+    provider: Provider,
+    account: String,
+    model: Option<String>,
+    range: HistoryRange,
+    bucket: Option<Bucket>,
+) -> CommandResult<Vec<HistoryPoint>> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let bucket = bucket.unwrap_or(Bucket::Hour);
+        app.state::<AppState>()
+            .history()
+            .query(provider, &account, model.as_deref(), &range, bucket)
+            .map_err(CommandError::General)
+    })
+    .await
+    .map_err(|e| CommandError::General(e.to_string()))?
+}
+
+#[command]
+pub async fn get_quota_history_settings(state: State<'_, AppState>) -> CommandResult<HistorySettings> {
+    Ok(state.history().settings())
+}
+
+/// Save retention and compaction settings and apply them right away
+#[command]
+pub async fn set_quota_history_settings(app: AppHandle, settings: HistorySettings) -> CommandResult<()> {
+    settings.validate().map_err(CommandError::General)?;
+    history::save(&settings)
+        .map_err(|e| CommandError::General(format!("Failed to save quota history settings: {}", e)))?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        state.history().apply(settings);
+        state.history().maintain().map_err(CommandError::General)
+    })
+    .await
+    .map_err(|e| CommandError::General(e.to_string()))?
+}
+
+/// When each model runs out at its recent burn rate, for one account or all cached ones
+///
+/// Accounts whose latest snapshot failed are left out.
+#[command]
+pub async fn forecast_quota(app: AppHandle, account: Option<String>) -> CommandResult<Vec<QuotaForecast>> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        let now = chrono::Utc::now();
+        let mut forecasts = Vec::new();
+        for snapshot in state.quota().snapshots() {
+            if snapshot.error.is_some() || account.as_ref().is_some_and(|a| *a != snapshot.auth_index) {
+                continue;
+            }
+            forecasts.extend(forecast::forecast(state.history(), &snapshot, now).map_err(CommandError::General)?);
+        }
+        Ok(forecasts)
+    })
+    .await
+    .map_err(|e| CommandError::General(e.to_string()))?
+}
+
+#[command]
+pub async fn get_alert_settings(state: State<'_, AppState>) -> CommandResult<AlertSettings> {
+    Ok(state.alerts().settings())
+}
+
+/// Save the notification rules; they apply to the next snapshot
+#[command]
+pub async fn set_alert_settings(state: State<'_, AppState>, settings: AlertSettings) -> CommandResult<()> {
+    settings.validate().map_err(CommandError::General)?;
+    alerts::save(&settings)
+        .map_err(|e| CommandError::General(format!("Failed to save notification settings: {}", e)))?;
+    state.alerts().apply(settings);
+    Ok(())
+}
@@ -1,9 +1,10 @@
 //! Utility commands
 
-use tauri::command;
+use tauri::{command, State};
 
 use crate::error::{CommandError, CommandResult};
-use crate::state;
+use crate::net::{self, HttpSettings};
+use crate::state::AppState;
 
 /// Open URL in system browser
 #[command]
@@ -13,7 +14,26 @@ pub async fn open_external_url(url: String) -> CommandResult<()> {
 
 /// Set run in background mode
 #[command]
-pub async fn set_run_in_background(enabled: bool) -> CommandResult<()> {
-    state::set_run_in_background(enabled);
+pub async fn set_run_in_background(state: State<'_, AppState>, enabled: bool) -> CommandResult<()> {
+    state.set_run_in_background(enabled);
     Ok(())
 }
+
+/// Get the outbound HTTP settings (proxy, CA bundle, timeouts, user agent)
+#[command]
+pub async fn get_http_settings(state: State<'_, AppState>) -> CommandResult<HttpSettings> {
+    Ok(state.http().settings())
+}
+
+/// Save the outbound HTTP settings
+///
+/// The settings are checked by building a client from them first, so a bad
+/// proxy URL or CA bundle is reported and nothing is saved. Requests that
+/// are already running keep the previous client.
+#[command]
+pub async fn set_http_settings(state: State<'_, AppState>, settings: HttpSettings) -> CommandResult<()> {
+    settings.build_client().map_err(CommandError::General)?;
+    net::save(&settings)
+        .map_err(|e| CommandError::General(format!("Failed to save HTTP settings: {}", e)))?;
+    state.http().apply(settings).map_err(CommandError::General)
+}
@@ -1,8 +1,13 @@
-use tauri::command;
-use reqwest;
+use tauri::{command, State};
 use serde::Serialize;
 
 use crate::error::{CommandError, CommandResult};
+use crate::install::{
+    inventory::Edition,
+    release::{self, Channel, ResolvedRelease},
+};
+use crate::proxy::health;
+use crate::state::AppState;
 
 #[derive(Serialize)]
 pub struct ProxyVersionInfo {
@@ -12,7 +17,11 @@ pub struct ProxyVersionInfo {
 }
 
 #[command]
-pub async fn check_proxy_version(api_base: String, management_key: String) -> CommandResult<ProxyVersionInfo> {
+pub async fn check_proxy_version(
+    state: State<'_, AppState>,
+    api_base: String,
+    management_key: String,
+) -> CommandResult<ProxyVersionInfo> {
     let base_url = api_base
         .trim_end_matches('/')
         .to_string();
@@ -20,7 +29,7 @@ pub async fn check_proxy_version(api_base: String, management_key: String) -> Co
     let url = format!("{}/v0/management/latest-version", base_url);
     // println!("[Rust] Checking proxy version at: {}", url);
 
-    let client = reqwest::Client::new();
+    let client = state.http().client();
     let response = client.get(&url)
         .header("Authorization", format!("Bearer {}", management_key))
         .header("Accept", "application/json")
@@ -33,17 +42,7 @@ pub async fn check_proxy_version(api_base: String, management_key: String) -> Co
         return Err(CommandError::General(format!("API returned status {}", response.status())));
     }
 
-    let current_version = response.headers()
-        .get("x-cpa-version")
-        .or_else(|| response.headers().get("x-server-version"))
-        .and_then(|v| v.to_str().ok())
-        .map(|s| s.to_string());
-
-    let build_date = response.headers()
-        .get("x-cpa-build-date")
-        .or_else(|| response.headers().get("x-server-build-date"))
-        .and_then(|v| v.to_str().ok())
-        .map(|s| s.to_string());
+    let (current_version, build_date) = health::version_headers(response.headers());
 
     let body: serde_json::Value = response.json().await
         .map_err(|e| CommandError::General(format!("Failed to parse response: {}", e)))?;
@@ -62,3 +61,18 @@ pub async fn check_proxy_version(api_base: String, management_key: String) -> Co
         latest_version,
     })
 }
+
+/// Find the release and asset to install for `edition` on this OS and arch
+///
+/// `channel` is `"stable"` (default), `"prerelease"` or `{ "tag": "v6.1.2" }`;
+/// `api_base` replaces `https://api.github.com`, e.g. with a mock server.
+#[command]
+pub async fn resolve_proxy_release(
+    state: State<'_, AppState>,
+    edition: Edition,
+    channel: Option<Channel>,
+    api_base: Option<String>,
+) -> CommandResult<ResolvedRelease> {
+    let client = state.http().client();
+    release::resolve(&client, api_base.as_deref(), edition, &channel.unwrap_or_default()).await
+}
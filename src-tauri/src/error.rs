@@ -1,5 +1,6 @@
 //! Error types for Tauri commands
 
+use serde::ser::SerializeMap;
 use serde::Serialize;
 
 /// Command error type
@@ -7,16 +8,69 @@ use serde::Serialize;
 pub enum CommandError {
     #[error("{0}")]
     General(String),
+    #[error("Port {port} is already in use by {holder}")]
+    PortInUse { port: u16, holder: String },
+    #[error("Verification of {file} failed: {reason}")]
+    VerificationFailed { file: String, reason: String },
 }
 
+/// Serialized as `{ kind, message, ...fields }` so the frontend can tell
+/// errors apart and still show `message` like before
 impl Serialize for CommandError {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        let mut map = serializer.serialize_map(None)?;
+        match self {
+            CommandError::General(_) => {
+                map.serialize_entry("kind", "general")?;
+            }
+            CommandError::PortInUse { port, holder } => {
+                map.serialize_entry("kind", "port_in_use")?;
+                map.serialize_entry("port", port)?;
+                map.serialize_entry("holder", holder)?;
+            }
+            CommandError::VerificationFailed { file, reason } => {
+                map.serialize_entry("kind", "verification_failed")?;
+                map.serialize_entry("file", file)?;
+                map.serialize_entry("reason", reason)?;
+            }
+        }
+        map.serialize_entry("message", &self.to_string())?;
+        map.end()
     }
 }
 
 /// Result type alias for commands
 pub type CommandResult<T> = Result<T, CommandError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_kind_and_fields() {
+        let error = CommandError::VerificationFailed {
+            file: "cli-proxy-api.zip".to_string(),
+            reason: "SHA-256 mismatch".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({
+                "kind": "verification_failed",
+                "file": "cli-proxy-api.zip",
+                "reason": "SHA-256 mismatch",
+                "message": "Verification of cli-proxy-api.zip failed: SHA-256 mismatch",
+            })
+        );
+
+        let error = CommandError::PortInUse { port: 8317, holder: "nginx (pid 42)".to_string() };
+        let value = serde_json::to_value(&error).unwrap();
+        assert_eq!(value["kind"], "port_in_use");
+        assert_eq!(value["port"], 8317);
+
+        let value = serde_json::to_value(CommandError::General("Boom".to_string())).unwrap();
+        assert_eq!(value, serde_json::json!({ "kind": "general", "message": "Boom" }));
+    }
+}
@@ -0,0 +1,536 @@
+//! Carrying `config.yaml` across proxy upgrades
+//!
+//! Every release ships a `config.example.yaml`. On upgrade the user's
+//! `config.yaml` is compared with it, using the example it was last merged
+//! with as the base: keys the user never had are added with their default
+//! and comments, and every value the user has is kept. Defaults that changed
+//! upstream are only reported, since the user may rely on the old value.
+//! Edits are made to the text rather than re-serializing, so the user's own
+//! comments, layout and line endings survive. The previous file is saved as
+//! `config.yaml.bak` first.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::Serialize;
+use serde_yaml::{Mapping, Value};
+
+use crate::proxy::config::{join_lines, CONFIG_FILE_NAME};
+
+pub const EXAMPLE_FILE_NAME: &str = "config.example.yaml";
+/// Copy of the example `config.yaml` was last merged with, kept in the install root
+pub const BASE_FILE_NAME: &str = ".config.example.base.yaml";
+pub const BACKUP_FILE_NAME: &str = "config.yaml.bak";
+
+/// What an upgrade did to `config.yaml`; keys are dotted paths like `remote-management.allow-remote`
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct MergeSummary {
+    /// There was no `config.yaml`, so it was created from the example
+    pub created: bool,
+    /// New keys, added with their default
+    pub added: Vec<String>,
+    /// Defaults the user never changed that changed upstream; left as they are
+    pub changed_defaults: Vec<String>,
+    /// User values kept although their default changed upstream
+    pub kept: Vec<String>,
+    /// Keys the new example dropped; left in place for the user to remove
+    pub obsolete: Vec<String>,
+    /// Upstream changes that could not be applied to the text, e.g. inside flow mappings
+    pub manual: Vec<String>,
+    pub backup_path: Option<String>,
+}
+
+/// Bring `<root>/config.yaml` up to date with the example shipped in `example_dir`
+///
+/// `previous_dir` holds the example of the build that was current before,
+/// used as the base when no merge was recorded yet. Returns `None` when the
+/// release has no example config.
+pub fn sync(root: &Path, example_dir: &Path, previous_dir: Option<&Path>) -> io::Result<Option<MergeSummary>> {
+    let Ok(theirs) = fs::read_to_string(example_dir.join(EXAMPLE_FILE_NAME)) else {
+        return Ok(None);
+    };
+    let config_path = root.join(CONFIG_FILE_NAME);
+    let base_path = root.join(BASE_FILE_NAME);
+
+    if !config_path.exists() {
+        fs::write(&config_path, &theirs)?;
+        fs::write(&base_path, &theirs)?;
+        println!("Created {} from {}", config_path.display(), EXAMPLE_FILE_NAME);
+        return Ok(Some(MergeSummary { created: true, ..Default::default() }));
+    }
+
+    let user = fs::read_to_string(&config_path)?;
+    // Without any base every key the user has counts as theirs, so nothing of it is overwritten
+    let base = fs::read_to_string(&base_path)
+        .ok()
+        .or_else(|| previous_dir.and_then(|dir| fs::read_to_string(dir.join(EXAMPLE_FILE_NAME)).ok()))
+        .unwrap_or_default();
+
+    let (merged, mut summary) = merge(&base, &user, &theirs)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    if merged != user {
+        let backup_path = root.join(BACKUP_FILE_NAME);
+        fs::copy(&config_path, &backup_path)?;
+        let temp = root.join(format!("{}.tmp", CONFIG_FILE_NAME));
+        fs::write(&temp, &merged)?;
+        fs::rename(&temp, &config_path)?;
+        summary.backup_path = Some(backup_path.to_string_lossy().to_string());
+    }
+    fs::write(&base_path, &theirs)?;
+    Ok(Some(summary))
+}
+
+/// Three-way merge of YAML texts that only adds keys, returning the merged user text
+pub fn merge(base: &str, user: &str, theirs: &str) -> Result<(String, MergeSummary), String> {
+    let base_value = parse(base).map_err(|e| format!("Old example config is not valid YAML: {}", e))?;
+    let user_value = parse(user).map_err(|e| format!("{} is not valid YAML: {}", CONFIG_FILE_NAME, e))?;
+    let theirs_value = parse(theirs).map_err(|e| format!("New example config is not valid YAML: {}", e))?;
+
+    let mut summary = MergeSummary::default();
+    let mut additions = Vec::new();
+    diff(&mut Vec::new(), Some(&base_value), &user_value, &theirs_value, &mut additions, &mut summary);
+
+    let mut text = user.to_string();
+    for path in additions {
+        match insert_block(&text, theirs, &path) {
+            Some(merged) => {
+                text = merged;
+                summary.added.push(path.join("."));
+            }
+            None => summary.manual.push(path.join(".")),
+        }
+    }
+    Ok((text, summary))
+}
+
+fn parse(text: &str) -> Result<Mapping, serde_yaml::Error> {
+    match serde_yaml::from_str::<Value>(text)? {
+        Value::Mapping(mapping) => Ok(mapping),
+        _ => Ok(Mapping::new()),
+    }
+}
+
+/// Collect the keys `user` lacks and report how its values relate to `theirs`
+fn diff(
+    path: &mut Vec<String>,
+    base: Option<&Mapping>,
+    user: &Mapping,
+    theirs: &Mapping,
+    additions: &mut Vec<Vec<String>>,
+    summary: &mut MergeSummary,
+) {
+    for (key, theirs_value) in theirs {
+        let Some(name) = key.as_str() else {
+            continue;
+        };
+        path.push(name.to_string());
+        let base_value = base.and_then(|base| base.get(key));
+
+        match (user.get(key), base_value) {
+            // Not in the old example either: a new key
+            (None, None) => additions.push(path.clone()),
+            // The user deleted it on purpose
+            (None, Some(_)) => {}
+            (Some(Value::Mapping(user_map)), _) if theirs_value.is_mapping() => {
+                let base_map = base_value.and_then(Value::as_mapping);
+                let theirs_map = theirs_value.as_mapping().unwrap_or(user_map);
+                diff(path, base_map, user_map, theirs_map, additions, summary);
+            }
+            (Some(user_value), Some(base_value)) if base_value != theirs_value => {
+                if user_value == base_value {
+                    summary.changed_defaults.push(path.join("."));
+                } else if user_value != theirs_value {
+                    summary.kept.push(path.join("."));
+                }
+            }
+            _ => {}
+        }
+        path.pop();
+    }
+
+    for key in user.keys() {
+        let dropped = !theirs.contains_key(key) && base.is_some_and(|base| base.contains_key(key));
+        if let (true, Some(name)) = (dropped, key.as_str()) {
+            let mut obsolete = path.clone();
+            obsolete.push(name.to_string());
+            summary.obsolete.push(obsolete.join("."));
+        }
+    }
+}
+
+/// A block-mapping key found in the text
+struct Entry {
+    path: Vec<String>,
+    indent: usize,
+    /// First line of the comments directly above the key
+    start: usize,
+    line: usize,
+    /// One past the last line of the key's value, trailing blanks excluded
+    end: usize,
+}
+
+/// Locate block-mapping keys line by line
+///
+/// Keys inside sequences and block scalars are not indexed; merging never
+/// reaches into those.
+fn index(lines: &[&str]) -> Vec<Entry> {
+    let mut entries: Vec<Entry> = Vec::new();
+    let mut stack: Vec<(usize, String)> = Vec::new();
+    let mut opaque_below: Option<usize> = None;
+
+    for (i, line) in lines.iter().enumerate() {
+        let Some(indent) = content_indent(line) else {
+            continue;
+        };
+        if let Some(floor) = opaque_below {
+            if indent > floor {
+                continue;
+            }
+            opaque_below = None;
+        }
+        let trimmed = line.trim_start();
+        if trimmed == "-" || trimmed.starts_with("- ") {
+            opaque_below = Some(indent);
+            continue;
+        }
+        let Some((key, value)) = split_key(trimmed) else {
+            continue;
+        };
+
+        while stack.last().is_some_and(|(depth, _)| *depth >= indent) {
+            stack.pop();
+        }
+        let mut path: Vec<String> = stack.iter().map(|(_, key)| key.clone()).collect();
+        path.push(key.clone());
+        let value = strip_comment(value).trim();
+        if value.starts_with('|') || value.starts_with('>') {
+            opaque_below = Some(indent);
+        }
+        stack.push((indent, key));
+
+        let mut start = i;
+        while start > 0 && is_comment(lines[start - 1]) && indent_of(lines[start - 1]) == indent {
+            start -= 1;
+        }
+        entries.push(Entry { path, indent, start, line: i, end: lines.len() });
+    }
+
+    for entry in &mut entries {
+        let mut end = (entry.line + 1..lines.len())
+            .find(|&j| {
+                content_indent(lines[j]).is_some_and(|indent| {
+                    // `key:` followed by `- item` at the same indent is still the key's value
+                    indent < entry.indent || (indent == entry.indent && !lines[j].trim_start().starts_with('-'))
+                })
+            })
+            .unwrap_or(lines.len());
+        // Blank lines and the next key's comments belong to what follows
+        while end > entry.line + 1
+            && (lines[end - 1].trim().is_empty() || (is_comment(lines[end - 1]) && indent_of(lines[end - 1]) <= entry.indent))
+        {
+            end -= 1;
+        }
+        entry.end = end;
+    }
+    entries
+}
+
+/// Copy a key with its comments and children from `theirs` into `user`
+///
+/// It goes after the nearest preceding sibling the user has, so upstream
+/// ordering is kept where possible.
+fn insert_block(user: &str, theirs: &str, path: &[String]) -> Option<String> {
+    let theirs_lines: Vec<&str> = theirs.lines().collect();
+    let theirs_entries = index(&theirs_lines);
+    let source = theirs_entries.iter().find(|entry| entry.path == path)?;
+    let parent = &path[..path.len() - 1];
+
+    let user_lines: Vec<&str> = user.lines().collect();
+    let user_entries = index(&user_lines);
+    let find_user = |path: &[String]| user_entries.iter().find(|entry| entry.path == path);
+
+    let user_parent = if parent.is_empty() { None } else { Some(find_user(parent)?) };
+    // A parent written as a flow mapping (`key: {a: 1}`) has no lines to add to
+    if let Some(user_parent) = user_parent {
+        let value = split_key(user_lines[user_parent.line].trim_start())?.1;
+        if !strip_comment(value).trim().is_empty() {
+            return None;
+        }
+    }
+
+    let siblings: Vec<&Entry> = theirs_entries
+        .iter()
+        .filter(|entry| entry.path.len() == path.len() && entry.path[..parent.len()] == *parent)
+        .collect();
+    let position = siblings.iter().position(|entry| entry.path == path)?;
+    let sibling_indent = user_entries
+        .iter()
+        .find(|entry| entry.path.len() == path.len() && entry.path[..parent.len()] == *parent)
+        .map(|entry| entry.indent);
+
+    let at = if let Some(previous) = siblings[..position].iter().rev().find_map(|s| find_user(&s.path)) {
+        previous.end
+    } else if let Some(next) = siblings[position + 1..].iter().find_map(|s| find_user(&s.path)) {
+        next.start
+    } else {
+        user_parent.map(|parent| parent.end).unwrap_or(user_lines.len())
+    };
+
+    let target_indent = match (sibling_indent, user_parent, theirs_entries.iter().find(|e| e.path == parent)) {
+        (Some(indent), _, _) => indent,
+        (None, Some(user_parent), Some(theirs_parent)) => user_parent.indent + (source.indent - theirs_parent.indent),
+        _ => 0,
+    };
+
+    let mut block: Vec<String> = theirs_lines[source.start..source.end]
+        .iter()
+        .map(|line| reindent(line, source.indent, target_indent))
+        .collect();
+    // Top-level keys are separated by blank lines in the examples
+    if parent.is_empty() {
+        if at > 0 && !user_lines[at - 1].trim().is_empty() {
+            block.insert(0, String::new());
+        }
+        if at < user_lines.len() && !user_lines[at].trim().is_empty() {
+            block.push(String::new());
+        }
+    }
+
+    let mut lines: Vec<String> = user_lines.iter().map(|line| line.to_string()).collect();
+    lines.splice(at..at, block);
+    Some(join_lines(lines, user))
+}
+
+/// Split `key: value` into the unquoted key and everything after the colon
+fn split_key(trimmed: &str) -> Option<(String, &str)> {
+    let (key, rest) = if let Some(quote) = trimmed.chars().next().filter(|c| *c == '"' || *c == '\'') {
+        let close = trimmed[1..].find(quote)? + 1;
+        (trimmed[1..close].to_string(), &trimmed[close + 1..])
+    } else {
+        let colon = trimmed
+            .char_indices()
+            .find(|&(i, c)| c == ':' && trimmed[i + 1..].chars().next().is_none_or(char::is_whitespace))?
+            .0;
+        let key = trimmed[..colon].trim_end();
+        if key.is_empty() || key.starts_with(['{', '[', '?', '&', '*', '!', '#']) {
+            return None;
+        }
+        (key.to_string(), &trimmed[colon..])
+    };
+    let value = rest.trim_start().strip_prefix(':')?;
+    value.chars().next().is_none_or(char::is_whitespace).then_some((key, value))
+}
+
+/// The part of a value before its `# comment`, ignoring `#` inside quotes
+fn strip_comment(value: &str) -> &str {
+    let mut quote: Option<char> = None;
+    let mut previous = ' ';
+    for (i, c) in value.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '#' && previous.is_whitespace() => return &value[..i],
+            None => {}
+        }
+        previous = c;
+    }
+    value
+}
+
+fn reindent(line: &str, from: usize, to: usize) -> String {
+    if line.trim().is_empty() {
+        return String::new();
+    }
+    let indent = indent_of(line);
+    let kept = indent.saturating_sub(from);
+    // Lines indented less than the block itself (odd comments) are left at the target indent
+    format!("{}{}", " ".repeat(to + kept), line.trim_start())
+}
+
+/// Indent of a line holding YAML content, `None` for blanks and comments
+fn content_indent(line: &str) -> Option<usize> {
+    let trimmed = line.trim_start();
+    (!trimmed.is_empty() && !trimmed.starts_with('#')).then(|| line.len() - trimmed.len())
+}
+
+fn is_comment(line: &str) -> bool {
+    line.trim_start().starts_with('#')
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = "\
+# Server port
+port: 8317
+
+# Enable debug logging
+debug: false
+
+remote-management:
+  # Allow remote access
+  allow-remote: false
+  secret-key: \"\"
+";
+
+    const THEIRS: &str = "\
+# Server port
+port: 8317
+
+# Enable debug logging
+debug: true
+
+# Retries for failed requests
+request-retry: 3
+
+remote-management:
+  # Allow remote access
+  allow-remote: false
+  secret-key: \"\"
+  # Disable the control panel
+  disable-control-panel: false
+";
+
+    fn merged(base: &str, user: &str, theirs: &str) -> (String, MergeSummary) {
+        merge(base, user, theirs).unwrap()
+    }
+
+    #[test]
+    fn adds_new_keys_and_keeps_user_values() {
+        let user = "\
+# Server port
+port: 9000 # moved off the default
+
+# Enable debug logging
+debug: false
+
+remote-management:
+  # Allow remote access
+  allow-remote: true
+  secret-key: \"hunter2\"
+";
+        let (text, summary) = merged(BASE, user, THEIRS);
+        assert_eq!(
+            text,
+            "\
+# Server port
+port: 9000 # moved off the default
+
+# Enable debug logging
+debug: false
+
+# Retries for failed requests
+request-retry: 3
+
+remote-management:
+  # Allow remote access
+  allow-remote: true
+  secret-key: \"hunter2\"
+  # Disable the control panel
+  disable-control-panel: false
+"
+        );
+        assert_eq!(summary.added, ["request-retry", "remote-management.disable-control-panel"]);
+        // `debug` still has the old default, which is reported but not overwritten
+        assert_eq!(summary.changed_defaults, ["debug"]);
+        assert!(summary.kept.is_empty() && summary.manual.is_empty() && summary.obsolete.is_empty());
+    }
+
+    #[test]
+    fn reports_kept_and_obsolete_keys() {
+        let base = "port: 8317\nlegacy-mode: false\n";
+        let theirs = "port: 8318\n";
+        let (text, summary) = merged(base, "port: 9000\nlegacy-mode: true\n", theirs);
+        assert_eq!(text, "port: 9000\nlegacy-mode: true\n");
+        assert_eq!(summary.kept, ["port"]);
+        assert_eq!(summary.obsolete, ["legacy-mode"]);
+
+        // A key the user deleted stays deleted
+        let (text, summary) = merged(base, "port: 8317\n", "port: 8317\nlegacy-mode: false\n");
+        assert_eq!(text, "port: 8317\n");
+        assert!(summary.added.is_empty());
+    }
+
+    #[test]
+    fn adds_nested_maps_at_their_indent() {
+        let base = "auth:\n    providers:\n        codex: true\n";
+        let theirs = "auth:\n  providers:\n    codex: true\n    kiro:\n      enabled: false\n";
+        let (text, summary) = merged(base, base, theirs);
+        // The user's four-space indent wins over the example's two
+        assert_eq!(text, "auth:\n    providers:\n        codex: true\n        kiro:\n          enabled: false\n");
+        assert_eq!(summary.added, ["auth.providers.kiro"]);
+    }
+
+    #[test]
+    fn leaves_flow_mappings_to_the_user() {
+        let user = "remote-management: {allow-remote: false, secret-key: \"\"}\n";
+        let (text, summary) = merged(BASE, user, THEIRS);
+        assert!(text.contains("remote-management: {allow-remote: false, secret-key: \"\"}\n"));
+        assert_eq!(summary.manual, ["remote-management.disable-control-panel"]);
+    }
+
+    #[test]
+    fn adds_after_lists() {
+        let base = "api-keys:\n  - one\nport: 8317\n";
+        let user = "api-keys:\n- one\n- two:\n    nested: x\nport: 8317\n";
+        let theirs = "api-keys:\n  - one\nproxy-url: \"\"\nport: 8317\n";
+        let (text, summary) = merged(base, user, theirs);
+        assert_eq!(text, "api-keys:\n- one\n- two:\n    nested: x\n\nproxy-url: \"\"\n\nport: 8317\n");
+        assert_eq!(summary.added, ["proxy-url"]);
+    }
+
+    #[test]
+    fn ignores_hashes_inside_quotes() {
+        assert_eq!(strip_comment(" \"a # b\" # note"), " \"a # b\" ");
+        assert_eq!(strip_comment(" url#fragment"), " url#fragment");
+        assert_eq!(split_key("\"odd key\": 1 # c").map(|(key, _)| key).as_deref(), Some("odd key"));
+        assert_eq!(split_key("- item"), None);
+    }
+
+    #[test]
+    fn handles_document_starts_and_empty_files() {
+        let (text, summary) = merged("port: 8317\n", "---\nport: 8317\n", "port: 8317\ndebug: false\n");
+        assert_eq!(text, "---\nport: 8317\n\ndebug: false\n");
+        assert_eq!(summary.added, ["debug"]);
+
+        let (text, summary) = merged("", "", "port: 8317\n");
+        assert_eq!(text, "port: 8317\n");
+        assert_eq!(summary.added, ["port"]);
+    }
+
+    #[test]
+    fn keeps_crlf_line_endings() {
+        let user = BASE.replace('\n', "\r\n");
+        let (text, _) = merged(BASE, &user, THEIRS);
+        assert_eq!(text.matches("\r\n").count(), text.matches('\n').count());
+        assert_eq!(text, merged(BASE, BASE, THEIRS).0.replace('\n', "\r\n"));
+    }
+
+    #[test]
+    fn syncs_files_with_a_backup() {
+        let root = tempfile::tempdir().unwrap();
+        let example_dir = tempfile::tempdir().unwrap();
+        fs::write(example_dir.path().join(EXAMPLE_FILE_NAME), BASE).unwrap();
+
+        let summary = sync(root.path(), example_dir.path(), None).unwrap().unwrap();
+        assert!(summary.created);
+        assert_eq!(fs::read_to_string(root.path().join(CONFIG_FILE_NAME)).unwrap(), BASE);
+
+        fs::write(example_dir.path().join(EXAMPLE_FILE_NAME), THEIRS).unwrap();
+        let summary = sync(root.path(), example_dir.path(), None).unwrap().unwrap();
+        assert_eq!(summary.added.len(), 2);
+        assert_eq!(fs::read_to_string(root.path().join(BACKUP_FILE_NAME)).unwrap(), BASE);
+        assert_eq!(fs::read_to_string(root.path().join(BASE_FILE_NAME)).unwrap(), THEIRS);
+
+        // Nothing left to add: the file isn't touched again
+        let summary = sync(root.path(), example_dir.path(), None).unwrap().unwrap();
+        assert!(summary.added.is_empty() && summary.backup_path.is_none());
+    }
+}
@@ -0,0 +1,397 @@
+//! Streamed archive downloads
+//!
+//! Archives are written to `~/.zerolimit/downloads/<name>.part` while
+//! `proxy-download://progress` events report progress. An interrupted
+//! download resumes from its partial file with an HTTP Range request, and a
+//! running one can be cancelled by id. Mirrors are tried in turn when a
+//! source fails or is too slow.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use reqwest::header::{CONTENT_RANGE, ETAG, IF_RANGE, LAST_MODIFIED, RANGE};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use tokio::io::AsyncWriteExt;
+use tokio_util::sync::CancellationToken;
+
+use super::mirror::{self, Source};
+use crate::error::{CommandError, CommandResult};
+
+pub const EVENT_PROGRESS: &str = "proxy-download://progress";
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+/// Below this many bytes per second over `THROUGHPUT_WINDOW` the next source is tried
+const MIN_THROUGHPUT: u64 = 32 * 1024;
+const THROUGHPUT_WINDOW: Duration = Duration::from_secs(20);
+
+/// Payload of `proxy-download://progress`
+#[derive(Clone, Serialize)]
+pub struct DownloadProgress {
+    pub id: String,
+    /// Bytes on disk, including a resumed partial file
+    pub bytes: u64,
+    pub total: Option<u64>,
+    /// Average speed of this session
+    pub bytes_per_sec: u64,
+    /// Size of the partial file the download resumed from
+    pub resumed_from: u64,
+    /// URL being fetched, a mirror's or the original
+    pub source: String,
+    pub done: bool,
+}
+
+/// Running downloads, keyed by download id
+#[derive(Default)]
+pub struct DownloadRegistry {
+    active: Mutex<HashMap<String, ActiveDownload>>,
+}
+
+struct ActiveDownload {
+    /// File name in the downloads dir, shared by every download of the same asset
+    name: String,
+    token: CancellationToken,
+}
+
+impl DownloadRegistry {
+    /// Claim `id` and the partial files of `name`; two downloads never write the same `.part`
+    fn register(&self, id: &str, name: &str) -> CommandResult<Registration<'_>> {
+        let mut active = self.active.lock().unwrap_or_else(|e| e.into_inner());
+        if active.contains_key(id) {
+            return Err(CommandError::General(format!("Download {} is already in progress", id)));
+        }
+        if let Some((other, _)) = active.iter().find(|(_, download)| download.name == name) {
+            return Err(CommandError::General(format!("{} is already being downloaded as {}", name, other)));
+        }
+        let token = CancellationToken::new();
+        active.insert(id.to_string(), ActiveDownload { name: name.to_string(), token: token.clone() });
+        Ok(Registration { registry: self, id: id.to_string(), token })
+    }
+
+    /// Cancel a running download, returning whether one was found
+    pub fn cancel(&self, id: &str) -> bool {
+        let active = self.active.lock().unwrap_or_else(|e| e.into_inner());
+        match active.get(id) {
+            Some(download) => {
+                download.token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Removes the download from the registry however it ends
+struct Registration<'a> {
+    registry: &'a DownloadRegistry,
+    id: String,
+    token: CancellationToken,
+}
+
+impl Drop for Registration<'_> {
+    fn drop(&mut self) {
+        let mut active = self.registry.active.lock().unwrap_or_else(|e| e.into_inner());
+        active.remove(&self.id);
+    }
+}
+
+/// Sidecar of a partial file, so a resume only happens against the same resource
+#[derive(Serialize, Deserialize)]
+struct PartialMeta {
+    url: String,
+    /// Mirror URL the bytes came from, `url` itself when missing
+    #[serde(default)]
+    source: Option<String>,
+    /// ETag or Last-Modified of the first response, sent back as `If-Range`
+    validator: Option<String>,
+}
+
+/// `~/.zerolimit/downloads`
+pub fn downloads_dir() -> Option<PathBuf> {
+    let mut dir = dirs::home_dir()?;
+    dir.push(".zerolimit");
+    dir.push("downloads");
+    Some(dir)
+}
+
+fn cancelled(id: &str) -> CommandError {
+    CommandError::General(format!("Download {} was cancelled", id))
+}
+
+/// A finished download and the source it came from
+pub struct Downloaded {
+    pub path: PathBuf,
+    pub source: Source,
+}
+
+/// Files of one download in the downloads dir
+struct Target {
+    name: String,
+    part_path: PathBuf,
+    meta_path: PathBuf,
+}
+
+/// Download `url` into the downloads dir and return the finished file
+///
+/// The sources from `mirror::candidates` are tried in turn; a source fails
+/// over to the next on an error, or when it stays below `MIN_THROUGHPUT`
+/// and another one is left. The partial file carries over between sources,
+/// the checksum check afterwards catches a mirror serving other bytes.
+/// A cancelled or failed download keeps its `.part` file for the next attempt.
+pub async fn download(
+    app: &AppHandle,
+    registry: &DownloadRegistry,
+    client: &reqwest::Client,
+    id: &str,
+    url: &str,
+    mirrors: &[String],
+) -> CommandResult<Downloaded> {
+    let name = super::asset_name(url);
+    let registration = registry.register(id, &name)?;
+    let token = registration.token.clone();
+
+    let dir = downloads_dir()
+        .ok_or_else(|| CommandError::General("Could not determine user home directory".to_string()))?;
+    fs::create_dir_all(&dir)
+        .map_err(|e| CommandError::General(format!("Failed to create downloads dir: {}", e)))?;
+    let final_path = dir.join(&name);
+    let target = Target {
+        part_path: dir.join(format!("{}.part", name)),
+        meta_path: dir.join(format!("{}.part.json", name)),
+        name,
+    };
+
+    let sources = mirror::candidates(mirrors);
+    let mut last_error = None;
+    for (index, source) in sources.iter().enumerate() {
+        let has_fallback = index + 1 < sources.len();
+        let source_url = source.apply(url);
+        if source.mirror().is_some() {
+            println!("Notice: Downloading {} via mirror: {}", target.name, source_url);
+        }
+
+        match fetch(app, &token, client, id, url, &source_url, &target, has_fallback).await {
+            Ok(()) => {
+                fs::rename(&target.part_path, &final_path)
+                    .map_err(|e| CommandError::General(format!("Failed to finish download: {}", e)))?;
+                let _ = fs::remove_file(&target.meta_path);
+                if !mirrors.is_empty() {
+                    mirror::remember(source);
+                }
+                return Ok(Downloaded { path: final_path, source: source.clone() });
+            }
+            Err(_) if token.is_cancelled() => return Err(cancelled(id)),
+            Err(e) => {
+                if has_fallback {
+                    println!("Notice: Download from {} failed: {}; trying the next source", source_url, e);
+                }
+                last_error = Some(e);
+            }
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| CommandError::General(format!("No source to download {} from", url))))
+}
+
+/// Fetch `source_url` into the partial file of `url`, resuming where possible
+#[allow(clippy::too_many_arguments)]
+async fn fetch(
+    app: &AppHandle,
+    token: &CancellationToken,
+    client: &reqwest::Client,
+    id: &str,
+    url: &str,
+    source_url: &str,
+    target: &Target,
+    has_fallback: bool,
+) -> CommandResult<()> {
+    let Target { name, part_path, meta_path } = target;
+
+    let meta = fs::read_to_string(meta_path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<PartialMeta>(&content).ok())
+        .filter(|meta| meta.url == url);
+    let mut offset = match meta {
+        Some(_) => fs::metadata(part_path).map(|m| m.len()).unwrap_or(0),
+        None => 0,
+    };
+    // Validators are per server, another mirror gets a plain range request
+    let validator = meta
+        .as_ref()
+        .filter(|meta| meta.source.as_deref().unwrap_or(&meta.url) == source_url)
+        .and_then(|meta| meta.validator.as_deref());
+
+    // A second round only happens when the server refuses the range
+    let (mut response, total) = loop {
+        let mut request = client.get(source_url);
+        if offset > 0 {
+            request = request.header(RANGE, format!("bytes={}-", offset));
+            if let Some(validator) = validator {
+                request = request.header(IF_RANGE, validator);
+            }
+        }
+
+        let response = tokio::select! {
+            response = request.send() => response
+                .map_err(|e| CommandError::General(format!("Failed to fetch URL: {}", e)))?,
+            _ = token.cancelled() => return Err(cancelled(id)),
+        };
+
+        match response.status() {
+            StatusCode::PARTIAL_CONTENT if offset > 0 && range_start(&response) == Some(offset) => {
+                let total = range_total(&response).or(response.content_length().map(|len| len + offset));
+                break (response, total);
+            }
+            StatusCode::PARTIAL_CONTENT | StatusCode::RANGE_NOT_SATISFIABLE if offset > 0 => {
+                // A range other than the one asked for would land at the wrong place in the file
+                println!("Notice: Server refused to resume {}, starting over", name);
+                let _ = fs::remove_file(part_path);
+                offset = 0;
+            }
+            StatusCode::PARTIAL_CONTENT => {
+                return Err(CommandError::General("Received partial content without asking for a range".to_string()));
+            }
+            status if status.is_success() => {
+                // Full body: the resource changed or ranges aren't supported
+                offset = 0;
+                let total = response.content_length();
+                break (response, total);
+            }
+            status => {
+                return Err(CommandError::General(format!("Received non-success status code: {}", status)));
+            }
+        }
+    };
+
+    if offset == 0 {
+        let validator = [ETAG, LAST_MODIFIED]
+            .iter()
+            .find_map(|header| response.headers().get(header))
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string());
+        let meta = PartialMeta { url: url.to_string(), source: Some(source_url.to_string()), validator };
+        if let Ok(json) = serde_json::to_string(&meta) {
+            let _ = fs::write(meta_path, json);
+        }
+    }
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(offset > 0)
+        .truncate(offset == 0)
+        .open(part_path)
+        .await
+        .map_err(|e| CommandError::General(format!("Failed to open {}: {}", part_path.display(), e)))?;
+
+    let resumed_from = offset;
+    let mut bytes = offset;
+    let started = Instant::now();
+    let mut last_emit = Instant::now();
+    let mut window = (Instant::now(), bytes);
+    let progress = |bytes: u64, done: bool| DownloadProgress {
+        id: id.to_string(),
+        bytes,
+        total,
+        bytes_per_sec: speed(bytes - resumed_from, started.elapsed()),
+        resumed_from,
+        source: source_url.to_string(),
+        done,
+    };
+
+    loop {
+        let chunk = tokio::select! {
+            chunk = response.chunk() => chunk
+                .map_err(|e| CommandError::General(format!("Failed to read response bytes: {}", e)))?,
+            _ = token.cancelled() => {
+                let _ = file.flush().await;
+                return Err(cancelled(id));
+            }
+        };
+        let Some(chunk) = chunk else {
+            break;
+        };
+        file.write_all(&chunk)
+            .await
+            .map_err(|e| CommandError::General(format!("Failed to write download: {}", e)))?;
+        bytes += chunk.len() as u64;
+
+        if last_emit.elapsed() >= PROGRESS_INTERVAL {
+            last_emit = Instant::now();
+            let _ = app.emit(EVENT_PROGRESS, progress(bytes, false));
+        }
+
+        if window.0.elapsed() >= THROUGHPUT_WINDOW {
+            let rate = speed(bytes - window.1, window.0.elapsed());
+            if has_fallback && rate < MIN_THROUGHPUT {
+                let _ = file.flush().await;
+                return Err(CommandError::General(format!("Too slow ({} bytes/s)", rate)));
+            }
+            window = (Instant::now(), bytes);
+        }
+    }
+    file.flush()
+        .await
+        .map_err(|e| CommandError::General(format!("Failed to write download: {}", e)))?;
+    drop(file);
+
+    if let Some(total) = total.filter(|total| *total != bytes) {
+        return Err(CommandError::General(format!("Download incomplete: got {} of {} bytes", bytes, total)));
+    }
+
+    let _ = app.emit(EVENT_PROGRESS, progress(bytes, true));
+    Ok(())
+}
+
+/// Drop a finished download once it is installed or rejected
+pub fn discard(path: &Path) {
+    let _ = fs::remove_file(path);
+}
+
+fn speed(bytes: u64, elapsed: Duration) -> u64 {
+    let secs = elapsed.as_secs_f64();
+    if secs > 0.0 { (bytes as f64 / secs) as u64 } else { 0 }
+}
+
+/// `a` from `Content-Range: bytes a-b/total`
+fn range_start(response: &reqwest::Response) -> Option<u64> {
+    let value = response.headers().get(CONTENT_RANGE)?.to_str().ok()?;
+    value.strip_prefix("bytes ")?.split('-').next()?.trim().parse().ok()
+}
+
+/// `total` from `Content-Range: bytes a-b/total`
+fn range_total(response: &reqwest::Response) -> Option<u64> {
+    let value = response.headers().get(CONTENT_RANGE)?.to_str().ok()?;
+    value.rsplit('/').next()?.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refuses_a_second_download_of_the_same_file() {
+        let registry = DownloadRegistry::default();
+        let first = registry.register("a", "proxy.zip").unwrap();
+
+        assert!(registry.register("a", "other.zip").is_err());
+        assert!(registry.register("b", "proxy.zip").is_err());
+        assert!(registry.register("b", "other.zip").is_ok());
+
+        drop(first);
+        assert!(registry.register("b", "proxy.zip").is_ok());
+    }
+
+    #[test]
+    fn cancels_by_id() {
+        let registry = DownloadRegistry::default();
+        let registration = registry.register("a", "proxy.zip").unwrap();
+
+        assert!(!registry.cancel("b"));
+        assert!(registry.cancel("a"));
+        assert!(registration.token.is_cancelled());
+    }
+}
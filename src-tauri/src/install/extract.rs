@@ -0,0 +1,629 @@
+//! Archive extraction into a staging directory
+//!
+//! Release archives are treated as untrusted input. Entries with absolute
+//! paths or `..` components are rejected, as are symlinks and hardlinks that
+//! resolve outside the destination, and nothing is ever written through a
+//! link. Entry count and unpacked size are capped against archive bombs, and
+//! permissions are normalized to 0755/0644 instead of taken from the archive.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+
+use crate::error::{CommandError, CommandResult};
+
+/// More entries than any release has ever shipped, by two orders of magnitude
+pub const MAX_ENTRIES: usize = 10_000;
+/// Releases unpack to a few tens of MB
+pub const MAX_UNPACKED_BYTES: u64 = 1024 * 1024 * 1024;
+/// Symlink targets are short paths; anything longer is not a link we want
+const MAX_LINK_TARGET_BYTES: u64 = 4096;
+
+#[derive(Clone, Copy, Debug)]
+struct Limits {
+    entries: usize,
+    bytes: u64,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self { entries: MAX_ENTRIES, bytes: MAX_UNPACKED_BYTES }
+    }
+}
+
+/// Extract a `.zip` or `.tar.gz` release archive into an empty directory
+///
+/// The format is taken from `asset_name`, the archive's name in the release.
+pub fn extract(archive_path: &Path, asset_name: &str, dest: &Path) -> CommandResult<()> {
+    extract_with_limits(archive_path, asset_name, dest, Limits::default())
+}
+
+fn extract_with_limits(archive_path: &Path, asset_name: &str, dest: &Path, limits: Limits) -> CommandResult<()> {
+    let lower = asset_name.to_lowercase();
+    let file = File::open(archive_path)
+        .map_err(|e| CommandError::General(format!("Failed to open archive: {}", e)))?;
+    let mut extractor = Extractor { dest, limits, entries: 0, bytes: 0 };
+
+    if lower.ends_with(".zip") {
+        extract_zip(file, &mut extractor)
+    } else if lower.ends_with(".tar.gz") || lower.ends_with(".tgz") {
+        extract_tar(flate2::read::GzDecoder::new(file), &mut extractor)
+    } else {
+        Err(CommandError::General(format!("Unsupported file extension: {}", asset_name)))
+    }
+}
+
+fn extract_zip(file: File, extractor: &mut Extractor) -> CommandResult<()> {
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|e| CommandError::General(format!("Failed to read zip archive: {}", e)))?;
+    // The central directory tells us up front, so don't unpack anything of an oversized archive
+    if archive.len() > extractor.limits.entries {
+        return Err(too_many_entries(extractor.limits));
+    }
+
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)
+            .map_err(|e| CommandError::General(format!("Failed to access zip entry: {}", e)))?;
+        extractor.count_entry()?;
+
+        // Zips made on Windows may use backslashes as separators
+        let name = file.name().replace('\\', "/");
+        if name.contains('\0') {
+            return Err(unsafe_entry(Path::new(&name.replace('\0', "")), "contains a NUL byte"));
+        }
+        let rel = entry_path(Path::new(&name))?;
+
+        if file.is_dir() {
+            extractor.dir(&rel)?;
+        } else if file.is_symlink() {
+            let mut target = String::new();
+            file.by_ref()
+                .take(MAX_LINK_TARGET_BYTES)
+                .read_to_string(&mut target)
+                .map_err(|e| CommandError::General(format!("Failed to read zip entry: {}", e)))?;
+            extractor.symlink(&rel, Path::new(&target))?;
+        } else {
+            let executable = file.unix_mode().is_some_and(|mode| mode & 0o111 != 0);
+            extractor.file(&rel, &mut file, executable)?;
+        }
+    }
+    Ok(())
+}
+
+fn extract_tar(reader: impl Read, extractor: &mut Extractor) -> CommandResult<()> {
+    let read_error = |e: io::Error| CommandError::General(format!("Failed to unpack tarball: {}", e));
+    let mut archive = tar::Archive::new(reader);
+
+    for entry in archive.entries().map_err(read_error)? {
+        let mut entry = entry.map_err(read_error)?;
+        let kind = entry.header().entry_type();
+        // Metadata records describe the next entry and are not extracted themselves
+        if matches!(
+            kind,
+            tar::EntryType::XHeader | tar::EntryType::XGlobalHeader | tar::EntryType::GNULongName | tar::EntryType::GNULongLink
+        ) {
+            continue;
+        }
+        extractor.count_entry()?;
+
+        let name = entry.path().map_err(read_error)?.into_owned();
+        let rel = entry_path(&name)?;
+        let link_target = || -> CommandResult<PathBuf> {
+            entry.link_name()
+                .map_err(read_error)?
+                .map(|target| target.into_owned())
+                .ok_or_else(|| unsafe_entry(&name, "is a link without a target"))
+        };
+
+        match kind {
+            tar::EntryType::Directory => extractor.dir(&rel)?,
+            tar::EntryType::Symlink => {
+                let target = link_target()?;
+                extractor.symlink(&rel, &target)?;
+            }
+            tar::EntryType::Link => {
+                let target = link_target()?;
+                extractor.hardlink(&rel, &target)?;
+            }
+            tar::EntryType::Regular | tar::EntryType::Continuous => {
+                let executable = entry.header().mode().is_ok_and(|mode| mode & 0o111 != 0);
+                extractor.file(&rel, &mut entry, executable)?;
+            }
+            other => return Err(unsafe_entry(&name, &format!("has unsupported type {:?}", other))),
+        }
+    }
+    Ok(())
+}
+
+/// Writes entries below `dest`, keeping count of what was unpacked
+struct Extractor<'a> {
+    dest: &'a Path,
+    limits: Limits,
+    entries: usize,
+    bytes: u64,
+}
+
+impl Extractor<'_> {
+    fn count_entry(&mut self) -> CommandResult<()> {
+        self.entries += 1;
+        if self.entries > self.limits.entries {
+            return Err(too_many_entries(self.limits));
+        }
+        Ok(())
+    }
+
+    fn dir(&mut self, rel: &Path) -> CommandResult<()> {
+        // `./` at the top of many tarballs is the destination itself
+        if rel.as_os_str().is_empty() {
+            return Ok(());
+        }
+        let path = self.prepare(rel, false)?;
+        if !path.is_dir() {
+            fs::create_dir(&path).map_err(|e| write_error(&path, e))?;
+        }
+        set_mode(&path, 0o755);
+        Ok(())
+    }
+
+    fn file(&mut self, rel: &Path, reader: &mut dyn Read, executable: bool) -> CommandResult<()> {
+        let path = self.prepare(rel, false)?;
+        let mut out = create_new(&path)?;
+
+        // Sizes in headers can lie, so count what actually comes out of the decompressor
+        let remaining = self.limits.bytes - self.bytes;
+        let written = io::copy(&mut reader.take(remaining + 1), &mut out)
+            .map_err(|e| CommandError::General(format!("Failed to write extracted file: {}", e)))?;
+        self.add_bytes(written)?;
+
+        set_mode(&path, if executable { 0o755 } else { 0o644 });
+        Ok(())
+    }
+
+    fn symlink(&mut self, rel: &Path, target: &Path) -> CommandResult<()> {
+        if !link_stays_inside(rel, target) {
+            return Err(unsafe_entry(rel, &format!("links to {} outside the archive", target.display())));
+        }
+        let path = self.prepare(rel, false)?;
+
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(target, &path).map_err(|e| write_error(&path, e))?;
+        // Creating symlinks needs extra privileges on Windows, and releases don't rely on them
+        #[cfg(not(unix))]
+        {
+            let _ = path;
+            println!("Notice: Skipping symlink {} in archive", rel.display());
+        }
+
+        Ok(())
+    }
+
+    fn hardlink(&mut self, rel: &Path, target: &Path) -> CommandResult<()> {
+        // Hardlink targets name an earlier entry of the archive, not a path relative to the link
+        let source_rel = entry_path(target)
+            .map_err(|_| unsafe_entry(rel, &format!("links to {} outside the archive", target.display())))?;
+        let source = self.prepare(&source_rel, true)?;
+        let is_regular_file = fs::symlink_metadata(&source).is_ok_and(|m| m.file_type().is_file());
+        if !is_regular_file {
+            return Err(unsafe_entry(rel, &format!("links to {}, which is not an extracted file", target.display())));
+        }
+
+        let path = self.prepare(rel, false)?;
+        let size = fs::metadata(&source).map(|m| m.len()).unwrap_or(0);
+        // Falls back to a copy on filesystems without hardlinks, so count it like one
+        self.add_bytes(size)?;
+        if fs::hard_link(&source, &path).is_err() {
+            fs::copy(&source, &path).map_err(|e| write_error(&path, e))?;
+        }
+        Ok(())
+    }
+
+    fn add_bytes(&mut self, bytes: u64) -> CommandResult<()> {
+        self.bytes += bytes;
+        if self.bytes > self.limits.bytes {
+            return Err(CommandError::General(format!(
+                "Archive unpacks to more than {} MB, refusing to extract it",
+                self.limits.bytes / (1024 * 1024)
+            )));
+        }
+        Ok(())
+    }
+
+    /// Path for `rel` below the destination, with its parent dirs created
+    ///
+    /// Fails if any parent is a symlink, so later entries can't be written
+    /// through a link an earlier entry planted. An existing non-directory at
+    /// the path itself is removed unless `keep` is set, so the new entry
+    /// replaces a link instead of following it.
+    fn prepare(&self, rel: &Path, keep: bool) -> CommandResult<PathBuf> {
+        if rel.as_os_str().is_empty() {
+            return Err(unsafe_entry(rel, "has an empty name"));
+        }
+
+        let mut path = self.dest.to_path_buf();
+        let mut components = rel.components().peekable();
+        while let Some(component) = components.next() {
+            path.push(component);
+            let is_leaf = components.peek().is_none();
+            match fs::symlink_metadata(&path) {
+                Ok(m) if is_leaf => {
+                    if !keep && !m.is_dir() {
+                        fs::remove_file(&path).map_err(|e| write_error(&path, e))?;
+                    }
+                }
+                Ok(m) if m.file_type().is_symlink() => {
+                    return Err(unsafe_entry(rel, "would be written through a symlink"));
+                }
+                Ok(m) if !m.is_dir() => {
+                    return Err(unsafe_entry(rel, "is nested below a file"));
+                }
+                Ok(_) => {}
+                Err(_) if is_leaf => {}
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                    fs::create_dir(&path).map_err(|e| write_error(&path, e))?;
+                    set_mode(&path, 0o755);
+                }
+                Err(e) => return Err(write_error(&path, e)),
+            }
+        }
+        Ok(path)
+    }
+}
+
+/// Entry name as a relative path, rejecting absolute paths and `..`
+///
+/// An empty result means the archive root (`./`).
+fn entry_path(name: &Path) -> CommandResult<PathBuf> {
+    let mut path = PathBuf::new();
+    for component in name.components() {
+        match component {
+            Component::Normal(part) => path.push(part),
+            Component::CurDir => {}
+            Component::ParentDir => return Err(unsafe_entry(name, "contains '..'")),
+            Component::RootDir | Component::Prefix(_) => return Err(unsafe_entry(name, "is an absolute path")),
+        }
+    }
+    Ok(path)
+}
+
+/// Whether a symlink at `rel` pointing to `target` resolves inside the destination
+///
+/// `..` is only accepted at the start of the target, where it climbs the
+/// real directories holding the link. After a name it would climb out of
+/// whatever that name resolves to, which may itself be a link.
+fn link_stays_inside(rel: &Path, target: &Path) -> bool {
+    if target.as_os_str().is_empty() {
+        return false;
+    }
+    let mut depth = rel.components().count().saturating_sub(1);
+    let mut leading = true;
+    for component in target.components() {
+        match component {
+            Component::ParentDir if leading => match depth.checked_sub(1) {
+                Some(parent) => depth = parent,
+                None => return false,
+            },
+            Component::CurDir => {}
+            Component::Normal(_) => leading = false,
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return false,
+        }
+    }
+    true
+}
+
+/// Create a file that must not exist yet, so a link raced into its place is never followed
+fn create_new(path: &Path) -> CommandResult<File> {
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .map_err(|e| CommandError::General(format!("Failed to create extracted file {:?}: {}", path, e)))
+}
+
+fn set_mode(path: &Path, mode: u32) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = fs::set_permissions(path, fs::Permissions::from_mode(mode));
+    }
+    #[cfg(not(unix))]
+    let _ = (path, mode);
+}
+
+fn unsafe_entry(name: &Path, reason: &str) -> CommandError {
+    CommandError::General(format!("Refusing to extract archive: entry {} {}", name.display(), reason))
+}
+
+fn too_many_entries(limits: Limits) -> CommandError {
+    CommandError::General(format!(
+        "Archive has more than {} entries, refusing to extract it",
+        limits.entries
+    ))
+}
+
+fn write_error(path: &Path, e: io::Error) -> CommandError {
+    CommandError::General(format!("Failed to extract {:?}: {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+
+    struct Fixture {
+        dir: tempfile::TempDir,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let dir = tempfile::tempdir().unwrap();
+            fs::create_dir(dir.path().join("dest")).unwrap();
+            Self { dir }
+        }
+
+        fn dest(&self) -> PathBuf {
+            self.dir.path().join("dest")
+        }
+
+        /// A path next to the destination that no entry may reach
+        fn outside(&self, name: &str) -> PathBuf {
+            self.dir.path().join(name)
+        }
+
+        fn extract(&self, asset_name: &str, bytes: &[u8], limits: Limits) -> CommandResult<()> {
+            let archive = self.dir.path().join(asset_name);
+            fs::write(&archive, bytes).unwrap();
+            extract_with_limits(&archive, asset_name, &self.dest(), limits)
+        }
+    }
+
+    /// Append a tar entry with its name written raw, bypassing the builder's own path checks
+    fn tar_entry(builder: &mut tar::Builder<Vec<u8>>, name: &str, kind: tar::EntryType, link: Option<&str>, data: &[u8]) {
+        let mut header = tar::Header::new_gnu();
+        header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+        header.set_entry_type(kind);
+        if let Some(link) = link {
+            header.set_link_name_literal(link).unwrap();
+        }
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append(&header, data).unwrap();
+    }
+
+    fn tar_gz(build: impl FnOnce(&mut tar::Builder<Vec<u8>>)) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        build(&mut builder);
+        let tar = builder.into_inner().unwrap();
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&tar).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn zip(build: impl FnOnce(&mut zip::ZipWriter<Cursor<Vec<u8>>>)) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        build(&mut writer);
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn zip_options(mode: u32) -> zip::write::SimpleFileOptions {
+        zip::write::SimpleFileOptions::default().unix_permissions(mode)
+    }
+
+    #[test]
+    fn extracts_a_regular_release() {
+        let fixture = Fixture::new();
+        let archive = tar_gz(|b| {
+            tar_entry(b, "./", tar::EntryType::Directory, None, b"");
+            tar_entry(b, "cli-proxy-api", tar::EntryType::Regular, None, b"binary");
+            tar_entry(b, "docs/README.md", tar::EntryType::Regular, None, b"readme");
+        });
+        fixture.extract("release.tar.gz", &archive, Limits::default()).unwrap();
+        assert_eq!(fs::read(fixture.dest().join("cli-proxy-api")).unwrap(), b"binary");
+        assert_eq!(fs::read(fixture.dest().join("docs/README.md")).unwrap(), b"readme");
+    }
+
+    #[test]
+    fn tar_rejects_parent_dir_entries() {
+        let fixture = Fixture::new();
+        let archive = tar_gz(|b| tar_entry(b, "../evil", tar::EntryType::Regular, None, b"pwned"));
+        assert!(fixture.extract("release.tar.gz", &archive, Limits::default()).is_err());
+        assert!(!fixture.outside("evil").exists());
+    }
+
+    #[test]
+    fn tar_rejects_absolute_entries() {
+        let fixture = Fixture::new();
+        let target = fixture.outside("evil");
+        let archive = tar_gz(|b| tar_entry(b, target.to_str().unwrap(), tar::EntryType::Regular, None, b"pwned"));
+        assert!(fixture.extract("release.tar.gz", &archive, Limits::default()).is_err());
+        assert!(!target.exists());
+    }
+
+    #[test]
+    fn tar_rejects_symlink_escaping_the_destination() {
+        let fixture = Fixture::new();
+        let archive = tar_gz(|b| {
+            tar_entry(b, "link", tar::EntryType::Symlink, Some("../"), b"");
+            tar_entry(b, "link/evil", tar::EntryType::Regular, None, b"pwned");
+        });
+        assert!(fixture.extract("release.tar.gz", &archive, Limits::default()).is_err());
+        assert!(!fixture.outside("evil").exists());
+    }
+
+    #[test]
+    fn tar_rejects_absolute_symlink() {
+        let fixture = Fixture::new();
+        let archive = tar_gz(|b| tar_entry(b, "cli-proxy-api", tar::EntryType::Symlink, Some("/bin/sh"), b""));
+        assert!(fixture.extract("release.tar.gz", &archive, Limits::default()).is_err());
+    }
+
+    #[test]
+    fn tar_rejects_symlink_climbing_out_through_another_link() {
+        // `here -> .` is harmless, but `here/..` resolves to the destination's parent
+        let fixture = Fixture::new();
+        let archive = tar_gz(|b| {
+            tar_entry(b, "here", tar::EntryType::Symlink, Some("."), b"");
+            tar_entry(b, "escape", tar::EntryType::Symlink, Some("here/../evil"), b"");
+        });
+        assert!(fixture.extract("release.tar.gz", &archive, Limits::default()).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn tar_refuses_to_write_through_an_inner_symlink() {
+        let fixture = Fixture::new();
+        let archive = tar_gz(|b| {
+            tar_entry(b, "bin/", tar::EntryType::Directory, None, b"");
+            tar_entry(b, "alias", tar::EntryType::Symlink, Some("bin"), b"");
+            tar_entry(b, "alias/cli-proxy-api", tar::EntryType::Regular, None, b"binary");
+        });
+        assert!(fixture.extract("release.tar.gz", &archive, Limits::default()).is_err());
+        assert!(!fixture.dest().join("bin/cli-proxy-api").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn tar_replaces_a_symlink_instead_of_following_it() {
+        let fixture = Fixture::new();
+        fs::write(fixture.outside("victim"), b"original").unwrap();
+        let archive = tar_gz(|b| {
+            tar_entry(b, "bin/cli-proxy-api", tar::EntryType::Regular, None, b"binary");
+            tar_entry(b, "cli-proxy-api", tar::EntryType::Symlink, Some("bin/cli-proxy-api"), b"");
+            tar_entry(b, "cli-proxy-api", tar::EntryType::Regular, None, b"replaced");
+        });
+        fixture.extract("release.tar.gz", &archive, Limits::default()).unwrap();
+        assert_eq!(fs::read(fixture.dest().join("bin/cli-proxy-api")).unwrap(), b"binary");
+        assert_eq!(fs::read(fixture.dest().join("cli-proxy-api")).unwrap(), b"replaced");
+        assert_eq!(fs::read(fixture.outside("victim")).unwrap(), b"original");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn tar_keeps_symlinks_inside_the_destination() {
+        let fixture = Fixture::new();
+        let archive = tar_gz(|b| {
+            tar_entry(b, "bin/cli-proxy-api", tar::EntryType::Regular, None, b"binary");
+            tar_entry(b, "docs/proxy", tar::EntryType::Symlink, Some("../bin/cli-proxy-api"), b"");
+        });
+        fixture.extract("release.tar.gz", &archive, Limits::default()).unwrap();
+        assert_eq!(fs::read(fixture.dest().join("docs/proxy")).unwrap(), b"binary");
+    }
+
+    #[test]
+    fn tar_rejects_hardlink_outside_the_destination() {
+        let fixture = Fixture::new();
+        fs::write(fixture.outside("secret"), b"secret").unwrap();
+        let archive = tar_gz(|b| tar_entry(b, "leak", tar::EntryType::Link, Some("../secret"), b""));
+        assert!(fixture.extract("release.tar.gz", &archive, Limits::default()).is_err());
+        assert!(!fixture.dest().join("leak").exists());
+    }
+
+    #[test]
+    fn tar_hardlinks_earlier_entries() {
+        let fixture = Fixture::new();
+        let archive = tar_gz(|b| {
+            tar_entry(b, "cli-proxy-api", tar::EntryType::Regular, None, b"binary");
+            tar_entry(b, "cli-proxy-api-copy", tar::EntryType::Link, Some("cli-proxy-api"), b"");
+        });
+        fixture.extract("release.tar.gz", &archive, Limits::default()).unwrap();
+        assert_eq!(fs::read(fixture.dest().join("cli-proxy-api-copy")).unwrap(), b"binary");
+    }
+
+    #[test]
+    fn tar_caps_unpacked_size() {
+        let fixture = Fixture::new();
+        let archive = tar_gz(|b| tar_entry(b, "bomb", tar::EntryType::Regular, None, &vec![0; 2 * 1024 * 1024]));
+        let limits = Limits { bytes: 1024 * 1024, ..Limits::default() };
+        assert!(fixture.extract("release.tar.gz", &archive, limits).is_err());
+        let written = fs::metadata(fixture.dest().join("bomb")).map(|m| m.len()).unwrap_or(0);
+        assert!(written <= 1024 * 1024 + 1);
+    }
+
+    #[test]
+    fn tar_caps_entry_count() {
+        let fixture = Fixture::new();
+        let archive = tar_gz(|b| {
+            for i in 0..20 {
+                tar_entry(b, &format!("file-{}", i), tar::EntryType::Regular, None, b"x");
+            }
+        });
+        let limits = Limits { entries: 10, ..Limits::default() };
+        assert!(fixture.extract("release.tar.gz", &archive, limits).is_err());
+    }
+
+    #[test]
+    fn zip_rejects_parent_dir_entries() {
+        let fixture = Fixture::new();
+        let archive = zip(|w| {
+            w.start_file("../evil", zip_options(0o644)).unwrap();
+            w.write_all(b"pwned").unwrap();
+        });
+        assert!(fixture.extract("release.zip", &archive, Limits::default()).is_err());
+        assert!(!fixture.outside("evil").exists());
+    }
+
+    #[test]
+    fn zip_rejects_backslash_traversal() {
+        let fixture = Fixture::new();
+        let archive = zip(|w| {
+            w.start_file("..\\evil", zip_options(0o644)).unwrap();
+            w.write_all(b"pwned").unwrap();
+        });
+        assert!(fixture.extract("release.zip", &archive, Limits::default()).is_err());
+        assert!(!fixture.outside("evil").exists());
+    }
+
+    #[test]
+    fn zip_rejects_symlink_escaping_the_destination() {
+        let fixture = Fixture::new();
+        let archive = zip(|w| {
+            w.add_symlink("cli-proxy-api", "../../usr/bin/env", zip_options(0o777)).unwrap();
+        });
+        assert!(fixture.extract("release.zip", &archive, Limits::default()).is_err());
+        assert!(fs::symlink_metadata(fixture.dest().join("cli-proxy-api")).is_err());
+    }
+
+    #[test]
+    fn zip_caps_unpacked_size() {
+        let fixture = Fixture::new();
+        let archive = zip(|w| {
+            let options = zip_options(0o644).compression_method(zip::CompressionMethod::Deflated);
+            w.start_file("bomb", options).unwrap();
+            w.write_all(&vec![0; 2 * 1024 * 1024]).unwrap();
+        });
+        assert!(archive.len() < 64 * 1024);
+        let limits = Limits { bytes: 1024 * 1024, ..Limits::default() };
+        assert!(fixture.extract("release.zip", &archive, limits).is_err());
+    }
+
+    #[test]
+    fn zip_caps_entry_count() {
+        let fixture = Fixture::new();
+        let archive = zip(|w| {
+            for i in 0..20 {
+                w.start_file(format!("file-{}", i), zip_options(0o644)).unwrap();
+            }
+        });
+        let limits = Limits { entries: 10, ..Limits::default() };
+        assert!(fixture.extract("release.zip", &archive, limits).is_err());
+        assert_eq!(fs::read_dir(fixture.dest()).unwrap().count(), 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn zip_permissions_are_normalized() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let fixture = Fixture::new();
+        let archive = zip(|w| {
+            w.start_file("cli-proxy-api", zip_options(0o6777)).unwrap();
+            w.write_all(b"binary").unwrap();
+            w.start_file("config.example.yaml", zip_options(0o666)).unwrap();
+            w.write_all(b"port: 8317").unwrap();
+        });
+        fixture.extract("release.zip", &archive, Limits::default()).unwrap();
+        let mode = |name: &str| fs::metadata(fixture.dest().join(name)).unwrap().permissions().mode() & 0o7777;
+        assert_eq!(mode("cli-proxy-api"), 0o755);
+        assert_eq!(mode("config.example.yaml"), 0o644);
+    }
+}
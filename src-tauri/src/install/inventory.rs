@@ -0,0 +1,356 @@
+//! Inventory of installed proxy builds
+//!
+//! Every version dir gets a `manifest.json` at install time. Builds without
+//! one (and a binary from the old flat layout) are described by running them
+//! with `--version` once; the old layout's binary is described in
+//! `legacy-manifest.json` in the install root until it changes.
+
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use super::versions::{self, InstalledBuild, LEGACY_TAG};
+
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+
+// Windows: CREATE_NO_WINDOW flag to hide console
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+pub const LEGACY_MANIFEST_FILE_NAME: &str = "legacy-manifest.json";
+/// A binary that ignores `--version` and starts serving is killed after this
+const VERSION_PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Edition {
+    Standard,
+    Plus,
+}
+
+impl Edition {
+    /// Plus builds carry "plus" in their asset or binary name
+    pub fn from_name(name: &str) -> Self {
+        if name.to_lowercase().contains("plus") { Edition::Plus } else { Edition::Standard }
+    }
+}
+
+/// Written next to each versioned build at install time
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Manifest {
+    pub edition: Edition,
+    pub version: Option<String>,
+    pub tag: String,
+    pub arch: String,
+    /// Seconds since the Unix epoch
+    pub installed_at: u64,
+    pub asset_name: String,
+    /// Binary path relative to the version dir
+    pub exe: String,
+}
+
+/// Entry returned by `list_installed_proxies`
+#[derive(Clone, Debug, Serialize)]
+pub struct InstalledProxy {
+    /// Version dir name, or `legacy` for a binary in the install root
+    pub id: String,
+    pub edition: Edition,
+    pub version: Option<String>,
+    pub tag: String,
+    pub arch: String,
+    pub installed_at: Option<u64>,
+    pub size_bytes: u64,
+    pub exe_path: String,
+    pub current: bool,
+    pub previous: bool,
+}
+
+impl InstalledProxy {
+    pub fn build(&self) -> InstalledBuild {
+        InstalledBuild { tag: self.tag.clone(), exe_path: self.exe_path.clone() }
+    }
+}
+
+/// Record a freshly committed build
+pub fn write_manifest(build_dir: &Path, tag: &str, asset_name: &str, exe: &Path) {
+    let manifest = Manifest {
+        edition: Edition::from_name(asset_name),
+        version: version_from_tag(tag).or_else(|| probe_version(exe)),
+        tag: tag.to_string(),
+        arch: arch_from_name(asset_name).unwrap_or(std::env::consts::ARCH).to_string(),
+        installed_at: now_secs(),
+        asset_name: asset_name.to_string(),
+        exe: exe.strip_prefix(build_dir).unwrap_or(exe).to_string_lossy().to_string(),
+    };
+    save_manifest(&build_dir.join(MANIFEST_FILE_NAME), &manifest);
+}
+
+/// All builds under `root`, newest version first
+pub fn list(root: &Path) -> Vec<InstalledProxy> {
+    let pointer = versions::read_pointer(root);
+    let is_current = |exe: &str| pointer.current.as_ref().is_some_and(|b| b.exe_path == exe);
+    let is_previous = |exe: &str| pointer.previous.as_ref().is_some_and(|b| b.exe_path == exe);
+
+    let mut builds: Vec<InstalledProxy> = fs::read_dir(versions::versions_dir(root))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir() && !is_hidden(path))
+        .filter_map(|dir| describe_version_dir(&dir))
+        .collect();
+
+    if let Some((exe, manifest)) = super::find_proxy_exe(root, false)
+        .and_then(|exe| legacy_manifest(root, &exe).map(|manifest| (exe, manifest)))
+    {
+        builds.push(InstalledProxy {
+            id: LEGACY_TAG.to_string(),
+            edition: manifest.edition,
+            version: manifest.version,
+            tag: LEGACY_TAG.to_string(),
+            arch: manifest.arch,
+            installed_at: Some(manifest.installed_at),
+            size_bytes: fs::metadata(&exe).map(|m| m.len()).unwrap_or(0),
+            exe_path: exe.to_string_lossy().to_string(),
+            current: false,
+            previous: false,
+        });
+    }
+
+    for build in &mut builds {
+        build.current = is_current(&build.exe_path);
+        build.previous = is_previous(&build.exe_path);
+    }
+    builds.sort_by(|a, b| {
+        compare_versions(b.version.as_deref(), a.version.as_deref())
+            .then(b.installed_at.cmp(&a.installed_at))
+    });
+    builds
+}
+
+/// Look up a build by id
+pub fn find(root: &Path, id: &str) -> Option<InstalledProxy> {
+    list(root).into_iter().find(|build| build.id == id)
+}
+
+/// Delete a build that is not current; callers check for running instances
+pub fn remove(root: &Path, build: &InstalledProxy) -> Result<(), String> {
+    if build.current {
+        return Err(format!("{} is the current version", build.id));
+    }
+    if build.id == LEGACY_TAG {
+        fs::remove_file(&build.exe_path).map_err(|e| e.to_string())?;
+        let _ = fs::remove_file(root.join(LEGACY_MANIFEST_FILE_NAME));
+    } else {
+        let dir = versions::versions_dir(root).join(&build.id);
+        fs::remove_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    if build.previous {
+        versions::forget_previous(root).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn describe_version_dir(dir: &Path) -> Option<InstalledProxy> {
+    let id = dir.file_name()?.to_str()?.to_string();
+    let manifest = read_manifest(&dir.join(MANIFEST_FILE_NAME)).or_else(|| {
+        // Installed before manifests existed: describe it once and remember
+        let exe = super::find_proxy_exe(dir, true)?;
+        let name = exe.file_name()?.to_str()?.to_string();
+        let manifest = Manifest {
+            edition: Edition::from_name(&name),
+            version: probe_version(&exe).or_else(|| version_from_tag(&id)),
+            tag: id.clone(),
+            arch: std::env::consts::ARCH.to_string(),
+            installed_at: fs::metadata(dir).ok()?.modified().ok().and_then(secs_since_epoch).unwrap_or(0),
+            asset_name: name,
+            exe: exe.strip_prefix(dir).ok()?.to_string_lossy().to_string(),
+        };
+        save_manifest(&dir.join(MANIFEST_FILE_NAME), &manifest);
+        Some(manifest)
+    })?;
+
+    let exe_path = dir.join(&manifest.exe);
+    if !exe_path.is_file() {
+        return None;
+    }
+    Some(InstalledProxy {
+        id,
+        edition: manifest.edition,
+        version: manifest.version,
+        tag: manifest.tag,
+        arch: manifest.arch,
+        installed_at: Some(manifest.installed_at),
+        size_bytes: dir_size(dir),
+        exe_path: exe_path.to_string_lossy().to_string(),
+        current: false,
+        previous: false,
+    })
+}
+
+/// Description of the old layout's binary, probed again only when it was replaced
+fn legacy_manifest(root: &Path, exe: &Path) -> Option<Manifest> {
+    let name = exe.file_name()?.to_str()?.to_string();
+    let modified = fs::metadata(exe).ok()?.modified().ok().and_then(secs_since_epoch).unwrap_or(0);
+    let path = root.join(LEGACY_MANIFEST_FILE_NAME);
+    let stored = read_manifest(&path).filter(|manifest| manifest.exe == name && manifest.installed_at == modified);
+    if stored.is_some() {
+        return stored;
+    }
+
+    let manifest = Manifest {
+        edition: Edition::from_name(&name),
+        version: probe_version(exe),
+        tag: LEGACY_TAG.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        installed_at: modified,
+        asset_name: name.clone(),
+        exe: name,
+    };
+    save_manifest(&path, &manifest);
+    Some(manifest)
+}
+
+fn read_manifest(path: &Path) -> Option<Manifest> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn save_manifest(path: &Path, manifest: &Manifest) {
+    let result = serde_json::to_string_pretty(manifest)
+        .map_err(|e| e.to_string())
+        .and_then(|json| fs::write(path, json).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        println!("Notice: Could not write install manifest {}: {}", path.display(), e);
+    }
+}
+
+/// Version printed by `<exe> --version`
+pub fn probe_version(exe: &Path) -> Option<String> {
+    let mut command = Command::new(exe);
+    command
+        .arg("--version")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    #[cfg(windows)]
+    command.creation_flags(CREATE_NO_WINDOW);
+
+    let mut child = command.spawn().ok()?;
+    let deadline = Instant::now() + VERSION_PROBE_TIMEOUT;
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(50)),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    }
+
+    let mut output = String::new();
+    if let Some(mut stdout) = child.stdout.take() {
+        let _ = stdout.read_to_string(&mut output);
+    }
+    if let Some(mut stderr) = child.stderr.take() {
+        let _ = stderr.read_to_string(&mut output);
+    }
+    output.split_whitespace().find_map(version_from_tag)
+}
+
+/// `6.1.2` from `v6.1.2`, `Version: 6.1.2,` and the like
+pub fn version_from_tag(text: &str) -> Option<String> {
+    let trimmed = text.trim_matches(|c: char| !c.is_ascii_alphanumeric());
+    let version = trimmed.strip_prefix('v').unwrap_or(trimmed);
+    let mut parts = version.split('.');
+    let is_number = |part: Option<&str>| part.is_some_and(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()));
+    (is_number(parts.next()) && is_number(parts.next().map(|p| p.split('-').next().unwrap_or(p))))
+        .then(|| version.to_string())
+}
+
+/// Compare dotted versions numerically; unknown versions sort last
+pub fn compare_versions(a: Option<&str>, b: Option<&str>) -> std::cmp::Ordering {
+    let key = |v: &str| -> Vec<u64> {
+        v.split(['.', '-'])
+            .map_while(|part| part.parse().ok())
+            .collect()
+    };
+    match (a, b) {
+        (Some(a), Some(b)) => key(a).cmp(&key(b)),
+        (Some(_), None) => std::cmp::Ordering::Greater,
+        (None, Some(_)) => std::cmp::Ordering::Less,
+        (None, None) => std::cmp::Ordering::Equal,
+    }
+}
+
+fn arch_from_name(name: &str) -> Option<&'static str> {
+    let lower = name.to_lowercase();
+    if lower.contains("arm64") || lower.contains("aarch64") {
+        Some("aarch64")
+    } else if lower.contains("amd64") || lower.contains("x86_64") || lower.contains("x64") {
+        Some("x86_64")
+    } else {
+        None
+    }
+}
+
+fn dir_size(dir: &Path) -> u64 {
+    let mut total = 0;
+    let mut stack: Vec<PathBuf> = vec![dir.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        for entry in fs::read_dir(&dir).into_iter().flatten().flatten() {
+            match entry.metadata() {
+                Ok(m) if m.is_dir() => stack.push(entry.path()),
+                Ok(m) => total += m.len(),
+                Err(_) => {}
+            }
+        }
+    }
+    total
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with('.'))
+}
+
+fn now_secs() -> u64 {
+    secs_since_epoch(SystemTime::now()).unwrap_or(0)
+}
+
+fn secs_since_epoch(time: SystemTime) -> Option<u64> {
+    time.duration_since(UNIX_EPOCH).ok().map(|d| d.as_secs())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+
+    use super::*;
+
+    #[test]
+    fn probes_the_legacy_binary_once() {
+        let root = tempfile::tempdir().unwrap();
+        let probes = root.path().join("probes");
+        let exe = root.path().join("cli-proxy-api");
+        fs::write(&exe, format!("#!/bin/sh\necho probe >> '{}'\necho 'CLIProxyAPI Version: 6.1.2'\n", probes.display()))
+            .unwrap();
+        fs::set_permissions(&exe, fs::Permissions::from_mode(0o755)).unwrap();
+
+        for _ in 0..3 {
+            let builds = list(root.path());
+            assert_eq!(builds.len(), 1);
+            assert_eq!(builds[0].id, LEGACY_TAG);
+            assert_eq!(builds[0].version.as_deref(), Some("6.1.2"));
+        }
+        assert_eq!(fs::read_to_string(&probes).unwrap().lines().count(), 1);
+        assert!(root.path().join(LEGACY_MANIFEST_FILE_NAME).is_file());
+    }
+}
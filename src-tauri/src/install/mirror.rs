@@ -0,0 +1,117 @@
+//! Download mirrors and failover order
+//!
+//! A mirror is either a prefix put in front of the full GitHub URL
+//! (`https://ghproxy.example/` gives `https://ghproxy.example/https://github.com/...`)
+//! or a template with `{url}` or `{path}`, e.g.
+//! `https://artifactory.corp/api/github/{path}` for the URL path without the
+//! host. The source that last completed a download is remembered in
+//! `~/.zerolimit/mirrors.json` and tried first next time.
+
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// Where a download comes from
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Source {
+    /// The original URL
+    Direct,
+    Mirror(String),
+}
+
+impl Source {
+    /// `url` as fetched through this source
+    pub fn apply(&self, url: &str) -> String {
+        match self {
+            Source::Direct => url.to_string(),
+            Source::Mirror(mirror) => mirror_url(mirror, url),
+        }
+    }
+
+    pub fn mirror(&self) -> Option<&str> {
+        match self {
+            Source::Direct => None,
+            Source::Mirror(mirror) => Some(mirror),
+        }
+    }
+}
+
+fn mirror_url(mirror: &str, url: &str) -> String {
+    if mirror.contains("{url}") {
+        return mirror.replace("{url}", url);
+    }
+    if mirror.contains("{path}") {
+        let path = url
+            .split_once("://")
+            .and_then(|(_, rest)| rest.split_once('/'))
+            .map(|(_, path)| path)
+            .unwrap_or(url);
+        return mirror.replace("{path}", path);
+    }
+    format!("{}/{}", mirror.trim_end_matches('/'), url)
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Memory {
+    last_success: Option<Source>,
+}
+
+/// `~/.zerolimit/mirrors.json`
+fn memory_path() -> Option<PathBuf> {
+    let mut path = dirs::home_dir()?;
+    path.push(".zerolimit");
+    path.push("mirrors.json");
+    Some(path)
+}
+
+fn last_success() -> Option<Source> {
+    memory_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str::<Memory>(&content).ok())
+        .and_then(|memory| memory.last_success)
+}
+
+/// Try `source` first on the next download
+pub fn remember(source: &Source) {
+    let Some(path) = memory_path() else { return };
+    if last_success().as_ref() == Some(source) {
+        return;
+    }
+    let memory = Memory { last_success: Some(source.clone()) };
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    if let Ok(json) = serde_json::to_string_pretty(&memory) {
+        if let Err(e) = fs::write(&path, json) {
+            println!("Notice: Could not remember download mirror: {}", e);
+        }
+    }
+}
+
+/// Sources to try in order: the last successful one if it is still on offer,
+/// then `mirrors` as given, then the original URL
+pub fn candidates(mirrors: &[String]) -> Vec<Source> {
+    let mut sources = Vec::new();
+    let offered = mirrors
+        .iter()
+        .map(|mirror| mirror.trim())
+        .filter(|mirror| !mirror.is_empty())
+        .map(|mirror| Source::Mirror(mirror.to_string()))
+        .chain([Source::Direct]);
+    for source in offered {
+        if !sources.contains(&source) {
+            sources.push(source);
+        }
+    }
+
+    if let Some(last) = last_success() {
+        if let Some(index) = sources.iter().position(|source| *source == last) {
+            let source = sources.remove(index);
+            sources.insert(0, source);
+        }
+    }
+    sources
+}
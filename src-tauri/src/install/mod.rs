@@ -0,0 +1,156 @@
+//! Proxy installation
+//!
+//! Streamed downloads, verification and extraction of release archives,
+//! and the versioned install layout they end up in.
+
+pub mod config_merge;
+pub mod download;
+pub mod extract;
+pub mod inventory;
+pub mod mirror;
+pub mod release;
+pub mod verify;
+pub mod versions;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::error::{CommandError, CommandResult};
+use crate::proxy;
+use config_merge::MergeSummary;
+use versions::InstalledBuild;
+
+/// Last path segment of an asset URL, without query string
+pub fn asset_name(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    path.rsplit('/').next().unwrap_or(path).to_string()
+}
+
+/// Result of installing a build
+#[derive(Clone, Debug, Serialize)]
+pub struct InstallOutcome {
+    pub tag: String,
+    pub exe_path: String,
+    /// What happened to the shared `config.yaml`; `None` if the release has no example config
+    pub config: Option<MergeSummary>,
+}
+
+/// Extract a verified archive into a staging dir and make it the current version
+pub fn install_archive(root: &Path, archive_path: &Path, asset_name: &str, tag: &str) -> CommandResult<InstallOutcome> {
+    install_staged(root, asset_name, tag, |staging| extract::extract(archive_path, asset_name, staging))
+}
+
+/// Install a bare proxy binary, e.g. one copied from another machine, as a new version
+pub fn install_binary(root: &Path, binary_path: &Path, tag: &str) -> CommandResult<InstallOutcome> {
+    let file_name = binary_path.file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| CommandError::General(format!("Invalid binary path: {}", binary_path.display())))?;
+    // Discovery goes by the release binary names, so a renamed copy gets its name back
+    let exe_name = if proxy::is_proxy_exe(binary_path) {
+        file_name.to_string()
+    } else {
+        canonical_exe_name(inventory::Edition::from_name(file_name))
+    };
+    install_staged(root, &exe_name, tag, |staging| {
+        fs::copy(binary_path, staging.join(&exe_name))
+            .map(|_| ())
+            .map_err(|e| CommandError::General(format!("Failed to copy proxy binary: {}", e)))
+    })
+}
+
+/// Whether a local file is a release archive rather than a bare binary
+pub fn is_archive(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    lower.ends_with(".zip") || lower.ends_with(".tar.gz") || lower.ends_with(".tgz")
+}
+
+/// Binary name used by releases of `edition`
+pub fn canonical_exe_name(edition: inventory::Edition) -> String {
+    let stem = match edition {
+        inventory::Edition::Standard => "cli-proxy-api",
+        inventory::Edition::Plus => "cli-proxy-api-plus",
+    };
+    format!("{}{}", stem, std::env::consts::EXE_SUFFIX)
+}
+
+/// Fill a staging dir with `fill`, then move it into place and make it current
+fn install_staged(
+    root: &Path,
+    asset_name: &str,
+    tag: &str,
+    fill: impl FnOnce(&Path) -> CommandResult<()>,
+) -> CommandResult<InstallOutcome> {
+    let staging = versions::create_staging_dir(root)
+        .map_err(|e| CommandError::General(format!("Failed to create staging dir: {}", e)))?;
+
+    let result = fill(&staging).and_then(|_| stage(&staging)).and_then(|exe| {
+        let dir_name = versions::version_dir_name(tag, asset_name);
+        versions::commit(root, &staging, &dir_name, tag, &exe)
+            .map_err(|e| CommandError::General(format!("Failed to install version {}: {}", tag, e)))
+    });
+    if result.is_err() {
+        let _ = fs::remove_dir_all(&staging);
+    }
+
+    let build = result?;
+    let build_dir = versions::versions_dir(root).join(versions::version_dir_name(tag, asset_name));
+    inventory::write_manifest(&build_dir, tag, asset_name, Path::new(&build.exe_path));
+    let config = sync_config(root, &build);
+    Ok(InstallOutcome { tag: build.tag, exe_path: build.exe_path, config })
+}
+
+/// Merge the new build's example config into the root `config.yaml`
+///
+/// A failed merge leaves `config.yaml` as it was and doesn't fail the install.
+fn sync_config(root: &Path, build: &InstalledBuild) -> Option<MergeSummary> {
+    let exe_dir = Path::new(&build.exe_path).parent()?;
+    let previous = versions::read_pointer(root).previous;
+    let previous_dir = previous.as_ref().and_then(|previous| Path::new(&previous.exe_path).parent());
+    match config_merge::sync(root, exe_dir, previous_dir) {
+        Ok(summary) => summary,
+        Err(e) => {
+            println!("Notice: Could not merge {} into config.yaml: {}", config_merge::EXAMPLE_FILE_NAME, e);
+            None
+        }
+    }
+}
+
+/// Find the proxy binary in a filled staging dir and make it executable
+fn stage(staging: &Path) -> CommandResult<PathBuf> {
+    let exe = find_proxy_exe(staging, true)
+        .ok_or_else(|| CommandError::General("Could not locate CLIProxyAPI executable after extraction.".to_string()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = fs::set_permissions(&exe, fs::Permissions::from_mode(0o755));
+    }
+    Ok(exe)
+}
+
+/// First proxy binary in `dir`, looking in subdirectories breadth-first if `recursive`
+pub fn find_proxy_exe(dir: &Path, recursive: bool) -> Option<PathBuf> {
+    let mut queue = vec![dir.to_path_buf()];
+    while !queue.is_empty() {
+        let mut next = Vec::new();
+        for dir in queue {
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            let mut entries: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+            entries.sort();
+            for path in entries {
+                if path.is_file() && proxy::is_proxy_exe(&path) {
+                    return Some(path);
+                }
+                if recursive && path.is_dir() {
+                    next.push(path);
+                }
+            }
+        }
+        queue = next;
+    }
+    None
+}
@@ -0,0 +1,357 @@
+//! GitHub release discovery and asset selection
+//!
+//! Releases of both editions come from `router-for-me/<repo>` on GitHub.
+//! The API base can be swapped for a mirror or a local mock server, since
+//! only the `releases` endpoints and their JSON shape are relied upon.
+
+use serde::{Deserialize, Serialize};
+
+use super::inventory::Edition;
+use super::verify::CHECKSUMS_FILE_NAME;
+use crate::error::{CommandError, CommandResult};
+
+pub const DEFAULT_API_BASE: &str = "https://api.github.com";
+const OWNER: &str = "router-for-me";
+/// How many recent releases to scan for the newest prerelease
+const PRERELEASE_SCAN: usize = 20;
+
+/// Which release to resolve
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Channel {
+    /// `releases/latest`, which never is a prerelease
+    #[default]
+    Stable,
+    /// Newest release, prereleases included
+    Prerelease,
+    /// A pinned tag such as `v6.1.2`
+    Tag(String),
+}
+
+/// Release and asset picked for this OS and architecture
+#[derive(Clone, Debug, Serialize)]
+pub struct ResolvedRelease {
+    pub edition: Edition,
+    pub tag: String,
+    pub name: Option<String>,
+    pub prerelease: bool,
+    pub published_at: Option<String>,
+    pub asset_name: String,
+    pub download_url: String,
+    pub size: u64,
+    /// `checksums.txt` of the same release, for `download_and_extract_proxy`
+    pub checksums_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    name: Option<String>,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    prerelease: bool,
+    published_at: Option<String>,
+    #[serde(default)]
+    assets: Vec<GithubAsset>,
+}
+
+#[derive(Deserialize)]
+struct GithubAsset {
+    name: String,
+    browser_download_url: String,
+    #[serde(default)]
+    size: u64,
+}
+
+pub fn repo_name(edition: Edition) -> &'static str {
+    match edition {
+        Edition::Standard => "CLIProxyAPI",
+        Edition::Plus => "CLIProxyAPIPlus",
+    }
+}
+
+/// Find the release for `channel` and the asset matching this OS and arch
+pub async fn resolve(
+    client: &reqwest::Client,
+    api_base: Option<&str>,
+    edition: Edition,
+    channel: &Channel,
+) -> CommandResult<ResolvedRelease> {
+    let base = api_base
+        .map(|base| base.trim_end_matches('/'))
+        .filter(|base| !base.is_empty())
+        .unwrap_or(DEFAULT_API_BASE);
+    let repo_url = format!("{}/repos/{}/{}", base, OWNER, repo_name(edition));
+
+    let (release, asset) = match channel {
+        Channel::Stable => {
+            let release: GithubRelease = get_json(client, &format!("{}/releases/latest", repo_url)).await?;
+            with_asset(release)?
+        }
+        Channel::Tag(tag) => {
+            let release: GithubRelease = get_json(client, &format!("{}/releases/tags/{}", repo_url, tag)).await?;
+            with_asset(release)?
+        }
+        Channel::Prerelease => {
+            let url = format!("{}/releases?per_page={}", repo_url, PRERELEASE_SCAN);
+            let releases: Vec<GithubRelease> = get_json(client, &url).await?;
+            // Newest first; skip drafts and releases that don't ship our platform yet
+            releases
+                .into_iter()
+                .filter(|release| !release.draft)
+                .find_map(|release| with_asset(release).ok())
+                .ok_or_else(no_asset_error)?
+        }
+    };
+
+    let checksums_url = release
+        .assets
+        .iter()
+        .find(|a| a.name == CHECKSUMS_FILE_NAME)
+        .map(|a| a.browser_download_url.clone());
+
+    Ok(ResolvedRelease {
+        edition,
+        tag: release.tag_name,
+        name: release.name,
+        prerelease: release.prerelease,
+        published_at: release.published_at,
+        asset_name: asset.name,
+        download_url: asset.browser_download_url,
+        size: asset.size,
+        checksums_url,
+    })
+}
+
+/// Split the matching asset off a release
+fn with_asset(mut release: GithubRelease) -> CommandResult<(GithubRelease, GithubAsset)> {
+    let index = select_asset(&release.assets, os_token(), arch_tokens()).ok_or_else(no_asset_error)?;
+    let asset = release.assets.swap_remove(index);
+    Ok((release, asset))
+}
+
+fn no_asset_error() -> CommandError {
+    CommandError::General(format!(
+        "No compatible asset found for {} {}",
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    ))
+}
+
+/// Index of the archive for `os`, preferring one built for `arch`
+///
+/// Assets without any arch in their name are accepted as a last resort,
+/// since older releases only shipped one build per OS.
+fn select_asset(assets: &[GithubAsset], os: &str, arch: &[&str]) -> Option<usize> {
+    const ALL_ARCHES: [&str; 6] = ["amd64", "x86_64", "x64", "arm64", "aarch64", "386"];
+
+    let os_prefix = format!("{}_", os);
+    let candidates: Vec<(usize, String)> = assets
+        .iter()
+        .enumerate()
+        .map(|(i, a)| (i, a.name.to_lowercase()))
+        .filter(|(_, name)| {
+            name.contains(&os_prefix)
+                && (name.ends_with(".zip") || name.ends_with(".tar.gz") || name.ends_with(".tgz"))
+        })
+        .collect();
+
+    // Tokens like x86_64 contain a separator themselves, so match on boundaries instead of splitting
+    let mentions = |name: &str, token: &str| {
+        let is_boundary = |c: Option<char>| c.is_none_or(|c| matches!(c, '_' | '-' | '.'));
+        name.match_indices(token).any(|(i, _)| {
+            is_boundary(name[..i].chars().next_back()) && is_boundary(name[i + token.len()..].chars().next())
+        })
+    };
+    candidates
+        .iter()
+        .find(|(_, name)| arch.iter().any(|token| mentions(name, token)))
+        .or_else(|| candidates.iter().find(|(_, name)| !ALL_ARCHES.iter().any(|token| mentions(name, token))))
+        .map(|(i, _)| *i)
+}
+
+/// OS name as used in release asset names
+fn os_token() -> &'static str {
+    match std::env::consts::OS {
+        "macos" => "darwin",
+        os => os,
+    }
+}
+
+/// Spellings of this CPU architecture in release asset names
+fn arch_tokens() -> &'static [&'static str] {
+    match std::env::consts::ARCH {
+        "x86_64" => &["amd64", "x86_64", "x64"],
+        "aarch64" => &["arm64", "aarch64"],
+        "x86" => &["386", "i386"],
+        _ => &[],
+    }
+}
+
+async fn get_json<T: serde::de::DeserializeOwned>(client: &reqwest::Client, url: &str) -> CommandResult<T> {
+    let response = client.get(url)
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await
+        .map_err(|e| CommandError::General(format!("Failed to fetch release info: {}", e)))?;
+
+    if !response.status().is_success() {
+        return Err(CommandError::General(format!("Release API returned status {}", response.status())));
+    }
+    response.json().await
+        .map_err(|e| CommandError::General(format!("Failed to parse release info: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::{json, Value};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    const X86_64: &[&str] = &["amd64", "x86_64", "x64"];
+
+    fn assets(names: &[&str]) -> Vec<GithubAsset> {
+        names
+            .iter()
+            .map(|name| GithubAsset {
+                name: name.to_string(),
+                browser_download_url: format!("https://example.com/{}", name),
+                size: 1,
+            })
+            .collect()
+    }
+
+    fn selected(names: &[&str], os: &str, arch: &[&str]) -> Option<String> {
+        select_asset(&assets(names), os, arch).map(|i| names[i].to_string())
+    }
+
+    #[test]
+    fn picks_the_asset_for_os_and_arch() {
+        let names = [
+            "checksums.txt",
+            "CLIProxyAPI_6.2.0_darwin_amd64.tar.gz",
+            "CLIProxyAPI_6.2.0_linux_arm64.tar.gz",
+            "CLIProxyAPI_6.2.0_linux_amd64.tar.gz",
+            "CLIProxyAPI_6.2.0_windows_amd64.zip",
+        ];
+        assert_eq!(selected(&names, "linux", X86_64).as_deref(), Some("CLIProxyAPI_6.2.0_linux_amd64.tar.gz"));
+        assert_eq!(selected(&names, "linux", &["arm64", "aarch64"]).as_deref(), Some("CLIProxyAPI_6.2.0_linux_arm64.tar.gz"));
+        assert_eq!(selected(&names, "windows", X86_64).as_deref(), Some("CLIProxyAPI_6.2.0_windows_amd64.zip"));
+        assert_eq!(selected(&names, "freebsd", X86_64), None);
+    }
+
+    #[test]
+    fn treats_x86_64_and_amd64_alike() {
+        let names = ["cli-proxy-api_linux_arm64.tar.gz", "cli-proxy-api_linux_x86_64.tar.gz"];
+        assert_eq!(selected(&names, "linux", X86_64).as_deref(), Some("cli-proxy-api_linux_x86_64.tar.gz"));
+    }
+
+    #[test]
+    fn falls_back_to_an_archless_asset() {
+        let names = ["CLIProxyAPI_5.0.0_linux_arm64.tar.gz", "CLIProxyAPI_5.0.0_linux_static.tar.gz"];
+        assert_eq!(selected(&names, "linux", X86_64).as_deref(), Some("CLIProxyAPI_5.0.0_linux_static.tar.gz"));
+        // An asset for another arch is never the fallback
+        assert_eq!(selected(&names[..1], "linux", X86_64), None);
+    }
+
+    #[test]
+    fn does_not_take_arm64_for_arm() {
+        let names = ["CLIProxyAPI_6.2.0_linux_arm64.tar.gz"];
+        assert_eq!(selected(&names, "linux", &["arm"]), None);
+        let names = ["CLIProxyAPI_6.2.0_linux_arm64.tar.gz", "CLIProxyAPI_6.2.0_linux_arm.tar.gz"];
+        assert_eq!(selected(&names, "linux", &["arm"]).as_deref(), Some("CLIProxyAPI_6.2.0_linux_arm.tar.gz"));
+    }
+
+    /// Asset name this machine resolves to
+    fn native_asset(version: &str) -> String {
+        match arch_tokens().first() {
+            Some(arch) => format!("CLIProxyAPI_{}_{}_{}.tar.gz", version, os_token(), arch),
+            None => format!("CLIProxyAPI_{}_{}_static.tar.gz", version, os_token()),
+        }
+    }
+
+    fn release(tag: &str, prerelease: bool, draft: bool, asset_names: &[String]) -> Value {
+        let assets: Vec<Value> = asset_names
+            .iter()
+            .map(|name| json!({ "name": name, "browser_download_url": format!("https://dl.test/{}/{}", tag, name), "size": 42 }))
+            .collect();
+        json!({ "tag_name": tag, "name": tag, "prerelease": prerelease, "draft": draft, "assets": assets })
+    }
+
+    /// Serve fixed JSON bodies by request target until the test ends
+    async fn mock_server(routes: HashMap<String, Value>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buffer = [0; 1024];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    match stream.read(&mut buffer).await {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => request.extend_from_slice(&buffer[..n]),
+                    }
+                }
+                let request = String::from_utf8_lossy(&request);
+                let target = request.split_whitespace().nth(1).unwrap_or_default();
+                let (status, body) = match routes.get(target) {
+                    Some(body) => ("200 OK", body.to_string()),
+                    None => ("404 Not Found", "{}".to_string()),
+                };
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{}", address)
+    }
+
+    #[tokio::test]
+    async fn resolves_stable_and_prerelease_channels() {
+        let stable = release("v6.2.0", false, false, &[native_asset("6.2.0"), CHECKSUMS_FILE_NAME.to_string()]);
+        let repo = "/repos/router-for-me/CLIProxyAPI";
+        let routes = HashMap::from([
+            (format!("{}/releases/latest", repo), stable.clone()),
+            (format!("{}/releases/tags/v6.1.0", repo), release("v6.1.0", false, false, &[native_asset("6.1.0")])),
+            (
+                format!("{}/releases?per_page={}", repo, PRERELEASE_SCAN),
+                json!([
+                    release("v6.4.0-rc1", true, true, &[native_asset("6.4.0-rc1")]),
+                    release("v6.3.0-rc2", true, false, &["CLIProxyAPI_6.3.0-rc2_plan9_mips.tar.gz".to_string()]),
+                    release("v6.3.0-rc1", true, false, &[native_asset("6.3.0-rc1")]),
+                    stable,
+                ]),
+            ),
+        ]);
+        let base = mock_server(routes).await;
+        let client = reqwest::Client::new();
+
+        let resolved = resolve(&client, Some(&base), Edition::Standard, &Channel::Stable).await.unwrap();
+        assert_eq!(resolved.tag, "v6.2.0");
+        assert!(!resolved.prerelease);
+        assert_eq!(resolved.asset_name, native_asset("6.2.0"));
+        assert_eq!(resolved.checksums_url.as_deref(), Some("https://dl.test/v6.2.0/checksums.txt"));
+
+        // Drafts and releases without an asset for this machine are skipped
+        let resolved = resolve(&client, Some(&base), Edition::Standard, &Channel::Prerelease).await.unwrap();
+        assert_eq!(resolved.tag, "v6.3.0-rc1");
+        assert!(resolved.prerelease);
+        assert_eq!(resolved.checksums_url, None);
+
+        let pinned = Channel::Tag("v6.1.0".to_string());
+        let resolved = resolve(&client, Some(&format!("{}/", base)), Edition::Standard, &pinned).await.unwrap();
+        assert_eq!(resolved.download_url, format!("https://dl.test/v6.1.0/{}", native_asset("6.1.0")));
+
+        // The Plus repo isn't served
+        assert!(resolve(&client, Some(&base), Edition::Plus, &Channel::Stable).await.is_err());
+    }
+}
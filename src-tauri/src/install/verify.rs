@@ -0,0 +1,218 @@
+//! Integrity checks for downloaded release archives
+//!
+//! The archive's SHA-256 must match its line in the release's
+//! `checksums.txt`. When a public key is configured, `checksums.txt` itself
+//! must carry a valid minisign or cosign signature, which covers the archive
+//! transitively.
+
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use base64::Engine;
+use p256::ecdsa::signature::Verifier;
+use p256::pkcs8::DecodePublicKey;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::error::{CommandError, CommandResult};
+
+pub const CHECKSUMS_FILE_NAME: &str = "checksums.txt";
+
+/// Where to find the checksums and signature for an archive
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct VerifyOptions {
+    /// `checksums.txt` URL, next to the archive by default
+    pub checksums_url: Option<String>,
+    /// Signature of `checksums.txt`, `checksums.txt.minisig` / `.sig` by default
+    pub signature_url: Option<String>,
+    /// minisign (`RW...`) or cosign (PEM) public key; no signature check without one
+    pub public_key: Option<String>,
+}
+
+/// Verify a downloaded archive against the release checksums and optional signature
+pub async fn verify_archive(
+    client: &reqwest::Client,
+    archive_url: &str,
+    archive: &Path,
+    options: &VerifyOptions,
+) -> CommandResult<()> {
+    let file_name = super::asset_name(archive_url);
+    let fail = |reason: String| CommandError::VerificationFailed { file: file_name.clone(), reason };
+
+    let checksums_url = match options.checksums_url.clone() {
+        Some(url) => url,
+        None => sibling_url(archive_url, CHECKSUMS_FILE_NAME)
+            .ok_or_else(|| fail("Cannot derive checksums.txt URL".to_string()))?,
+    };
+    let checksums = fetch(client, &checksums_url)
+        .await
+        .map_err(|e| fail(format!("Could not download {}: {}", CHECKSUMS_FILE_NAME, e)))?;
+
+    if let Some(public_key) = options.public_key.as_deref().filter(|key| !key.trim().is_empty()) {
+        let key = PublicKey::parse(public_key).map_err(fail)?;
+        let signature_url = options
+            .signature_url
+            .clone()
+            .unwrap_or_else(|| format!("{}{}", checksums_url, key.signature_suffix()));
+        let signature = fetch(client, &signature_url)
+            .await
+            .map_err(|e| fail(format!("Could not download signature: {}", e)))?;
+        key.verify(&checksums, &String::from_utf8_lossy(&signature))
+            .map_err(|e| fail(format!("Invalid signature on {}: {}", CHECKSUMS_FILE_NAME, e)))?;
+    }
+
+    let checksums = String::from_utf8_lossy(&checksums);
+    let expected = expected_checksum(&checksums, &file_name)
+        .ok_or_else(|| fail(format!("No entry in {}", CHECKSUMS_FILE_NAME)))?;
+    let actual = sha256_file(archive)
+        .map_err(|e| fail(format!("Could not read archive: {}", e)))?;
+    if !actual.eq_ignore_ascii_case(&expected) {
+        return Err(fail(format!("SHA-256 mismatch: expected {}, got {}", expected, actual)));
+    }
+
+    Ok(())
+}
+
+/// Hex SHA-256 of a file, read in chunks
+pub fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Hash listed for `file_name` in a `sha256sum`-style checksums file
+pub fn expected_checksum(checksums: &str, file_name: &str) -> Option<String> {
+    checksums.lines().find_map(|line| {
+        let (hash, name) = line.trim().split_once(char::is_whitespace)?;
+        // `sha256sum -b` marks binary mode with a leading '*'
+        let name = name.trim().trim_start_matches('*');
+        (name == file_name && hash.len() == 64).then(|| hash.to_lowercase())
+    })
+}
+
+/// URL of another asset in the same release
+fn sibling_url(url: &str, name: &str) -> Option<String> {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let (base, _) = path.rsplit_once('/')?;
+    Some(format!("{}/{}", base, name))
+}
+
+async fn fetch(client: &reqwest::Client, url: &str) -> Result<Vec<u8>, String> {
+    let response = client.get(url)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("status {}", response.status()));
+    }
+    response.bytes().await.map(|b| b.to_vec()).map_err(|e| e.to_string())
+}
+
+enum PublicKey {
+    Minisign(minisign_verify::PublicKey),
+    Cosign(p256::ecdsa::VerifyingKey),
+}
+
+impl PublicKey {
+    /// Accepts a minisign key (bare or with its comment line) or a PEM cosign key
+    fn parse(key: &str) -> Result<Self, String> {
+        let key = key.trim();
+        if key.starts_with("-----BEGIN") {
+            return p256::ecdsa::VerifyingKey::from_public_key_pem(key)
+                .map(PublicKey::Cosign)
+                .map_err(|e| format!("Invalid cosign public key: {}", e));
+        }
+        let encoded = key.lines().last().unwrap_or(key).trim();
+        minisign_verify::PublicKey::from_base64(encoded)
+            .map(PublicKey::Minisign)
+            .map_err(|e| format!("Invalid minisign public key: {}", e))
+    }
+
+    fn signature_suffix(&self) -> &'static str {
+        match self {
+            PublicKey::Minisign(_) => ".minisig",
+            PublicKey::Cosign(_) => ".sig",
+        }
+    }
+
+    fn verify(&self, data: &[u8], signature: &str) -> Result<(), String> {
+        match self {
+            PublicKey::Minisign(key) => {
+                let signature = minisign_verify::Signature::decode(signature).map_err(|e| e.to_string())?;
+                key.verify(data, &signature, false).map_err(|e| e.to_string())
+            }
+            PublicKey::Cosign(key) => {
+                // `cosign sign-blob` writes a base64 DER ECDSA signature
+                let der = base64::engine::general_purpose::STANDARD
+                    .decode(signature.trim())
+                    .map_err(|e| e.to_string())?;
+                let signature = p256::ecdsa::Signature::from_der(&der).map_err(|e| e.to_string())?;
+                key.verify(data, &signature).map_err(|e| e.to_string())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p256::ecdsa::signature::Signer;
+    use p256::pkcs8::{EncodePublicKey, LineEnding};
+
+    use super::*;
+
+    const HASH: &str = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+
+    #[test]
+    fn finds_checksum_by_file_name() {
+        let checksums = format!(
+            "{hash}  cli-proxy-api_linux_amd64.tar.gz\n{upper} *cli-proxy-api_windows_amd64.zip\nnot a checksum line\n",
+            hash = HASH,
+            upper = HASH.to_uppercase(),
+        );
+        assert_eq!(expected_checksum(&checksums, "cli-proxy-api_linux_amd64.tar.gz").as_deref(), Some(HASH));
+        // Binary mode marker and upper case hex
+        assert_eq!(expected_checksum(&checksums, "cli-proxy-api_windows_amd64.zip").as_deref(), Some(HASH));
+        assert_eq!(expected_checksum(&checksums, "cli-proxy-api_linux_amd64"), None);
+        assert_eq!(expected_checksum(&format!("{} short", &HASH[..40]), "short"), None);
+    }
+
+    #[test]
+    fn hashes_file_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test");
+        std::fs::write(&path, "test").unwrap();
+        assert_eq!(sha256_file(&path).unwrap(), HASH);
+    }
+
+    #[test]
+    fn checks_minisign_signatures() {
+        // Test vector from minisign-verify
+        let key = PublicKey::parse("RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3").unwrap();
+        let signature = "untrusted comment: signature from minisign secret key
+RUQf6LRCGA9i559r3g7V1qNyJDApGip8MfqcadIgT9CuhV3EMhHoN1mGTkUidF/z7SrlQgXdy8ofjb7bNJJylDOocrCo8KLzZwo=
+trusted comment: timestamp:1633700835\tfile:test\tprehashed
+wLMDjy9FLAuxZ3q4NlEvkgtyhrr0gtTu6KC4KBJdITbbOeAi1zBIYo0v4iTgt8jJpIidRJnp94ABQkJAgAooBQ==";
+        assert_eq!(key.signature_suffix(), ".minisig");
+        assert!(key.verify(b"test", signature).is_ok());
+        assert!(key.verify(b"tampered", signature).is_err());
+        assert!(key.verify(b"test", "not a signature").is_err());
+        assert!(PublicKey::parse("RWQnot-a-key").is_err());
+    }
+
+    #[test]
+    fn checks_cosign_signatures() {
+        let signing_key = p256::ecdsa::SigningKey::from_slice(&[7; 32]).unwrap();
+        let pem = signing_key.verifying_key().to_public_key_pem(LineEnding::LF).unwrap();
+        let signature: p256::ecdsa::Signature = signing_key.sign(b"checksums");
+        let signature = base64::engine::general_purpose::STANDARD.encode(signature.to_der());
+
+        let key = PublicKey::parse(&pem).unwrap();
+        assert_eq!(key.signature_suffix(), ".sig");
+        assert!(key.verify(b"checksums", &signature).is_ok());
+        assert!(key.verify(b"tampered", &signature).is_err());
+        assert!(key.verify(b"checksums", "bm90IGRlcg==").is_err());
+        assert!(PublicKey::parse("-----BEGIN PUBLIC KEY-----\ngarbage\n-----END PUBLIC KEY-----").is_err());
+    }
+}
@@ -0,0 +1,194 @@
+//! Versioned proxy installs
+//!
+//! Each release is extracted into a staging directory and then moved into
+//! `<root>/versions/<tag>/`, where root is `~/.zerolimit/cli_proxy` unless
+//! the user picked another directory. `<root>/current.json` names the active
+//! build and the one before it. It is replaced atomically, so a failed
+//! install never leaves the root without a working proxy. The user's
+//! `config.yaml` stays in the root and versioned binaries run from there;
+//! see `config_merge` for how it follows upgrades.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+
+pub const VERSIONS_DIR_NAME: &str = "versions";
+pub const POINTER_FILE_NAME: &str = "current.json";
+/// Tag recorded for a binary installed before versioned installs existed
+pub const LEGACY_TAG: &str = "legacy";
+
+/// A build the pointer can refer to
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InstalledBuild {
+    pub tag: String,
+    pub exe_path: String,
+}
+
+/// Contents of `current.json`
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Pointer {
+    pub current: Option<InstalledBuild>,
+    pub previous: Option<InstalledBuild>,
+}
+
+/// Install root for a directory the frontend passed, which may be inside a version dir
+pub fn install_root(dir: &Path) -> PathBuf {
+    versions_root_of(dir).unwrap_or_else(|| dir.to_path_buf())
+}
+
+/// Root of the versioned install containing `dir`, if any
+///
+/// Used as the working directory of versioned binaries so they share the
+/// root's `config.yaml`.
+pub fn versions_root_of(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .filter(|ancestor| ancestor.file_name().is_some_and(|name| name == VERSIONS_DIR_NAME))
+        .filter_map(Path::parent)
+        .find(|root| root.join(POINTER_FILE_NAME).is_file())
+        .map(Path::to_path_buf)
+}
+
+pub fn versions_dir(root: &Path) -> PathBuf {
+    root.join(VERSIONS_DIR_NAME)
+}
+
+pub fn read_pointer(root: &Path) -> Pointer {
+    fs::read_to_string(root.join(POINTER_FILE_NAME))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Write `current.json` through a temp file and rename, so readers never see half of it
+fn write_pointer(root: &Path, pointer: &Pointer) -> io::Result<()> {
+    let json = serde_json::to_string_pretty(pointer)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let temp = root.join(format!("{}.tmp", POINTER_FILE_NAME));
+    fs::write(&temp, json)?;
+    fs::rename(&temp, root.join(POINTER_FILE_NAME))
+}
+
+/// Directory name for a release: the tag, with a `-plus` suffix for Plus builds
+pub fn version_dir_name(tag: &str, asset_name: &str) -> String {
+    let mut name: String = tag
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+        .collect();
+    let name_lower = name.to_lowercase();
+    if asset_name.to_lowercase().contains("plus") && !name_lower.contains("plus") {
+        name.push_str("-plus");
+    }
+    name
+}
+
+/// Release tag from a GitHub download URL (`.../releases/download/<tag>/<asset>`)
+pub fn tag_from_url(url: &str) -> Option<String> {
+    let mut segments = url.split(['?', '#']).next()?.split('/');
+    segments.find(|segment| *segment == "download")?;
+    let tag = segments.next()?;
+    segments.next()?;
+    Some(tag.to_string())
+}
+
+/// Create a fresh staging directory next to the version dirs, so the final move is a rename
+///
+/// Every call gets its own directory, even for concurrent installs into the same root.
+pub fn create_staging_dir(root: &Path) -> io::Result<PathBuf> {
+    static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+    fs::create_dir_all(versions_dir(root))?;
+    loop {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+        let sequence = SEQUENCE.fetch_add(1, Ordering::Relaxed);
+        let dir = versions_dir(root).join(format!(".staging-{}-{}-{}", std::process::id(), nanos, sequence));
+        // Not create_dir_all: an existing dir means another run owns it
+        match fs::create_dir(&dir) {
+            Ok(()) => return Ok(dir),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Move a staged build into `versions/<dir_name>` and make it current
+///
+/// `exe` is the proxy binary inside `staging`.
+pub fn commit(root: &Path, staging: &Path, dir_name: &str, tag: &str, exe: &Path) -> io::Result<InstalledBuild> {
+    let relative = exe.strip_prefix(staging)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Executable is outside the staging dir"))?
+        .to_path_buf();
+    let target = versions_dir(root).join(dir_name);
+
+    // Reinstalling a tag: move the old copy aside first and only delete it once the new one is in place
+    let replaced = if target.exists() {
+        let aside = staging.with_file_name(format!(".replaced-{}", dir_name));
+        let _ = fs::remove_dir_all(&aside);
+        fs::rename(&target, &aside)?;
+        Some(aside)
+    } else {
+        None
+    };
+    if let Err(e) = fs::rename(staging, &target) {
+        if let Some(ref aside) = replaced {
+            let _ = fs::rename(aside, &target);
+        }
+        return Err(e);
+    }
+    if let Some(aside) = replaced {
+        let _ = fs::remove_dir_all(aside);
+    }
+
+    let build = InstalledBuild {
+        tag: tag.to_string(),
+        exe_path: target.join(relative).to_string_lossy().to_string(),
+    };
+    activate(root, build.clone())?;
+    Ok(build)
+}
+
+/// Point `current` at `build`, keeping the old current build as `previous`
+pub fn activate(root: &Path, build: InstalledBuild) -> io::Result<()> {
+    let pointer = read_pointer(root);
+    // The first versioned install keeps a binary from the old flat layout as its fallback
+    let previous = pointer.current.or_else(|| {
+        super::find_proxy_exe(root, false).map(|exe| InstalledBuild {
+            tag: LEGACY_TAG.to_string(),
+            exe_path: exe.to_string_lossy().to_string(),
+        })
+    });
+    let previous = previous.filter(|previous| previous.exe_path != build.exe_path);
+    write_pointer(root, &Pointer { current: Some(build), previous })
+}
+
+/// Swap `current` and `previous`, returning the restored build
+pub fn rollback(root: &Path) -> io::Result<InstalledBuild> {
+    let pointer = read_pointer(root);
+    let previous = pointer.previous
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No previous version to roll back to"))?;
+    if !Path::new(&previous.exe_path).is_file() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Previous version {} is no longer installed", previous.tag),
+        ));
+    }
+    write_pointer(root, &Pointer { current: Some(previous.clone()), previous: pointer.current })?;
+    Ok(previous)
+}
+
+/// Drop `previous` after its build was removed
+pub fn forget_previous(root: &Path) -> io::Result<()> {
+    let pointer = read_pointer(root);
+    if pointer.previous.is_none() {
+        return Ok(());
+    }
+    write_pointer(root, &Pointer { current: pointer.current, previous: None })
+}
@@ -4,74 +4,40 @@
 
 mod commands;
 mod error;
+mod install;
+mod net;
+mod proxy;
+mod quota;
 mod state;
 mod tray;
 
 use commands::*;
+use state::AppState;
+use tauri::{AppHandle, Manager};
 
-fn cleanup_on_exit() {
-    if let Ok(mut guard) = state::CLI_PROXY_PROCESS.lock() {
-        if let Some(ref mut child) = *guard {
-            #[cfg(windows)]
-            {
-                use std::process::Command;
-                use std::os::windows::process::CommandExt;
-                const CREATE_NO_WINDOW: u32 = 0x08000000;
-
-                let pid = child.id();
-                let _ = Command::new("taskkill")
-                    .args(["/F", "/T", "/PID", &pid.to_string()])
-                    .creation_flags(CREATE_NO_WINDOW)
-                    .output();
-            }
-
-            #[cfg(not(windows))]
-            {
-                let _ = child.kill();
-            }
-
-            let _ = child.wait();
-        }
-        *guard = None;
-    }
-
-    if let Ok(mut name_guard) = state::CLI_PROXY_NAME.lock() {
-        if let Some(ref name) = *name_guard {
-            if name.to_lowercase().contains("cliproxy") {
-                #[cfg(windows)]
-                {
-                    use std::process::Command;
-                    use std::os::windows::process::CommandExt;
-                    const CREATE_NO_WINDOW: u32 = 0x08000000;
-
-                    let _ = Command::new("taskkill")
-                        .args(["/F", "/T", "/IM", name])
-                        .creation_flags(CREATE_NO_WINDOW)
-                        .output();
-                }
-
-                #[cfg(not(windows))]
-                {
-                    use std::process::Command;
-                    let _ = Command::new("pkill")
-                        .args(["-f", name])
-                        .output();
-                }
-            }
+/// Stop every managed proxy instance before the app exits
+///
+/// Proxies that were only found running, not started by us, are left alone.
+fn cleanup_on_exit(app: &AppHandle) {
+    for instance in app.state::<AppState>().instances() {
+        if instance.is_observed_only() {
+            continue;
         }
-        *name_guard = None;
+        instance.stop(proxy::shutdown::DEFAULT_GRACE_PERIOD);
     }
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
+        .manage(AppState::default())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_os::init())
+        .plugin(tauri_plugin_notification::init())
         .setup(|app| {
             #[cfg(desktop)]
             {
@@ -79,11 +45,16 @@ pub fn run() {
                     .plugin(tauri_plugin_updater::Builder::new().build())?;
             }
             tray::setup_tray(app)?;
+            proxy::pidfile::remove_stale();
+            proxy::discovery::adopt_running(app.handle(), &app.state::<AppState>());
+            proxy::stats::spawn_reporter(app.handle().clone());
+            quota::poller::spawn(app.handle().clone());
+            quota::history::spawn_maintenance(app.handle().clone());
             Ok(())
         })
         .on_window_event(|window, event| {
             if let tauri::WindowEvent::CloseRequested { api, .. } = event {
-                if state::get_run_in_background() {
+                if window.state::<AppState>().get_run_in_background() {
                     let _ = window.hide();
                     api.prevent_close();
                 }
@@ -93,17 +64,45 @@ pub fn run() {
             open_external_url,
             set_run_in_background,
             start_cli_proxy,
+            check_proxy_port,
+            get_proxy_config_path,
+            get_launch_profile,
+            set_launch_profile,
+            wait_for_proxy_ready,
             stop_cli_proxy,
             is_cli_proxy_running,
+            list_proxy_instances,
+            get_proxy_output,
+            get_proxy_stats,
             download_and_extract_proxy,
+            cancel_proxy_download,
+            rollback_proxy,
+            list_installed_proxies,
+            remove_installed_proxy,
+            activate_installed_proxy,
+            install_proxy_from_file,
             find_alternate_proxy_exe,
             check_proxy_version,
+            resolve_proxy_release,
+            get_http_settings,
+            set_http_settings,
+            set_management_connection,
+            fetch_quota,
+            get_cached_quotas,
+            get_quota_poll_settings,
+            set_quota_poll_settings,
+            query_quota_history,
+            get_quota_history_settings,
+            set_quota_history_settings,
+            forecast_quota,
+            get_alert_settings,
+            set_alert_settings,
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
-        .run(|_app_handle, event| {
+        .run(|app_handle, event| {
             if let tauri::RunEvent::Exit = event {
-                cleanup_on_exit();
+                cleanup_on_exit(app_handle);
             }
         });
 }
@@ -0,0 +1,154 @@
+//! Helpers for the proxy's `config.yaml`
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+pub const CONFIG_FILE_NAME: &str = "config.yaml";
+/// Port CLIProxyAPI listens on when config.yaml doesn't set one
+pub const DEFAULT_PORT: u16 = 8317;
+
+/// Read the top-level `port` from a proxy config file
+pub fn read_port(config_path: &Path) -> Option<u16> {
+    let content = fs::read_to_string(config_path).ok()?;
+    top_level_value(&content, "port")?.parse().ok()
+}
+
+/// Port a proxy started with this config file will listen on
+pub fn effective_port(config_path: &Path) -> u16 {
+    read_port(config_path).unwrap_or(DEFAULT_PORT)
+}
+
+/// Set the top-level `port` in a config file, keeping the rest of the file intact
+///
+/// A missing config file is first seeded from `example_path`; without an
+/// example the write is refused rather than leaving a config with only a port.
+pub fn write_port(config_path: &Path, port: u16, example_path: &Path) -> io::Result<()> {
+    let content = match fs::read_to_string(config_path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => fs::read_to_string(example_path).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} does not exist and there is no {} to create it from", config_path.display(), example_path.display()),
+            )
+        })?,
+        Err(e) => return Err(e),
+    };
+
+    let mut replaced = false;
+    let mut lines: Vec<String> = content
+        .lines()
+        .map(|line| {
+            if replaced || line.starts_with(char::is_whitespace) {
+                return line.to_string();
+            }
+            let Some(rest) = line.strip_prefix("port").map(str::trim_start) else {
+                return line.to_string();
+            };
+            if !rest.starts_with(':') {
+                return line.to_string();
+            }
+            replaced = true;
+            match rest.find(" #") {
+                Some(idx) => format!("port: {}{}", port, &rest[idx..]),
+                None => format!("port: {}", port),
+            }
+        })
+        .collect();
+    if !replaced {
+        lines.push(format!("port: {}", port));
+    }
+
+    fs::write(config_path, join_lines(lines, &content))
+}
+
+/// Join lines with the line ending `original` uses
+pub(crate) fn join_lines(lines: Vec<String>, original: &str) -> String {
+    let eol = if original.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut text = lines.join(eol);
+    if original.ends_with('\n') || original.is_empty() {
+        text.push_str(eol);
+    }
+    text
+}
+
+/// Value of a top-level `key: value` line, without quotes or trailing comment
+fn top_level_value<'a>(content: &'a str, key: &str) -> Option<&'a str> {
+    content.lines().find_map(|line| {
+        if line.starts_with(char::is_whitespace) {
+            return None;
+        }
+        let rest = line.strip_prefix(key)?.trim_start().strip_prefix(':')?;
+        let value = rest.split(" #").next().unwrap_or(rest).trim();
+        Some(value.trim_matches(|c| c == '"' || c == '\''))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_top_level_port() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE_NAME);
+        fs::write(&path, "host: \"\"\nremote:\n  port: 9000\nport: \"8400\" # api\n").unwrap();
+        assert_eq!(read_port(&path), Some(8400));
+
+        fs::write(&path, "host: \"\"\n").unwrap();
+        assert_eq!(read_port(&path), None);
+        assert_eq!(effective_port(&path), DEFAULT_PORT);
+    }
+
+    #[test]
+    fn replaces_only_the_top_level_port() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE_NAME);
+        fs::write(&path, "remote:\n  port: 9000\nport: 8317 # api\ndebug: false\n").unwrap();
+
+        write_port(&path, 8318, &dir.path().join("missing.yaml")).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "remote:\n  port: 9000\nport: 8318 # api\ndebug: false\n"
+        );
+        assert_eq!(read_port(&path), Some(8318));
+    }
+
+    #[test]
+    fn keeps_crlf_line_endings() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE_NAME);
+        fs::write(&path, "port: 8317\r\ndebug: false\r\n").unwrap();
+
+        write_port(&path, 8318, &dir.path().join("missing.yaml")).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "port: 8318\r\ndebug: false\r\n");
+
+        fs::write(&path, "debug: false\r\n").unwrap();
+        write_port(&path, 8318, &dir.path().join("missing.yaml")).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "debug: false\r\nport: 8318\r\n");
+    }
+
+    #[test]
+    fn seeds_a_missing_config_from_the_example() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE_NAME);
+        let example = dir.path().join("config.example.yaml");
+        fs::write(&example, "# Server port\nport: 8317\nauth-dir: \"~/.cli-proxy-api\"\n").unwrap();
+
+        write_port(&path, 8318, &example).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "# Server port\nport: 8318\nauth-dir: \"~/.cli-proxy-api\"\n"
+        );
+    }
+
+    #[test]
+    fn refuses_a_missing_config_without_an_example() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE_NAME);
+
+        let err = write_port(&path, 8318, &dir.path().join("config.example.yaml")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,141 @@
+//! Discovery of proxies that outlived a previous ZeroLimit session
+//!
+//! On launch we look for a live proxy via its pidfile, the process table
+//! or the configured port, and attach it to its instance as an adopted
+//! process. Only binaries inside our install dir are considered. A proxy
+//! found without one of our pidfiles is observed only: we never write a
+//! pidfile for it and leave it running when the app exits.
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+use tauri::{AppHandle, Emitter};
+
+use super::instance::{ProxyInstance, ProxyInstanceInfo};
+use super::pidfile::{self, PidFile};
+use super::{config, port};
+use crate::state::{AppState, DEFAULT_INSTANCE_ID};
+
+pub const EVENT_ADOPTED: &str = "proxy://adopted";
+
+/// How an adopted process was found
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdoptionSource {
+    Pidfile,
+    ProcessTable,
+    Port,
+}
+
+/// A live proxy we didn't spawn in this session but still manage
+#[derive(Clone)]
+pub struct AdoptedProcess {
+    pub record: PidFile,
+    pub source: AdoptionSource,
+    pub port: Option<u16>,
+}
+
+impl AdoptedProcess {
+    /// Found running without a pidfile, so not known to be started by us
+    pub fn is_observed_only(&self) -> bool {
+        self.source != AdoptionSource::Pidfile
+    }
+}
+
+/// Find live proxies and attach them to their instances, returning what was adopted
+pub fn adopt_running(app: &AppHandle, state: &AppState) -> Vec<ProxyInstanceInfo> {
+    let mut adopted = Vec::new();
+
+    for record in pidfile::list() {
+        if !record.is_alive() {
+            continue;
+        }
+        let instance = state.instance(&record.instance_id);
+        if !instance.is_running() {
+            adopt(&instance, record, AdoptionSource::Pidfile);
+            adopted.push(instance);
+        }
+    }
+
+    let default = state.instance(DEFAULT_INSTANCE_ID);
+    if !default.is_running() {
+        let found = find_in_process_table()
+            .map(|pid| (pid, AdoptionSource::ProcessTable))
+            .or_else(|| find_by_port(&default).map(|pid| (pid, AdoptionSource::Port)));
+
+        if let Some((pid, source)) = found {
+            if let Some(record) = exe_of(pid).and_then(|exe| pidfile::identify(DEFAULT_INSTANCE_ID, pid, &exe)) {
+                adopt(&default, record, source);
+                adopted.push(default);
+            }
+        }
+    }
+
+    let infos: Vec<ProxyInstanceInfo> = adopted.iter().map(|instance| instance.info()).collect();
+    for (instance, info) in adopted.iter().zip(&infos) {
+        super::supervisor::watch_adopted(app.clone(), instance.clone());
+        let _ = app.emit(EVENT_ADOPTED, info);
+    }
+    infos
+}
+
+/// Adopt the instance's pidfile process if it is alive, returning its pid
+pub fn adopt_from_pidfile(instance: &ProxyInstance) -> Option<u32> {
+    let record = pidfile::read(&instance.id).filter(PidFile::is_alive)?;
+    let pid = record.pid;
+    adopt(instance, record, AdoptionSource::Pidfile);
+    Some(pid)
+}
+
+fn adopt(instance: &ProxyInstance, record: PidFile, source: AdoptionSource) {
+    let profile = instance.launch_profile();
+    let port = Path::new(&record.exe_path).parent()
+        .and_then(|exe_dir| config::read_port(&profile.config_file(exe_dir)));
+    instance.set_adopted(AdoptedProcess { record, source, port });
+}
+
+/// A proxy binary running out of our install dir
+fn find_in_process_table() -> Option<u32> {
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing().with_exe(UpdateKind::Always),
+    );
+
+    system.processes().iter().find_map(|(pid, process)| {
+        let exe = process.exe()?;
+        is_ours(exe).then(|| pid.as_u32())
+    })
+}
+
+/// A proxy binary listening on the port the instance would use from our install dir
+fn find_by_port(instance: &ProxyInstance) -> Option<u32> {
+    let profile = instance.launch_profile();
+    let port = super::default_install_dir()
+        .map(|dir| config::effective_port(&profile.config_file(&dir)))
+        .unwrap_or(config::DEFAULT_PORT);
+    let pid = port::owner_pid(port)?;
+    exe_of(pid).filter(|exe| is_ours(exe)).map(|_| pid)
+}
+
+/// A proxy binary inside our install dir, whoever started it
+fn is_ours(exe: &Path) -> bool {
+    let Some(install_dir) = super::default_install_dir().and_then(|dir| dir.canonicalize().ok()) else {
+        return false;
+    };
+    let exe = exe.canonicalize().unwrap_or_else(|_| exe.to_path_buf());
+    exe.starts_with(install_dir) && super::is_proxy_exe(&exe)
+}
+
+fn exe_of(pid: u32) -> Option<PathBuf> {
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        true,
+        ProcessRefreshKind::nothing().with_exe(UpdateKind::Always),
+    );
+    system.process(pid)?.exe().map(Path::to_path_buf)
+}
@@ -0,0 +1,128 @@
+//! Readiness checks against a freshly started proxy
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde::Serialize;
+
+use super::instance::ProxyInstance;
+
+pub const DEFAULT_READY_TIMEOUT: Duration = Duration::from_secs(15);
+const INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_BACKOFF: Duration = Duration::from_secs(1);
+const ATTEMPT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Outcome of `wait_for_proxy_ready`
+#[derive(Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Readiness {
+    Ready {
+        latency_ms: u64,
+        attempts: u32,
+        version: Option<String>,
+        build_date: Option<String>,
+    },
+    /// The managed process died before it answered
+    ProcessExited,
+    /// Nothing ever accepted connections on the API port
+    PortClosed { attempts: u32 },
+    /// The management key was refused
+    AuthRejected { http_status: u16 },
+    Timeout { attempts: u32, last_error: Option<String> },
+}
+
+/// `x-cpa-version` / `x-cpa-build-date` (or the older `x-server-*`) headers
+pub fn version_headers(headers: &HeaderMap) -> (Option<String>, Option<String>) {
+    let get = |keys: [&str; 2]| {
+        keys.iter()
+            .find_map(|key| headers.get(*key))
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string())
+    };
+    (
+        get(["x-cpa-version", "x-server-version"]),
+        get(["x-cpa-build-date", "x-server-build-date"]),
+    )
+}
+
+/// Poll the proxy until it answers, with exponential backoff
+///
+/// With a management key the management API is queried, so a wrong key is
+/// reported as `AuthRejected`; without one only the root endpoint is hit.
+pub async fn wait_until_ready(
+    client: &reqwest::Client,
+    api_base: &str,
+    management_key: Option<&str>,
+    instance: Option<Arc<ProxyInstance>>,
+    timeout: Duration,
+) -> Readiness {
+    let base_url = api_base.trim_end_matches('/');
+    let url = match management_key {
+        Some(_) => format!("{}/v0/management/config", base_url),
+        None => format!("{}/", base_url),
+    };
+
+    let deadline = Instant::now() + timeout;
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempts = 0;
+    let mut last_error;
+    let mut ever_connected = false;
+
+    loop {
+        if let Some(ref instance) = instance {
+            if !instance.is_running() {
+                return Readiness::ProcessExited;
+            }
+        }
+
+        attempts += 1;
+        let mut request = client.get(&url)
+            .header("Accept", "application/json")
+            .timeout(ATTEMPT_TIMEOUT);
+        if let Some(key) = management_key {
+            request = request.header("Authorization", format!("Bearer {}", key));
+        }
+
+        let started = Instant::now();
+        match request.send().await {
+            Ok(response) => {
+                ever_connected = true;
+                let status = response.status();
+                if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN {
+                    return Readiness::AuthRejected { http_status: status.as_u16() };
+                }
+                if status.is_success() {
+                    let (version, build_date) = version_headers(response.headers());
+                    return Readiness::Ready {
+                        latency_ms: started.elapsed().as_millis() as u64,
+                        attempts,
+                        version,
+                        build_date,
+                    };
+                }
+                last_error = Some(format!("API returned status {}", status));
+            }
+            Err(e) => {
+                if !e.is_connect() {
+                    ever_connected = true;
+                }
+                last_error = Some(e.to_string());
+            }
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            break;
+        }
+        tokio::time::sleep(backoff.min(deadline - now)).await;
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+
+    if ever_connected {
+        Readiness::Timeout { attempts, last_error }
+    } else {
+        Readiness::PortClosed { attempts }
+    }
+}
@@ -0,0 +1,210 @@
+//! A single managed CLI Proxy instance
+//!
+//! Each instance owns its child process, supervision generation and output
+//! buffer, so several proxies (e.g. standard and Plus builds on different
+//! ports) can run side by side.
+
+use std::path::PathBuf;
+use std::process::Child;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use super::config;
+use super::discovery::{AdoptedProcess, AdoptionSource};
+use super::output::OutputBuffer;
+use super::pidfile;
+use super::profile::{self, LaunchProfile};
+use super::shutdown::{self, StopResult, StopStage};
+
+/// Child process owned by an instance
+pub struct ManagedChild {
+    pub child: Child,
+    pub exe_path: PathBuf,
+    pub port: Option<u16>,
+    pub started_at: SystemTime,
+}
+
+impl ManagedChild {
+    /// Wrap a child started with `profile`, reading its port from the profile's config file
+    pub fn new(child: Child, exe_path: PathBuf, profile: &LaunchProfile) -> Self {
+        let port = exe_path.parent()
+            .and_then(|exe_dir| config::read_port(&profile.config_file(exe_dir)));
+        Self {
+            child,
+            exe_path,
+            port,
+            started_at: SystemTime::now(),
+        }
+    }
+}
+
+/// Snapshot returned by `list_proxy_instances`
+#[derive(Serialize)]
+pub struct ProxyInstanceInfo {
+    pub id: String,
+    pub running: bool,
+    pub pid: Option<u32>,
+    pub exe_path: Option<String>,
+    pub port: Option<u16>,
+    pub uptime_secs: Option<u64>,
+    /// Set when the process outlived a previous session and was adopted
+    pub adopted_from: Option<AdoptionSource>,
+}
+
+pub struct ProxyInstance {
+    pub id: String,
+    pub process: Mutex<Option<ManagedChild>>,
+    /// Live proxy from an earlier session, managed through its pidfile
+    adopted: Mutex<Option<AdoptedProcess>>,
+    /// Recent stdout/stderr lines
+    pub output: Mutex<OutputBuffer>,
+    /// Config path, args and environment used for the next start
+    profile: Mutex<LaunchProfile>,
+    /// Bumped on every start/stop so a stale supervisor knows to exit
+    generation: AtomicU64,
+}
+
+impl ProxyInstance {
+    /// Create an instance with its stored launch profile
+    pub fn new(id: String) -> Self {
+        let profile = profile::load(&id);
+        Self {
+            id,
+            process: Mutex::new(None),
+            adopted: Mutex::new(None),
+            output: Mutex::new(OutputBuffer::new()),
+            profile: Mutex::new(profile),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    /// Start a new supervision generation, invalidating any running supervisor
+    pub fn next_generation(&self) -> u64 {
+        self.generation.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    pub fn is_current_generation(&self, generation: u64) -> bool {
+        self.generation.load(Ordering::SeqCst) == generation
+    }
+
+    /// Whether the child (or adopted process) is alive, clearing it if it has exited
+    pub fn is_running(&self) -> bool {
+        if let Ok(mut guard) = self.process.lock() {
+            if let Some(ref mut managed) = *guard {
+                if managed.child.try_wait().ok().flatten().is_none() {
+                    return true;
+                }
+                *guard = None;
+            }
+        }
+        self.adopted_pid().is_some()
+    }
+
+    pub fn launch_profile(&self) -> LaunchProfile {
+        self.profile.lock().map(|guard| guard.clone()).unwrap_or_default()
+    }
+
+    pub fn set_launch_profile(&self, profile: LaunchProfile) {
+        if let Ok(mut guard) = self.profile.lock() {
+            *guard = profile;
+        }
+    }
+
+    pub fn set_adopted(&self, adopted: AdoptedProcess) {
+        if let Ok(mut guard) = self.adopted.lock() {
+            *guard = Some(adopted);
+        }
+    }
+
+    /// Pid of the adopted process if it is still alive, clearing it otherwise
+    pub fn adopted_pid(&self) -> Option<u32> {
+        let mut guard = self.adopted.lock().ok()?;
+        match *guard {
+            Some(ref adopted) if adopted.record.is_alive() => Some(adopted.record.pid),
+            _ => {
+                *guard = None;
+                None
+            }
+        }
+    }
+
+    pub fn is_adopted(&self) -> bool {
+        self.adopted.lock().is_ok_and(|guard| guard.is_some())
+    }
+
+    /// Whether the instance only watches a proxy it found running, see `AdoptedProcess::is_observed_only`
+    pub fn is_observed_only(&self) -> bool {
+        let spawned = self.process.lock().is_ok_and(|guard| guard.is_some());
+        !spawned
+            && self.adopted.lock().is_ok_and(|guard| guard.as_ref().is_some_and(AdoptedProcess::is_observed_only))
+    }
+
+    pub fn info(&self) -> ProxyInstanceInfo {
+        let running = self.is_running();
+        let mut info = ProxyInstanceInfo {
+            id: self.id.clone(),
+            running,
+            pid: None,
+            exe_path: None,
+            port: None,
+            uptime_secs: None,
+            adopted_from: None,
+        };
+
+        if let Ok(guard) = self.process.lock() {
+            if let Some(ref managed) = *guard {
+                info.pid = Some(managed.child.id());
+                info.exe_path = Some(managed.exe_path.to_string_lossy().to_string());
+                info.port = managed.port;
+                info.uptime_secs = managed.started_at.elapsed().ok().map(|d| d.as_secs());
+                return info;
+            }
+        }
+
+        if let Ok(guard) = self.adopted.lock() {
+            if let Some(ref adopted) = *guard {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0);
+                info.pid = Some(adopted.record.pid);
+                info.exe_path = Some(adopted.record.exe_path.clone());
+                info.port = adopted.port;
+                info.uptime_secs = Some(now.saturating_sub(adopted.record.start_time));
+                info.adopted_from = Some(adopted.source);
+            }
+        }
+        info
+    }
+
+    /// Stop the child or adopted process gracefully, then any orphan recorded in our pidfile
+    pub fn stop(&self, grace: Duration) -> StopResult {
+        // Detach the supervisor first so it doesn't treat this as a crash
+        self.next_generation();
+
+        // Take the child out so the supervisor isn't blocked while we wait
+        let managed = self.process.lock().ok().and_then(|mut guard| guard.take());
+        let adopted = self.adopted.lock().ok().and_then(|mut guard| guard.take());
+        let mut result = match (managed, adopted) {
+            (Some(mut managed), _) => shutdown::terminate(&mut managed.child, grace),
+            (None, Some(adopted)) => shutdown::terminate_orphan(&adopted.record, grace),
+            (None, None) => StopResult::not_running(),
+        };
+
+        // Reap an orphan we started in an earlier session, never anyone else's proxy
+        if let Some(record) = pidfile::read(&self.id) {
+            if record.is_alive() {
+                let orphan = shutdown::terminate_orphan(&record, grace);
+                if result.stage == StopStage::NotRunning {
+                    result = orphan;
+                }
+            }
+        }
+        pidfile::remove(&self.id);
+
+        result
+    }
+}
@@ -0,0 +1,97 @@
+//! CLI Proxy process management
+//!
+//! Spawning and supervision of the CLIProxyAPI child process.
+
+pub mod config;
+pub mod discovery;
+pub mod health;
+pub mod instance;
+pub mod output;
+pub mod pidfile;
+pub mod port;
+pub mod profile;
+pub mod shutdown;
+pub mod stats;
+pub mod supervisor;
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::Arc;
+
+use tauri::AppHandle;
+
+use instance::ProxyInstance;
+use profile::LaunchProfile;
+
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+
+// Windows: CREATE_NO_WINDOW flag to hide console
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+// Windows: own process group so CTRL_BREAK can be sent to the child alone
+#[cfg(windows)]
+const CREATE_NEW_PROCESS_GROUP: u32 = 0x00000200;
+
+/// `~/.zerolimit/cli_proxy`, where downloaded proxies are installed
+pub fn default_install_dir() -> Option<PathBuf> {
+    let mut dir = dirs::home_dir()?;
+    dir.push(".zerolimit");
+    dir.push("cli_proxy");
+    Some(dir)
+}
+
+/// Whether a path looks like a CLIProxyAPI executable (`cli-proxy-api*`, `CLIProxyAPI*`)
+pub fn is_proxy_exe(path: &Path) -> bool {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default().to_lowercase();
+    if !name.replace('-', "").starts_with("cliproxy") {
+        return false;
+    }
+    if cfg!(windows) {
+        name.ends_with(".exe")
+    } else {
+        !name.ends_with(".exe") && !name.ends_with(".dll") && !name.ends_with(".dylib")
+    }
+}
+
+/// Instance id made safe for use in a file name
+pub fn file_stem(instance_id: &str) -> String {
+    instance_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+
+/// Spawn the proxy executable with the arguments and environment of a launch profile
+///
+/// stdout/stderr are piped into the instance's output capture and the child
+/// is recorded in the instance's pidfile.
+pub fn spawn(
+    app: &AppHandle,
+    instance: &Arc<ProxyInstance>,
+    exe_path: &str,
+    profile: &LaunchProfile,
+) -> io::Result<Child> {
+    let exe_dir = Path::new(exe_path).parent()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Invalid path"))?;
+
+    let mut command = Command::new(exe_path);
+    command
+        .current_dir(profile.work_dir(exe_dir))
+        .args(profile.command_args(exe_dir))
+        .envs(&profile.env)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    #[cfg(windows)]
+    command.creation_flags(CREATE_NO_WINDOW | CREATE_NEW_PROCESS_GROUP);
+
+    let mut child = command.spawn()?;
+    output::capture(app, instance, &mut child);
+    if let Err(e) = pidfile::write(&instance.id, child.id(), Path::new(exe_path)) {
+        println!("Notice: Could not write pidfile for {}: {}", instance.id, e);
+    }
+    Ok(child)
+}
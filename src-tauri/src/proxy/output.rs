@@ -0,0 +1,190 @@
+//! CLI Proxy stdout/stderr capture
+//!
+//! Lines from the child are kept in an in-memory ring buffer, appended to a
+//! rotating log file under `~/.zerolimit/logs/` and streamed to the webview
+//! as `proxy://output` events.
+
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::process::Child;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use super::instance::ProxyInstance;
+
+pub const EVENT_OUTPUT: &str = "proxy://output";
+
+/// Number of lines kept in memory
+const BUFFER_CAPACITY: usize = 2000;
+const MAX_LOG_FILE_SIZE: u64 = 5 * 1024 * 1024;
+/// Rotated files kept next to the active one (`cli-proxy-<id>.log.1` ..)
+const MAX_ROTATED_FILES: usize = 3;
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// A single captured line
+#[derive(Clone, Serialize)]
+pub struct OutputLine {
+    pub seq: u64,
+    pub stream: OutputStream,
+    pub line: String,
+    /// Unix timestamp in milliseconds
+    pub timestamp: u64,
+}
+
+/// Payload of `proxy://output`
+#[derive(Clone, Serialize)]
+pub struct OutputEvent {
+    pub instance_id: String,
+    #[serde(flatten)]
+    pub line: OutputLine,
+}
+
+/// Bounded buffer of the most recent output lines
+pub struct OutputBuffer {
+    lines: VecDeque<OutputLine>,
+    next_seq: u64,
+}
+
+impl OutputBuffer {
+    pub const fn new() -> Self {
+        Self { lines: VecDeque::new(), next_seq: 1 }
+    }
+
+    pub fn push(&mut self, stream: OutputStream, line: String) -> OutputLine {
+        let entry = OutputLine {
+            seq: self.next_seq,
+            stream,
+            line,
+            timestamp: now_millis(),
+        };
+        self.next_seq += 1;
+        if self.lines.len() == BUFFER_CAPACITY {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(entry.clone());
+        entry
+    }
+
+    /// Lines with a sequence number greater than `since_seq`
+    pub fn since(&self, since_seq: Option<u64>) -> Vec<OutputLine> {
+        let since = since_seq.unwrap_or(0);
+        self.lines.iter().filter(|l| l.seq > since).cloned().collect()
+    }
+}
+
+/// Size-capped log file that rotates into numbered backups
+struct RotatingLog {
+    path: PathBuf,
+    file: Option<File>,
+    size: u64,
+}
+
+impl RotatingLog {
+    fn open(instance_id: &str) -> Option<Self> {
+        let mut dir = dirs::home_dir()?;
+        dir.push(".zerolimit");
+        dir.push("logs");
+        fs::create_dir_all(&dir).ok()?;
+
+        let path = dir.join(format!("cli-proxy-{}.log", super::file_stem(instance_id)));
+        let file = OpenOptions::new().create(true).append(true).open(&path).ok()?;
+        let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Some(Self { path, file: Some(file), size })
+    }
+
+    fn write_line(&mut self, entry: &OutputLine) {
+        let stream = match entry.stream {
+            OutputStream::Stdout => "stdout",
+            OutputStream::Stderr => "stderr",
+        };
+        let text = format!("[{}] [{}] {}\n", entry.timestamp, stream, entry.line);
+
+        if self.size + text.len() as u64 > MAX_LOG_FILE_SIZE {
+            self.rotate();
+        }
+        if let Some(ref mut file) = self.file {
+            if file.write_all(text.as_bytes()).is_ok() {
+                self.size += text.len() as u64;
+            }
+        }
+    }
+
+    fn rotate(&mut self) {
+        self.file = None;
+        let rotated = |n: usize| PathBuf::from(format!("{}.{}", self.path.display(), n));
+
+        let _ = fs::remove_file(rotated(MAX_ROTATED_FILES));
+        for n in (1..MAX_ROTATED_FILES).rev() {
+            let _ = fs::rename(rotated(n), rotated(n + 1));
+        }
+        let _ = fs::rename(&self.path, rotated(1));
+
+        self.file = OpenOptions::new().create(true).append(true).open(&self.path).ok();
+        self.size = 0;
+    }
+}
+
+/// Take the child's piped stdout/stderr and start forwarding them
+pub fn capture(app: &AppHandle, instance: &Arc<ProxyInstance>, child: &mut Child) {
+    let log = Arc::new(Mutex::new(RotatingLog::open(&instance.id)));
+
+    if let Some(stdout) = child.stdout.take() {
+        forward(app.clone(), instance.clone(), stdout, OutputStream::Stdout, log.clone());
+    }
+    if let Some(stderr) = child.stderr.take() {
+        forward(app.clone(), instance.clone(), stderr, OutputStream::Stderr, log);
+    }
+}
+
+fn forward<R: Read + Send + 'static>(
+    app: AppHandle,
+    instance: Arc<ProxyInstance>,
+    source: R,
+    stream: OutputStream,
+    log: Arc<Mutex<Option<RotatingLog>>>,
+) {
+    std::thread::spawn(move || {
+        let mut reader = BufReader::new(source);
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            match reader.read_until(b'\n', &mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {}
+            }
+            let line = String::from_utf8_lossy(&buf).trim_end_matches(['\r', '\n']).to_string();
+
+            let entry = match instance.output.lock() {
+                Ok(mut buffer) => buffer.push(stream, line),
+                Err(_) => break,
+            };
+            if let Ok(mut guard) = log.lock() {
+                if let Some(ref mut log) = *guard {
+                    log.write_line(&entry);
+                }
+            }
+            let _ = app.emit(EVENT_OUTPUT, OutputEvent {
+                instance_id: instance.id.clone(),
+                line: entry,
+            });
+        }
+    });
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
@@ -0,0 +1,189 @@
+//! Pidfiles for proxies started by ZeroLimit
+//!
+//! Every child we spawn is recorded under `~/.zerolimit/run/<instance>.json`
+//! with its pid, OS start time and exe path. A live process is only treated
+//! as ours (to adopt or reap) when all three still match, so a recycled pid
+//! or a proxy started by another tool is never touched.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessStatus, ProcessesToUpdate, System, UpdateKind};
+
+/// Start times can differ by a second depending on how the OS rounds them
+const START_TIME_TOLERANCE_SECS: u64 = 2;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PidFile {
+    pub instance_id: String,
+    pub pid: u32,
+    /// Process start time reported by the OS, seconds since the Unix epoch
+    pub start_time: u64,
+    pub exe_path: String,
+}
+
+impl PidFile {
+    /// Whether the recorded process is still alive and still the one we started
+    pub fn is_alive(&self) -> bool {
+        let Some((start_time, exe)) = process_identity(self.pid) else {
+            return false;
+        };
+        if start_time.abs_diff(self.start_time) > START_TIME_TOLERANCE_SECS {
+            return false;
+        }
+        // The exe can be unreadable (e.g. permissions); the start time already matched
+        exe.is_none_or(|exe| same_file(&exe, Path::new(&self.exe_path)))
+    }
+}
+
+/// `~/.zerolimit/run`
+pub fn run_dir() -> Option<PathBuf> {
+    let mut dir = dirs::home_dir()?;
+    dir.push(".zerolimit");
+    dir.push("run");
+    Some(dir)
+}
+
+fn pidfile_path(instance_id: &str) -> Option<PathBuf> {
+    Some(run_dir()?.join(format!("{}.json", super::file_stem(instance_id))))
+}
+
+/// Record a freshly spawned child
+pub fn write(instance_id: &str, pid: u32, exe_path: &Path) -> io::Result<PidFile> {
+    let path = pidfile_path(instance_id)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Could not determine user home directory"))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let record = identify(instance_id, pid, exe_path)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("Process {} not found", pid)))?;
+
+    let json = serde_json::to_string_pretty(&record)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    fs::write(&path, json)?;
+    Ok(record)
+}
+
+/// Identity of a live process without recording it, for proxies we didn't spawn
+pub fn identify(instance_id: &str, pid: u32, exe_path: &Path) -> Option<PidFile> {
+    let (start_time, _) = process_identity(pid)?;
+    Some(PidFile {
+        instance_id: instance_id.to_string(),
+        pid,
+        start_time,
+        exe_path: exe_path.to_string_lossy().to_string(),
+    })
+}
+
+pub fn read(instance_id: &str) -> Option<PidFile> {
+    let content = fs::read_to_string(pidfile_path(instance_id)?).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+pub fn remove(instance_id: &str) {
+    if let Some(path) = pidfile_path(instance_id) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Every pidfile in the run dir, live or stale
+pub fn list() -> Vec<PidFile> {
+    let Some(dir) = run_dir() else {
+        return Vec::new();
+    };
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect()
+}
+
+/// Drop pidfiles whose process is gone, e.g. after a reboot
+pub fn remove_stale() {
+    for record in list() {
+        if !record.is_alive() {
+            remove(&record.instance_id);
+        }
+    }
+}
+
+/// Start time and exe path of a running (non-zombie) process
+fn process_identity(pid: u32) -> Option<(u64, Option<PathBuf>)> {
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        true,
+        ProcessRefreshKind::nothing().with_exe(UpdateKind::Always),
+    );
+    let process = system.process(pid).filter(|p| p.status() != ProcessStatus::Zombie)?;
+    Some((process.start_time(), process.exe().map(Path::to_path_buf)))
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn this_process() -> PidFile {
+        let exe = std::env::current_exe().unwrap();
+        identify("test", std::process::id(), &exe).unwrap()
+    }
+
+    #[test]
+    fn matches_the_process_it_recorded() {
+        assert!(this_process().is_alive());
+    }
+
+    #[test]
+    fn rejects_a_different_start_time() {
+        let mut record = this_process();
+        record.start_time -= START_TIME_TOLERANCE_SECS + 1;
+        assert!(!record.is_alive());
+
+        let mut record = this_process();
+        record.start_time += START_TIME_TOLERANCE_SECS;
+        assert!(record.is_alive());
+    }
+
+    #[test]
+    fn rejects_a_different_exe() {
+        let dir = tempfile::tempdir().unwrap();
+        let other = dir.path().join("cli-proxy-api");
+        fs::write(&other, b"").unwrap();
+
+        let mut record = this_process();
+        record.exe_path = other.to_string_lossy().to_string();
+        assert!(!record.is_alive());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_an_exited_process() {
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+
+        let record = PidFile {
+            instance_id: "test".to_string(),
+            pid,
+            start_time: this_process().start_time,
+            exe_path: "/bin/true".to_string(),
+        };
+        assert!(!record.is_alive());
+    }
+}
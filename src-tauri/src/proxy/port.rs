@@ -0,0 +1,172 @@
+//! Port conflict detection for the proxy's listen port
+
+use std::net::{Ipv4Addr, TcpListener};
+
+use serde::Serialize;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+
+// Windows: CREATE_NO_WINDOW flag to hide console
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+/// How far above the configured port to look for a free one
+const FREE_PORT_SEARCH_RANGE: u16 = 100;
+
+/// Result of probing a port before spawning the proxy
+#[derive(Clone, Serialize)]
+pub struct PortStatus {
+    pub port: u16,
+    pub in_use: bool,
+    pub owner_pid: Option<u32>,
+    pub owner_name: Option<String>,
+}
+
+impl PortStatus {
+    /// "cli-proxy-api (pid 1234)", for error messages
+    pub fn holder(&self) -> String {
+        match (&self.owner_name, self.owner_pid) {
+            (Some(name), Some(pid)) => format!("{} (pid {})", name, pid),
+            (None, Some(pid)) => format!("pid {}", pid),
+            _ => "another process".to_string(),
+        }
+    }
+}
+
+/// Check whether `port` is bound and by whom
+pub fn probe(port: u16) -> PortStatus {
+    if is_free(port) {
+        return PortStatus { port, in_use: false, owner_pid: None, owner_name: None };
+    }
+    let owner_pid = owner_pid(port);
+    PortStatus {
+        port,
+        in_use: true,
+        owner_pid,
+        owner_name: owner_pid.and_then(process_name),
+    }
+}
+
+/// Whether nothing listens on `port`, on all interfaces or loopback
+pub fn is_free(port: u16) -> bool {
+    // Windows lets a wildcard bind succeed next to a loopback listener, so try both
+    TcpListener::bind((Ipv4Addr::UNSPECIFIED, port)).is_ok()
+        && TcpListener::bind((Ipv4Addr::LOCALHOST, port)).is_ok()
+}
+
+/// First free port above `port`, falling back to one picked by the OS
+pub fn find_free(port: u16) -> Option<u16> {
+    (1..=FREE_PORT_SEARCH_RANGE)
+        .filter_map(|offset| port.checked_add(offset))
+        .find(|candidate| is_free(*candidate))
+        .or_else(|| {
+            TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+                .and_then(|listener| listener.local_addr())
+                .map(|addr| addr.port())
+                .ok()
+        })
+}
+
+fn process_name(pid: u32) -> Option<String> {
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_processes_specifics(ProcessesToUpdate::Some(&[pid]), true, ProcessRefreshKind::nothing());
+    system.process(pid).map(|p| p.name().to_string_lossy().to_string())
+}
+
+/// Pid of the process listening on a local TCP port
+#[cfg(target_os = "linux")]
+pub fn owner_pid(port: u16) -> Option<u32> {
+    use std::fs;
+
+    let inodes: Vec<String> = ["/proc/net/tcp", "/proc/net/tcp6"]
+        .iter()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .flat_map(|content| listening_inodes(&content, port))
+        .collect();
+    if inodes.is_empty() {
+        return None;
+    }
+    let targets: Vec<String> = inodes.iter().map(|inode| format!("socket:[{}]", inode)).collect();
+
+    for entry in fs::read_dir("/proc").ok()?.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|s| s.parse::<u32>().ok()) else {
+            continue;
+        };
+        let Ok(fds) = fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        for fd in fds.flatten() {
+            if let Ok(link) = fs::read_link(fd.path()) {
+                if targets.iter().any(|t| link.as_os_str() == t.as_str()) {
+                    return Some(pid);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Socket inodes in LISTEN state on `port` from a `/proc/net/tcp*` table
+#[cfg(target_os = "linux")]
+fn listening_inodes(table: &str, port: u16) -> Vec<String> {
+    const TCP_LISTEN: &str = "0A";
+
+    table
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let local_port = fields.get(1)?.rsplit(':').next()?;
+            let state = fields.get(3)?;
+            let inode = fields.get(9)?;
+            (u16::from_str_radix(local_port, 16).ok()? == port && *state == TCP_LISTEN)
+                .then(|| inode.to_string())
+        })
+        .collect()
+}
+
+#[cfg(target_os = "macos")]
+pub fn owner_pid(port: u16) -> Option<u32> {
+    use std::process::Command;
+
+    let output = Command::new("lsof")
+        .args(["-nP", &format!("-iTCP:{}", port), "-sTCP:LISTEN", "-t"])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.trim().parse().ok())
+}
+
+#[cfg(windows)]
+pub fn owner_pid(port: u16) -> Option<u32> {
+    use std::process::Command;
+
+    let output = Command::new("netstat")
+        .args(["-ano"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .ok()?;
+    let suffix = format!(":{}", port);
+
+    String::from_utf8_lossy(&output.stdout).lines().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        // The state column is localized, so detect listeners by their empty foreign address
+        match fields.as_slice() {
+            ["TCP", local, foreign, _, pid]
+                if local.ends_with(&suffix) && (*foreign == "0.0.0.0:0" || *foreign == "[::]:0") =>
+            {
+                pid.parse().ok()
+            }
+            _ => None,
+        }
+    })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+pub fn owner_pid(_port: u16) -> Option<u32> {
+    None
+}
@@ -0,0 +1,146 @@
+//! Per-instance launch profiles
+//!
+//! A profile holds the config path, working directory, extra arguments and
+//! environment a proxy is started with, so several configurations can share
+//! one binary. Profiles live in `~/.zerolimit/profiles/<instance>.json`.
+
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::config;
+use crate::install::versions;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LaunchProfile {
+    /// Passed as `--config`; relative paths resolve against the working directory
+    pub config_path: Option<String>,
+    /// Working directory; by default the exe's directory, or the install
+    /// root for versioned installs
+    pub work_dir: Option<String>,
+    /// Extra arguments appended after `--config`
+    pub args: Vec<String>,
+    /// Extra environment variables, e.g. `HTTPS_PROXY`
+    pub env: BTreeMap<String, String>,
+}
+
+impl LaunchProfile {
+    /// Working directory for a proxy whose binary lives in `exe_dir`
+    pub fn work_dir(&self, exe_dir: &Path) -> PathBuf {
+        match self.work_dir.as_deref().filter(|dir| !dir.is_empty()) {
+            Some(dir) => exe_dir.join(dir),
+            None => versions::versions_root_of(exe_dir).unwrap_or_else(|| exe_dir.to_path_buf()),
+        }
+    }
+
+    /// Config file the proxy will read, `config.yaml` in the working directory by default
+    pub fn config_file(&self, exe_dir: &Path) -> PathBuf {
+        let work_dir = self.work_dir(exe_dir);
+        match self.config_path.as_deref().filter(|path| !path.is_empty()) {
+            Some(path) => work_dir.join(path),
+            None => work_dir.join(config::CONFIG_FILE_NAME),
+        }
+    }
+
+    /// Command line arguments for the proxy binary
+    pub fn command_args(&self, exe_dir: &Path) -> Vec<OsString> {
+        let mut args = Vec::new();
+        if self.config_path.as_deref().is_some_and(|path| !path.is_empty()) {
+            args.push(OsString::from("--config"));
+            args.push(self.config_file(exe_dir).into_os_string());
+        }
+        args.extend(self.args.iter().map(OsString::from));
+        args
+    }
+
+    /// Check the profile before it is saved
+    pub fn validate(&self) -> Result<(), String> {
+        for key in self.env.keys() {
+            if key.is_empty() || key.contains('=') || key.contains('\0') {
+                return Err(format!("Invalid environment variable name: {:?}", key));
+            }
+        }
+        if self.env.values().chain(&self.args).any(|value| value.contains('\0')) {
+            return Err("Arguments and environment values cannot contain NUL bytes".to_string());
+        }
+        if self.args.iter().any(|arg| arg == "--config" || arg == "-config") && self.config_path.is_some() {
+            return Err("Set the config path or pass --config in args, not both".to_string());
+        }
+        if let Some(dir) = self.work_dir.as_deref().filter(|dir| Path::new(dir).is_absolute()) {
+            if !Path::new(dir).is_dir() {
+                return Err(format!("Working directory does not exist: {}", dir));
+            }
+        }
+        if let Some(path) = self.config_path.as_deref().filter(|path| Path::new(path).is_absolute()) {
+            if !Path::new(path).is_file() {
+                return Err(format!("Config file does not exist: {}", path));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `~/.zerolimit/profiles`
+pub fn profiles_dir() -> Option<PathBuf> {
+    let mut dir = dirs::home_dir()?;
+    dir.push(".zerolimit");
+    dir.push("profiles");
+    Some(dir)
+}
+
+fn profile_path(instance_id: &str) -> Option<PathBuf> {
+    Some(profiles_dir()?.join(format!("{}.json", super::file_stem(instance_id))))
+}
+
+/// Stored profile for an instance, or the default one
+pub fn load(instance_id: &str) -> LaunchProfile {
+    profile_path(instance_id)
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save(instance_id: &str, profile: &LaunchProfile) -> io::Result<()> {
+    let path = profile_path(instance_id)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Could not determine user home directory"))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let json = serde_json::to_string_pretty(profile)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    fs::write(path, json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versioned_builds_read_the_root_config() {
+        let root = tempfile::tempdir().unwrap();
+        let exe_dir = versions::versions_dir(root.path()).join("v6.1.0");
+        fs::create_dir_all(&exe_dir).unwrap();
+        fs::write(root.path().join(versions::POINTER_FILE_NAME), "{}").unwrap();
+
+        let profile = LaunchProfile::default();
+        assert_eq!(profile.work_dir(&exe_dir), root.path());
+        assert_eq!(profile.config_file(&exe_dir), root.path().join(config::CONFIG_FILE_NAME));
+        // Found by the proxy in its working directory, so no `--config` is passed
+        assert!(profile.command_args(&exe_dir).is_empty());
+    }
+
+    #[test]
+    fn unversioned_builds_read_their_own_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let profile = LaunchProfile::default();
+        assert_eq!(profile.config_file(dir.path()), dir.path().join(config::CONFIG_FILE_NAME));
+
+        let profile = LaunchProfile { config_path: Some("work.yaml".to_string()), ..Default::default() };
+        assert_eq!(profile.config_file(dir.path()), dir.path().join("work.yaml"));
+    }
+}
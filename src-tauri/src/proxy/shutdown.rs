@@ -0,0 +1,183 @@
+//! Graceful shutdown of the proxy child
+//!
+//! The child is first asked to exit (SIGTERM on Unix, CTRL_BREAK on
+//! Windows) so in-flight requests and auth-file writes can finish, and is
+//! only force killed once the grace period runs out.
+
+use std::process::{Child, ExitStatus};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use super::pidfile::PidFile;
+
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+
+// Windows: CREATE_NO_WINDOW flag to hide console
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+pub const DEFAULT_GRACE_PERIOD: Duration = Duration::from_secs(5);
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Which stage ended the process
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StopStage {
+    /// No child was being managed
+    NotRunning,
+    /// The child had already exited on its own
+    AlreadyExited,
+    /// The child exited after the graceful signal
+    Graceful,
+    /// The grace period ran out and the child was force killed
+    Forced,
+}
+
+/// Result of `stop_cli_proxy`
+#[derive(Clone, Serialize)]
+pub struct StopResult {
+    pub stage: StopStage,
+    pub pid: Option<u32>,
+    pub exit_code: Option<i32>,
+    pub elapsed_ms: u64,
+}
+
+impl StopResult {
+    pub fn not_running() -> Self {
+        Self { stage: StopStage::NotRunning, pid: None, exit_code: None, elapsed_ms: 0 }
+    }
+}
+
+/// Ask the child to exit, escalating to a hard kill after `grace`
+pub fn terminate(child: &mut Child, grace: Duration) -> StopResult {
+    let started = Instant::now();
+    let pid = child.id();
+    let finish = |stage, status: Option<ExitStatus>| StopResult {
+        stage,
+        pid: Some(pid),
+        exit_code: status.and_then(|s| s.code()),
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    };
+
+    if let Ok(Some(status)) = child.try_wait() {
+        return finish(StopStage::AlreadyExited, Some(status));
+    }
+
+    if !grace.is_zero() && request_exit(pid) {
+        if let Some(status) = wait_timeout(child, grace) {
+            return finish(StopStage::Graceful, Some(status));
+        }
+    }
+
+    force_kill(child);
+    finish(StopStage::Forced, child.wait().ok())
+}
+
+/// Same as `terminate` for an orphan we only have a pidfile for
+pub fn terminate_orphan(record: &PidFile, grace: Duration) -> StopResult {
+    let started = Instant::now();
+    let finish = |stage| StopResult {
+        stage,
+        pid: Some(record.pid),
+        exit_code: None,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    };
+
+    if !record.is_alive() {
+        return finish(StopStage::AlreadyExited);
+    }
+
+    if !grace.is_zero() && request_exit(record.pid) && wait_until_gone(record, grace) {
+        return finish(StopStage::Graceful);
+    }
+
+    force_kill_pid(record.pid);
+    wait_until_gone(record, grace.max(WAIT_POLL_INTERVAL));
+    finish(StopStage::Forced)
+}
+
+fn wait_until_gone(record: &PidFile, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    while record.is_alive() {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        std::thread::sleep(WAIT_POLL_INTERVAL);
+    }
+    true
+}
+
+fn wait_timeout(child: &mut Child, timeout: Duration) -> Option<ExitStatus> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => return Some(status),
+            Ok(None) if Instant::now() < deadline => std::thread::sleep(WAIT_POLL_INTERVAL),
+            _ => return None,
+        }
+    }
+}
+
+#[cfg(unix)]
+fn request_exit(pid: u32) -> bool {
+    // SAFETY: kill(2) has no memory-safety preconditions
+    unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) == 0 }
+}
+
+/// Send CTRL_BREAK to the child's process group
+///
+/// The child is spawned with `CREATE_NEW_PROCESS_GROUP`, so its pid is also
+/// its group id. We briefly attach to its (hidden) console to deliver the
+/// event, ignoring it ourselves while attached.
+#[cfg(windows)]
+fn request_exit(pid: u32) -> bool {
+    use windows_sys::Win32::System::Console::{
+        AttachConsole, FreeConsole, GenerateConsoleCtrlEvent, SetConsoleCtrlHandler, CTRL_BREAK_EVENT,
+    };
+
+    // SAFETY: plain Win32 calls without pointer arguments
+    unsafe {
+        FreeConsole();
+        if AttachConsole(pid) == 0 {
+            return false;
+        }
+        SetConsoleCtrlHandler(None, 1);
+        let sent = GenerateConsoleCtrlEvent(CTRL_BREAK_EVENT, pid) != 0;
+        FreeConsole();
+        // Give the event time to be dispatched before we stop ignoring it
+        std::thread::sleep(WAIT_POLL_INTERVAL);
+        SetConsoleCtrlHandler(None, 0);
+        sent
+    }
+}
+
+#[cfg(windows)]
+fn force_kill(child: &mut Child) {
+    force_kill_pid(child.id());
+}
+
+#[cfg(not(windows))]
+fn force_kill(child: &mut Child) {
+    let _ = child.kill();
+}
+
+// On Windows, use taskkill for reliable termination of the whole tree
+#[cfg(windows)]
+fn force_kill_pid(pid: u32) {
+    use std::process::Command;
+
+    let _ = Command::new("taskkill")
+        .args(["/F", "/T", "/PID", &pid.to_string()])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output();
+}
+
+#[cfg(unix)]
+fn force_kill_pid(pid: u32) {
+    // SAFETY: kill(2) has no memory-safety preconditions
+    unsafe {
+        libc::kill(pid as libc::pid_t, libc::SIGKILL);
+    }
+}
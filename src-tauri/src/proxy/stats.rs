@@ -0,0 +1,242 @@
+//! Resource usage of running proxy processes
+//!
+//! CPU is reported as a percentage of one core, averaged since the previous
+//! sample of the same process. On Linux everything is read from `/proc`;
+//! elsewhere sysinfo is used, with native calls for threads and handles.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::state::AppState;
+
+pub const EVENT_STATS: &str = "proxy://stats";
+pub const STATS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Payload of `get_proxy_stats` and `proxy://stats`
+#[derive(Clone, Serialize)]
+pub struct ProxyStats {
+    pub instance_id: String,
+    pub pid: u32,
+    /// Percent of one core; above 100 when several cores are busy
+    pub cpu_percent: f64,
+    pub rss_bytes: u64,
+    /// Open file descriptors (handles on Windows)
+    pub open_fds: Option<u64>,
+    pub threads: Option<u64>,
+    pub uptime_secs: Option<u64>,
+}
+
+/// Raw counters read from the OS
+struct ProcessSample {
+    cpu_time: Duration,
+    rss_bytes: u64,
+    open_fds: Option<u64>,
+    threads: Option<u64>,
+}
+
+/// Remembers the previous CPU time per instance to turn totals into a rate
+#[derive(Default)]
+pub struct StatsSampler {
+    previous: Mutex<HashMap<String, (u32, Duration, Instant)>>,
+}
+
+impl StatsSampler {
+    /// Sample a process, or `None` if it is gone
+    pub fn sample(&self, instance_id: &str, pid: u32, uptime_secs: Option<u64>) -> Option<ProxyStats> {
+        let sample = read_process(pid)?;
+        let now = Instant::now();
+
+        let mut previous = self.previous.lock().unwrap_or_else(|e| e.into_inner());
+        let cpu_percent = match previous.get(instance_id) {
+            Some(&(prev_pid, prev_cpu, prev_at)) if prev_pid == pid => {
+                let wall = now.duration_since(prev_at).as_secs_f64();
+                let cpu = sample.cpu_time.saturating_sub(prev_cpu).as_secs_f64();
+                if wall > 0.0 { cpu / wall * 100.0 } else { 0.0 }
+            }
+            // First sample of this process: average over its lifetime
+            _ => match uptime_secs.filter(|secs| *secs > 0) {
+                Some(secs) => sample.cpu_time.as_secs_f64() / secs as f64 * 100.0,
+                None => 0.0,
+            },
+        };
+        previous.insert(instance_id.to_string(), (pid, sample.cpu_time, now));
+
+        Some(ProxyStats {
+            instance_id: instance_id.to_string(),
+            pid,
+            cpu_percent: (cpu_percent * 10.0).round() / 10.0,
+            rss_bytes: sample.rss_bytes,
+            open_fds: sample.open_fds,
+            threads: sample.threads,
+            uptime_secs,
+        })
+    }
+
+    pub fn forget(&self, instance_id: &str) {
+        let mut previous = self.previous.lock().unwrap_or_else(|e| e.into_inner());
+        previous.remove(instance_id);
+    }
+}
+
+/// Emit `proxy://stats` for every running instance every `STATS_INTERVAL`
+pub fn spawn_reporter(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(STATS_INTERVAL).await;
+            let app = app.clone();
+            let stats = tauri::async_runtime::spawn_blocking(move || {
+                let state = app.state::<AppState>();
+                let stats: Vec<ProxyStats> = state
+                    .instances()
+                    .iter()
+                    .filter_map(|instance| {
+                        let info = instance.info();
+                        match info.pid.filter(|_| info.running) {
+                            Some(pid) => state.stats().sample(&info.id, pid, info.uptime_secs),
+                            None => {
+                                state.stats().forget(&info.id);
+                                None
+                            }
+                        }
+                    })
+                    .collect();
+                for entry in &stats {
+                    let _ = app.emit(EVENT_STATS, entry);
+                }
+            })
+            .await;
+            if stats.is_err() {
+                return;
+            }
+        }
+    });
+}
+
+#[cfg(target_os = "linux")]
+fn read_process(pid: u32) -> Option<ProcessSample> {
+    use std::fs;
+
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // The command name may contain spaces, so split after its closing paren
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    // Offsets from field 3 (state) in proc(5)
+    let field = |n: usize| fields.get(n - 3).and_then(|v| v.parse::<u64>().ok());
+    if fields.first() == Some(&"Z") {
+        return None;
+    }
+
+    // SAFETY: sysconf(3) only reads a configuration value; errors come back as -1
+    let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) }.max(1) as f64;
+    // SAFETY: as above
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) }.max(1) as u64;
+    let cpu_ticks = field(14)? + field(15)?;
+    let open_fds = fs::read_dir(format!("/proc/{}/fd", pid))
+        .ok()
+        .map(|entries| entries.count() as u64);
+
+    Some(ProcessSample {
+        cpu_time: Duration::from_secs_f64(cpu_ticks as f64 / ticks),
+        rss_bytes: field(24)? * page_size,
+        open_fds,
+        threads: field(20),
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_process(pid: u32) -> Option<ProcessSample> {
+    use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+    let sys_pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[sys_pid]),
+        true,
+        ProcessRefreshKind::nothing().with_cpu().with_memory(),
+    );
+    let process = system.process(sys_pid)?;
+    let (open_fds, threads) = native_counts(pid);
+
+    Some(ProcessSample {
+        cpu_time: Duration::from_millis(process.accumulated_cpu_time()),
+        rss_bytes: process.memory(),
+        open_fds,
+        threads,
+    })
+}
+
+/// Open descriptors and thread count through `proc_pidinfo`
+#[cfg(target_os = "macos")]
+fn native_counts(pid: u32) -> (Option<u64>, Option<u64>) {
+    use std::mem;
+
+    let pid = pid as libc::c_int;
+    // With no buffer, PROC_PIDLISTFDS returns the size needed for all descriptors
+    let fds_size = unsafe { libc::proc_pidinfo(pid, libc::PROC_PIDLISTFDS, 0, std::ptr::null_mut(), 0) };
+    let open_fds = (fds_size > 0).then(|| fds_size as u64 / mem::size_of::<libc::proc_fdinfo>() as u64);
+
+    let mut info: libc::proc_taskinfo = unsafe { mem::zeroed() };
+    let size = mem::size_of::<libc::proc_taskinfo>() as libc::c_int;
+    let written = unsafe {
+        libc::proc_pidinfo(pid, libc::PROC_PIDTASKINFO, 0, &mut info as *mut _ as *mut libc::c_void, size)
+    };
+    let threads = (written == size).then_some(info.pti_threadnum as u64);
+
+    (open_fds, threads)
+}
+
+/// Handle count and thread count through the Win32 API
+#[cfg(windows)]
+fn native_counts(pid: u32) -> (Option<u64>, Option<u64>) {
+    use std::mem;
+    use windows_sys::Win32::Foundation::{CloseHandle, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W, TH32CS_SNAPPROCESS,
+    };
+    use windows_sys::Win32::System::Threading::{
+        GetProcessHandleCount, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    let handles = unsafe {
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if process.is_null() {
+            None
+        } else {
+            let mut count = 0u32;
+            let ok = GetProcessHandleCount(process, &mut count);
+            CloseHandle(process);
+            (ok != 0).then_some(count as u64)
+        }
+    };
+
+    let threads = unsafe {
+        let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0);
+        if snapshot == INVALID_HANDLE_VALUE {
+            None
+        } else {
+            let mut entry: PROCESSENTRY32W = mem::zeroed();
+            entry.dwSize = mem::size_of::<PROCESSENTRY32W>() as u32;
+            let mut found = None;
+            let mut more = Process32FirstW(snapshot, &mut entry) != 0;
+            while more {
+                if entry.th32ProcessID == pid {
+                    found = Some(entry.cntThreads as u64);
+                    break;
+                }
+                more = Process32NextW(snapshot, &mut entry) != 0;
+            }
+            CloseHandle(snapshot);
+            found
+        }
+    };
+
+    (handles, threads)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn native_counts(_pid: u32) -> (Option<u64>, Option<u64>) {
+    (None, None)
+}
@@ -0,0 +1,317 @@
+//! Supervisor task for the CLI Proxy process
+//!
+//! Watches the child owned by a proxy instance, restarts it with
+//! exponential backoff when it exits on its own, and gives up once it
+//! crashes too often inside the crash window.
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::process::ExitStatus;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use super::instance::{ManagedChild, ProxyInstance};
+use super::pidfile;
+use super::profile::LaunchProfile;
+
+pub const EVENT_EXITED: &str = "proxy://exited";
+pub const EVENT_RESTARTED: &str = "proxy://restarted";
+pub const EVENT_GAVE_UP: &str = "proxy://gave-up";
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Adopted processes are checked through the process table, so less often
+const ADOPTED_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// A child that stayed up this long resets the backoff
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+/// Give up after this many crashes inside `CRASH_WINDOW`
+const MAX_CRASHES: usize = 5;
+const CRASH_WINDOW: Duration = Duration::from_secs(120);
+
+/// Payload of `proxy://exited`
+#[derive(Clone, Serialize)]
+pub struct ProxyExitedEvent {
+    pub instance_id: String,
+    pub pid: u32,
+    pub code: Option<i32>,
+    pub signal: Option<i32>,
+    pub will_restart: bool,
+}
+
+/// Payload of `proxy://restarted`
+#[derive(Clone, Serialize)]
+pub struct ProxyRestartedEvent {
+    pub instance_id: String,
+    pub pid: u32,
+    pub attempt: u32,
+    pub previous_code: Option<i32>,
+}
+
+/// Payload of `proxy://gave-up`
+#[derive(Clone, Serialize)]
+pub struct ProxyGaveUpEvent {
+    pub instance_id: String,
+    pub crashes: usize,
+    pub window_secs: u64,
+    pub last_code: Option<i32>,
+    pub error: Option<String>,
+}
+
+/// Start supervising the current child for the given generation
+///
+/// Restarts reuse `profile`, so profile changes only apply on the next manual start.
+pub fn spawn(app: AppHandle, instance: Arc<ProxyInstance>, exe_path: String, profile: LaunchProfile, generation: u64) {
+    tauri::async_runtime::spawn(async move {
+        supervise(app, instance, exe_path, profile, generation).await;
+    });
+}
+
+async fn supervise(
+    app: AppHandle,
+    instance: Arc<ProxyInstance>,
+    exe_path: String,
+    profile: LaunchProfile,
+    generation: u64,
+) {
+    let mut crashes: VecDeque<Instant> = VecDeque::new();
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempt: u32 = 0;
+    let mut started_at = Instant::now();
+
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+
+        let (pid, status) = match poll_child(&instance, generation) {
+            Poll::Running => continue,
+            Poll::Gone => return,
+            Poll::Exited(pid, status) => (pid, status),
+        };
+
+        let code = status.code();
+        let now = Instant::now();
+        if now.duration_since(started_at) >= STABLE_AFTER {
+            backoff = INITIAL_BACKOFF;
+        }
+        record_crash(&mut crashes, now);
+        let will_restart = crashes.len() < MAX_CRASHES;
+
+        let _ = app.emit(EVENT_EXITED, ProxyExitedEvent {
+            instance_id: instance.id.clone(),
+            pid,
+            code,
+            signal: exit_signal(&status),
+            will_restart,
+        });
+
+        if !will_restart {
+            give_up(&app, &instance, crashes.len(), code, None);
+            return;
+        }
+
+        // Keep retrying the spawn itself until it works or we hit the crash limit
+        loop {
+            tokio::time::sleep(backoff).await;
+            backoff = next_backoff(backoff);
+            attempt += 1;
+
+            match respawn(&app, &instance, &exe_path, &profile, generation) {
+                Respawn::Superseded => return,
+                Respawn::Started(new_pid) => {
+                    started_at = Instant::now();
+                    let _ = app.emit(EVENT_RESTARTED, ProxyRestartedEvent {
+                        instance_id: instance.id.clone(),
+                        pid: new_pid,
+                        attempt,
+                        previous_code: code,
+                    });
+                    break;
+                }
+                Respawn::Failed(err) => {
+                    record_crash(&mut crashes, Instant::now());
+                    if crashes.len() >= MAX_CRASHES {
+                        give_up(&app, &instance, crashes.len(), code, Some(err));
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Watch an adopted process and report when it goes away
+///
+/// We don't own its stdio or exit status, so it is not restarted.
+pub fn watch_adopted(app: AppHandle, instance: Arc<ProxyInstance>) {
+    tauri::async_runtime::spawn(async move {
+        let Some(pid) = instance.adopted_pid() else {
+            return;
+        };
+        loop {
+            tokio::time::sleep(ADOPTED_POLL_INTERVAL).await;
+            if !instance.is_adopted() {
+                // Stopped through `stop_cli_proxy`
+                return;
+            }
+            if instance.adopted_pid().is_none() {
+                pidfile::remove(&instance.id);
+                let _ = app.emit(EVENT_EXITED, ProxyExitedEvent {
+                    instance_id: instance.id.clone(),
+                    pid,
+                    code: None,
+                    signal: None,
+                    will_restart: false,
+                });
+                return;
+            }
+        }
+    });
+}
+
+enum Poll {
+    Running,
+    /// Stopped by the user or replaced by a newer start
+    Gone,
+    Exited(u32, ExitStatus),
+}
+
+fn poll_child(instance: &ProxyInstance, generation: u64) -> Poll {
+    let Ok(mut guard) = instance.process.lock() else {
+        return Poll::Gone;
+    };
+    if !instance.is_current_generation(generation) {
+        return Poll::Gone;
+    }
+    let Some(ref mut managed) = *guard else {
+        return Poll::Gone;
+    };
+
+    match managed.child.try_wait() {
+        Ok(None) => Poll::Running,
+        Ok(Some(status)) => {
+            let pid = managed.child.id();
+            *guard = None;
+            Poll::Exited(pid, status)
+        }
+        Err(_) => Poll::Running,
+    }
+}
+
+enum Respawn {
+    Started(u32),
+    Superseded,
+    Failed(String),
+}
+
+fn respawn(
+    app: &AppHandle,
+    instance: &Arc<ProxyInstance>,
+    exe_path: &str,
+    profile: &LaunchProfile,
+    generation: u64,
+) -> Respawn {
+    let Ok(mut guard) = instance.process.lock() else {
+        return Respawn::Superseded;
+    };
+    if !instance.is_current_generation(generation) || guard.is_some() {
+        return Respawn::Superseded;
+    }
+
+    match super::spawn(app, instance, exe_path, profile) {
+        Ok(child) => {
+            let pid = child.id();
+            *guard = Some(ManagedChild::new(child, PathBuf::from(exe_path), profile));
+            Respawn::Started(pid)
+        }
+        Err(e) => Respawn::Failed(e.to_string()),
+    }
+}
+
+/// Double the wait before the next restart, up to `MAX_BACKOFF`
+fn next_backoff(backoff: Duration) -> Duration {
+    (backoff * 2).min(MAX_BACKOFF)
+}
+
+/// Add a crash and forget the ones that fell out of `CRASH_WINDOW`
+fn record_crash(crashes: &mut VecDeque<Instant>, now: Instant) {
+    crashes.push_back(now);
+    while let Some(first) = crashes.front() {
+        if now.duration_since(*first) > CRASH_WINDOW {
+            crashes.pop_front();
+        } else {
+            break;
+        }
+    }
+}
+
+fn give_up(app: &AppHandle, instance: &ProxyInstance, crashes: usize, last_code: Option<i32>, error: Option<String>) {
+    let _ = app.emit(EVENT_GAVE_UP, ProxyGaveUpEvent {
+        instance_id: instance.id.clone(),
+        crashes,
+        window_secs: CRASH_WINDOW.as_secs(),
+        last_code,
+        error,
+    });
+}
+
+#[cfg(unix)]
+fn exit_signal(status: &ExitStatus) -> Option<i32> {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal()
+}
+
+#[cfg(not(unix))]
+fn exit_signal(_status: &ExitStatus) -> Option<i32> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let mut backoff = INITIAL_BACKOFF;
+        let mut waits = Vec::new();
+        for _ in 0..7 {
+            waits.push(backoff.as_secs());
+            backoff = next_backoff(backoff);
+        }
+        assert_eq!(waits, [1, 2, 4, 8, 16, 30, 30]);
+    }
+
+    #[test]
+    fn forgets_crashes_outside_the_window() {
+        let start = Instant::now();
+        let mut crashes = VecDeque::new();
+        for secs in [0, 10, 20] {
+            record_crash(&mut crashes, start + Duration::from_secs(secs));
+        }
+        assert_eq!(crashes.len(), 3);
+
+        // Only the crash at 0s is older than the window by now
+        record_crash(&mut crashes, start + CRASH_WINDOW + Duration::from_secs(5));
+        assert_eq!(crashes.len(), 3);
+        assert_eq!(crashes.front(), Some(&(start + Duration::from_secs(10))));
+    }
+
+    #[test]
+    fn gives_up_after_max_crashes_in_the_window() {
+        let start = Instant::now();
+        let mut crashes = VecDeque::new();
+        for i in 0..MAX_CRASHES as u64 {
+            record_crash(&mut crashes, start + Duration::from_secs(i * 10));
+        }
+        assert!(crashes.len() >= MAX_CRASHES);
+
+        // The same crashes spread over more than the window never add up
+        let mut crashes = VecDeque::new();
+        for i in 0..MAX_CRASHES as u64 * 2 {
+            record_crash(&mut crashes, start + CRASH_WINDOW / 2 * i as u32 + Duration::from_secs(i));
+        }
+        assert!(crashes.len() < MAX_CRASHES);
+    }
+}
@@ -0,0 +1,336 @@
+//! Desktop notifications for quota thresholds, resets and expired tokens
+//!
+//! Every fresh snapshot is checked against the rules, also while the window
+//! is hidden, because the poller publishes through here. Thresholds are
+//! percentages used, per provider and optionally per model. Each threshold
+//! of a model fires once; after the model drops back under its lowest
+//! threshold it counts as reset, which may be announced and arms the
+//! thresholds again. An expired token is announced once until the account
+//! answers again. During quiet hours the rules still run, so nothing piles
+//! up, but no notification is shown.
+//! Settings live in `~/.zerolimit/alerts.json`.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use chrono::{Local, NaiveTime};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use super::{Provider, QuotaErrorKind, QuotaSnapshot};
+use crate::state::AppState;
+
+/// Thresholds of one provider, or of one of its models
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ThresholdRule {
+    pub provider: Provider,
+    /// Window name as shown on the quota page; all models when empty
+    pub model: Option<String>,
+    pub thresholds: Vec<u8>,
+}
+
+/// Local time span without notifications; may wrap past midnight
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl QuietHours {
+    fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AlertSettings {
+    pub enabled: bool,
+    /// Percent used, for models without a rule
+    pub thresholds: Vec<u8>,
+    pub rules: Vec<ThresholdRule>,
+    pub notify_reset: bool,
+    pub notify_token_expired: bool,
+    pub quiet_hours: Option<QuietHours>,
+}
+
+impl Default for AlertSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            thresholds: vec![75, 90, 100],
+            rules: Vec::new(),
+            notify_reset: true,
+            notify_token_expired: true,
+            quiet_hours: None,
+        }
+    }
+}
+
+impl AlertSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        let lists = std::iter::once(&self.thresholds).chain(self.rules.iter().map(|rule| &rule.thresholds));
+        for thresholds in lists {
+            if thresholds.iter().any(|t| *t == 0 || *t > 100) {
+                return Err("Thresholds must be between 1 and 100%".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// The model's rule, else the provider's, else the defaults
+    fn thresholds(&self, provider: Provider, model: &str) -> &[u8] {
+        let for_model = self.rules.iter().find(|rule| {
+            rule.provider == provider && rule.model.as_deref().is_some_and(|m| m.trim().eq_ignore_ascii_case(model))
+        });
+        let for_provider = || {
+            self.rules
+                .iter()
+                .find(|rule| rule.provider == provider && rule.model.as_deref().is_none_or(|m| m.trim().is_empty()))
+        };
+        for_model.or_else(for_provider).map_or(&self.thresholds, |rule| &rule.thresholds)
+    }
+}
+
+/// `~/.zerolimit/alerts.json`
+fn settings_path() -> Option<PathBuf> {
+    let mut path = dirs::home_dir()?;
+    path.push(".zerolimit");
+    path.push("alerts.json");
+    Some(path)
+}
+
+/// Stored settings, or the defaults
+pub fn load() -> AlertSettings {
+    settings_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save(settings: &AlertSettings) -> io::Result<()> {
+    let path = settings_path()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Could not determine user home directory"))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let json = serde_json::to_string_pretty(settings)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    fs::write(path, json)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Alert {
+    pub title: String,
+    pub body: String,
+}
+
+/// What has been announced already
+#[derive(Default)]
+struct Announced {
+    /// Highest threshold fired per provider, account and model
+    thresholds: HashMap<(Provider, String, String), u8>,
+    /// Accounts whose expired token was announced
+    expired: HashSet<(Provider, String)>,
+}
+
+pub struct AlertEngine {
+    settings: Mutex<AlertSettings>,
+    announced: Mutex<Announced>,
+}
+
+impl Default for AlertEngine {
+    fn default() -> Self {
+        Self {
+            settings: Mutex::new(load()),
+            announced: Mutex::default(),
+        }
+    }
+}
+
+impl AlertEngine {
+    pub fn settings(&self) -> AlertSettings {
+        self.settings.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn apply(&self, settings: AlertSettings) {
+        *self.settings.lock().unwrap_or_else(|e| e.into_inner()) = settings;
+    }
+
+    /// Alerts to show for a fresh snapshot right now
+    pub fn check(&self, snapshot: &QuotaSnapshot) -> Vec<Alert> {
+        let settings = self.settings();
+        if !settings.enabled {
+            return Vec::new();
+        }
+        let mut announced = self.announced.lock().unwrap_or_else(|e| e.into_inner());
+        let alerts = evaluate(&settings, &mut announced, snapshot);
+        let quiet = settings.quiet_hours.as_ref().is_some_and(|quiet| quiet.contains(Local::now().time()));
+        if quiet { Vec::new() } else { alerts }
+    }
+}
+
+fn evaluate(settings: &AlertSettings, announced: &mut Announced, snapshot: &QuotaSnapshot) -> Vec<Alert> {
+    let provider = provider_name(snapshot.provider);
+    let account = snapshot.email.as_deref().unwrap_or(&snapshot.auth_index);
+    let mut alerts = Vec::new();
+
+    let account_key = (snapshot.provider, snapshot.auth_index.clone());
+    if let Some(error) = &snapshot.error {
+        if error.kind == QuotaErrorKind::TokenExpired
+            && announced.expired.insert(account_key)
+            && settings.notify_token_expired
+        {
+            alerts.push(Alert {
+                title: format!("{} token expired", provider),
+                body: format!("{}: {}", account, error.message),
+            });
+        }
+        return alerts;
+    }
+    announced.expired.remove(&account_key);
+
+    for window in &snapshot.windows {
+        let used = 100.0 - window.remaining_percent;
+        let key = (snapshot.provider, snapshot.auth_index.clone(), window.name.clone());
+        let fired = announced.thresholds.get(&key).copied().unwrap_or(0);
+        let crossed = settings
+            .thresholds(snapshot.provider, &window.name)
+            .iter()
+            .copied()
+            .filter(|threshold| used >= f64::from(*threshold))
+            .max();
+
+        match crossed {
+            Some(threshold) if threshold > fired => {
+                announced.thresholds.insert(key, threshold);
+                let resets = window
+                    .resets_at
+                    .map(|at| format!(", resets {}", at.with_timezone(&Local).format("%a %H:%M")))
+                    .unwrap_or_default();
+                alerts.push(Alert {
+                    title: format!("{} {} at {}%", provider, window.name, threshold),
+                    body: format!("{}: {:.0}% left{}", account, window.remaining_percent, resets),
+                });
+            }
+            Some(_) => {}
+            None if fired > 0 => {
+                announced.thresholds.remove(&key);
+                if settings.notify_reset {
+                    alerts.push(Alert {
+                        title: format!("{} {} has reset", provider, window.name),
+                        body: format!("{}: {:.0}% left", account, window.remaining_percent),
+                    });
+                }
+            }
+            None => {}
+        }
+    }
+    alerts
+}
+
+fn provider_name(provider: Provider) -> &'static str {
+    match provider {
+        Provider::Claude => "Claude",
+        Provider::Antigravity => "Antigravity",
+        Provider::Codex => "Codex",
+        Provider::GeminiCli => "Gemini CLI",
+        Provider::Kiro => "Kiro",
+        Provider::Copilot => "Copilot",
+    }
+}
+
+/// Show the alerts a snapshot triggers as native notifications
+pub fn notify(app: &AppHandle, snapshot: &QuotaSnapshot) {
+    for alert in app.state::<AppState>().alerts().check(snapshot) {
+        if let Err(e) = app.notification().builder().title(&alert.title).body(&alert.body).show() {
+            println!("Notice: Failed to show notification: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{test_now, QuotaError, QuotaWindow};
+    use super::*;
+
+    fn snapshot(provider: Provider, windows: &[(&str, f64)]) -> QuotaSnapshot {
+        QuotaSnapshot {
+            provider,
+            auth_index: "claude-me.json".to_string(),
+            fetched_at: test_now(),
+            plan: None,
+            email: Some("me@example.com".to_string()),
+            windows: windows.iter().map(|(name, pct)| QuotaWindow::new(*name, *pct, None)).collect(),
+            error: None,
+        }
+    }
+
+    fn titles(alerts: Vec<Alert>) -> Vec<String> {
+        alerts.into_iter().map(|alert| alert.title).collect()
+    }
+
+    #[test]
+    fn fires_each_threshold_once_until_reset() {
+        let settings = AlertSettings::default();
+        let mut announced = Announced::default();
+        let mut check = |remaining: f64| {
+            titles(evaluate(&settings, &mut announced, &snapshot(Provider::Claude, &[("five-hour-session", remaining)])))
+        };
+
+        assert!(check(50.0).is_empty());
+        assert_eq!(check(20.0), ["Claude five-hour-session at 75%"]);
+        assert!(check(18.0).is_empty());
+        // Jumping past a threshold only reports the highest one
+        assert_eq!(check(0.0), ["Claude five-hour-session at 100%"]);
+        assert!(check(5.0).is_empty());
+        assert_eq!(check(100.0), ["Claude five-hour-session has reset"]);
+        assert!(check(100.0).is_empty());
+        assert_eq!(check(10.0), ["Claude five-hour-session at 90%"]);
+    }
+
+    #[test]
+    fn uses_the_most_specific_rule() {
+        let settings = AlertSettings {
+            rules: vec![
+                ThresholdRule { provider: Provider::Codex, model: None, thresholds: vec![50] },
+                ThresholdRule { provider: Provider::Codex, model: Some("Weekly limit".to_string()), thresholds: vec![95] },
+            ],
+            ..AlertSettings::default()
+        };
+        assert_eq!(settings.thresholds(Provider::Codex, "weekly limit"), [95]);
+        assert_eq!(settings.thresholds(Provider::Codex, "5-hour limit"), [50]);
+        assert_eq!(settings.thresholds(Provider::Claude, "five-hour-session"), [75, 90, 100]);
+    }
+
+    #[test]
+    fn announces_expired_token_once() {
+        let settings = AlertSettings::default();
+        let mut announced = Announced::default();
+        let mut expired = snapshot(Provider::Claude, &[]);
+        expired.error = Some(QuotaError::new(QuotaErrorKind::TokenExpired, "Token expired, please re-authenticate"));
+
+        assert_eq!(titles(evaluate(&settings, &mut announced, &expired)), ["Claude token expired"]);
+        assert!(evaluate(&settings, &mut announced, &expired).is_empty());
+        evaluate(&settings, &mut announced, &snapshot(Provider::Claude, &[("five-hour-session", 80.0)]));
+        assert_eq!(evaluate(&settings, &mut announced, &expired).len(), 1);
+    }
+
+    #[test]
+    fn quiet_hours_wrap_past_midnight() {
+        let time = |h| NaiveTime::from_hms_opt(h, 0, 0).unwrap();
+        let night = QuietHours { start: time(22), end: time(7) };
+        assert!(night.contains(time(23)) && night.contains(time(3)));
+        assert!(!night.contains(time(12)) && !night.contains(time(7)));
+        let lunch = QuietHours { start: time(12), end: time(13) };
+        assert!(lunch.contains(time(12)) && !lunch.contains(time(13)));
+    }
+}
@@ -0,0 +1,147 @@
+//! Antigravity per-model quotas from `fetchAvailableModels`
+
+use std::collections::BTreeSet;
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use super::management::ApiCall;
+use super::{field, number, parse_time, str_field, Quota, QuotaProvider, QuotaWindow};
+
+/// Tried in order; the daily endpoints answer first for most accounts
+const QUOTA_URLS: &[&str] = &[
+    "https://daily-cloudcode-pa.googleapis.com/v1internal:fetchAvailableModels",
+    "https://daily-cloudcode-pa.sandbox.googleapis.com/v1internal:fetchAvailableModels",
+    "https://cloudcode-pa.googleapis.com/v1internal:fetchAvailableModels",
+];
+const HEADERS: &[(&str, &str)] = &[
+    ("Authorization", "Bearer $TOKEN$"),
+    ("Content-Type", "application/json"),
+    ("User-Agent", "antigravity/1.11.5 windows/amd64"),
+];
+
+/// Model id lists outside `models` whose entries are shown at full quota
+const EXTRA_ID_LISTS: &[&str] = &[
+    "commandModelIds",
+    "tabModelIds",
+    "imageGenerationModelIds",
+    "mqueryModelIds",
+    "webSearchModelIds",
+];
+
+pub struct Antigravity;
+
+impl QuotaProvider for Antigravity {
+    fn requests(&self, _account: Option<&Value>) -> Result<Vec<ApiCall>, String> {
+        Ok(QUOTA_URLS.iter().map(|url| ApiCall::post(*url, HEADERS, "{}")).collect())
+    }
+
+    fn parse(&self, body: &Value, _now: DateTime<Utc>) -> Result<Quota, String> {
+        let Some(models) = body.get("models").and_then(Value::as_object) else {
+            return Err("No models in response".to_string());
+        };
+
+        let mut windows = Vec::new();
+        for (key, model) in models {
+            if !model.is_object() || model["isInternal"] == true || is_hidden(key) {
+                continue;
+            }
+            let name = str_field(model, &["displayName", "display_name"])
+                .map(str::to_string)
+                .unwrap_or_else(|| display_name(key));
+
+            let quota_info = field(model, &["quotaInfo", "quota_info"]);
+            let source = quota_info.unwrap_or(model);
+            let remaining = field(source, &["remainingFraction", "remaining_fraction", "remaining"]).and_then(number);
+            let reset = field(source, &["resetTime", "reset_time"]);
+            // A quota block with a reset time but no fraction is used up
+            let remaining = remaining.unwrap_or_else(|| {
+                let has_reset = quota_info.and_then(|info| field(info, &["resetTime", "reset_time"])).is_some();
+                if has_reset { 0.0 } else { 1.0 }
+            });
+            let resets_at = reset.and_then(Value::as_str).and_then(parse_time);
+            windows.push(QuotaWindow::new(name, remaining * 100.0, resets_at));
+        }
+
+        let mut extra_ids = BTreeSet::new();
+        let groups = body["agentModelSorts"].as_array().into_iter().flatten().filter_map(|sort| sort["groups"].as_array());
+        for group in groups.flatten() {
+            extra_ids.extend(string_ids(&group["modelIds"]));
+        }
+        for list in EXTRA_ID_LISTS {
+            extra_ids.extend(string_ids(&body[list]));
+        }
+        if let Some(id) = body["defaultAgentModelId"].as_str() {
+            extra_ids.insert(id);
+        }
+        for id in extra_ids {
+            if models.contains_key(id) || is_hidden(id) {
+                continue;
+            }
+            windows.push(QuotaWindow::new(display_name(id), 100.0, None));
+        }
+
+        if windows.is_empty() {
+            return Err("No models in response".to_string());
+        }
+        windows.sort_by_key(|window| window.name.to_lowercase());
+        Ok(Quota { windows, ..Quota::default() })
+    }
+}
+
+fn string_ids(value: &Value) -> impl Iterator<Item = &str> {
+    value.as_array().into_iter().flatten().filter_map(Value::as_str)
+}
+
+/// Chat-only and autocomplete models that have no quota of their own
+fn is_hidden(id: &str) -> bool {
+    id.starts_with("chat_") || id == "tab_flash_lite_preview" || id == "tab_jump_flash_lite_preview"
+}
+
+fn display_name(id: &str) -> String {
+    match id {
+        "rev19-uic3-1p" => "Gemini 2.5 Computer Use",
+        "gemini-3-pro-image" => "Gemini 3 Pro Image",
+        "gemini-2.5-flash-lite" => "Gemini 2.5 Flash Lite",
+        "gemini-2.5-flash" => "Gemini 2.5 Flash",
+        _ => id,
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{fixture, test_now};
+    use super::*;
+
+    #[test]
+    fn parses_models() {
+        let quota = Antigravity.parse(&fixture("antigravity_models.json"), test_now()).unwrap();
+        let summary: Vec<_> = quota.windows.iter().map(|w| (w.name.as_str(), w.remaining_percent)).collect();
+        assert_eq!(
+            summary,
+            [
+                ("Claude Sonnet 4.5", 0.0),
+                ("Gemini 2.5 Computer Use", 100.0),
+                ("Gemini 2.5 Flash", 100.0),
+                ("Gemini 3 Pro (High)", 42.0),
+                ("gpt-oss-120b-medium", 75.0),
+            ]
+        );
+        let gemini = &quota.windows[3];
+        assert_eq!(gemini.resets_at, parse_time("2025-06-01T17:30:00Z"));
+    }
+
+    #[test]
+    fn empty_model_list_fails_over() {
+        assert!(Antigravity.parse(&serde_json::json!({"models": {}}), test_now()).is_err());
+        assert!(Antigravity.parse(&Value::String("<html>".into()), test_now()).is_err());
+    }
+
+    #[test]
+    fn tries_every_endpoint() {
+        let requests = Antigravity.requests(None).unwrap();
+        assert_eq!(requests.len(), 3);
+        assert!(requests.iter().all(|call| call.method == "POST" && call.data.as_deref() == Some("{}")));
+    }
+}
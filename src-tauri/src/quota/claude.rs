@@ -0,0 +1,112 @@
+//! Claude (Anthropic OAuth) usage windows
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use super::management::{ApiCall, ApiResponse};
+use super::{number, number_field, parse_time, Quota, QuotaError, QuotaErrorKind, QuotaProvider, QuotaWindow};
+
+const USAGE_URL: &str = "https://api.anthropic.com/api/oauth/usage";
+const HEADERS: &[(&str, &str)] = &[
+    ("Authorization", "Bearer $TOKEN$"),
+    ("anthropic-beta", "oauth-2025-04-20"),
+    ("Accept", "application/json"),
+];
+
+/// Usage fields and the window names shown for them
+const WINDOWS: &[(&str, &str)] = &[
+    ("five_hour", "five-hour-session"),
+    ("seven_day", "seven-day-weekly"),
+    ("seven_day_sonnet", "seven-day-sonnet"),
+    ("seven_day_opus", "seven-day-opus"),
+];
+
+pub struct Claude;
+
+impl QuotaProvider for Claude {
+    fn requests(&self, _account: Option<&Value>) -> Result<Vec<ApiCall>, String> {
+        Ok(vec![ApiCall::get(USAGE_URL, HEADERS)])
+    }
+
+    fn parse(&self, body: &Value, _now: DateTime<Utc>) -> Result<Quota, String> {
+        if !body.is_object() {
+            return Err("Invalid response format".to_string());
+        }
+        if body["type"] == "error" && !body["error"].is_null() {
+            let message = body["error"]["message"].as_str().unwrap_or("API Error");
+            return Err(message.to_string());
+        }
+
+        let mut windows = Vec::new();
+        for (key, name) in WINDOWS {
+            let usage = &body[key];
+            if usage.is_null() {
+                continue;
+            }
+            if let Some(utilization) = usage.get("utilization").and_then(number) {
+                let resets_at = usage["resets_at"].as_str().and_then(parse_time);
+                windows.push(QuotaWindow::new(*name, 100.0 - utilization, resets_at));
+            }
+        }
+
+        let extra = &body["extra_usage"];
+        if extra["is_enabled"].as_bool() == Some(true) {
+            if let Some(utilization) = number_field(extra, &["utilization"]) {
+                let mut window = QuotaWindow::new("extra-usage", 100.0 - utilization, None);
+                if let (Some(used), Some(limit)) = (extra.get("used_credits"), extra.get("monthly_limit")) {
+                    window.detail = Some(format!("{} / {}", used, limit));
+                }
+                windows.push(window);
+            }
+        }
+
+        if windows.is_empty() {
+            return Err("No quota data found".to_string());
+        }
+        Ok(Quota { windows, ..Quota::default() })
+    }
+
+    fn on_error(&self, response: &ApiResponse) -> Result<Quota, QuotaError> {
+        match response.status_code {
+            401 => Err(QuotaError::new(QuotaErrorKind::TokenExpired, "Token expired, please re-authenticate")),
+            _ => Err(QuotaError::from_response(response)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{fixture, test_now};
+    use super::*;
+
+    #[test]
+    fn parses_usage_windows() {
+        let quota = Claude.parse(&fixture("claude_usage.json"), test_now()).unwrap();
+        let names: Vec<_> = quota.windows.iter().map(|w| w.name.as_str()).collect();
+        assert_eq!(names, ["five-hour-session", "seven-day-weekly", "seven-day-opus", "extra-usage"]);
+
+        let session = &quota.windows[0];
+        assert_eq!(session.remaining_percent, 63.0);
+        assert_eq!(session.resets_at, parse_time("2025-06-01T15:00:00Z"));
+        // String utilization and values over 100 are tolerated
+        assert_eq!(quota.windows[1].remaining_percent, 87.5);
+        assert_eq!(quota.windows[2].remaining_percent, 0.0);
+
+        let extra = &quota.windows[3];
+        assert_eq!(extra.remaining_percent, 90.0);
+        assert_eq!(extra.detail.as_deref(), Some("5 / 50"));
+    }
+
+    #[test]
+    fn reports_api_errors() {
+        let error = Claude.parse(&fixture("claude_error.json"), test_now()).unwrap_err();
+        assert_eq!(error, "OAuth token has expired");
+        assert!(Claude.parse(&serde_json::json!({}), test_now()).is_err());
+    }
+
+    #[test]
+    fn unauthorized_means_expired_token() {
+        let response = ApiResponse { status_code: 401, body: Value::Null, body_text: String::new() };
+        assert_eq!(Claude.on_error(&response).unwrap_err().kind, QuotaErrorKind::TokenExpired);
+    }
+}
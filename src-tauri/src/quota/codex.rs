@@ -0,0 +1,153 @@
+//! Codex (ChatGPT) rate limit windows
+
+use base64::Engine;
+use chrono::{DateTime, Duration, Utc};
+use serde_json::Value;
+
+use super::management::ApiCall;
+use super::{field, number_field, str_field, unix_time, Quota, QuotaProvider, QuotaWindow};
+
+const USAGE_URL: &str = "https://chatgpt.com/backend-api/wham/usage";
+const HEADERS: &[(&str, &str)] = &[
+    ("Authorization", "Bearer $TOKEN$"),
+    ("Content-Type", "application/json"),
+    ("User-Agent", "codex_cli_rs/0.76.0 (Debian 13.0.0; x86_64) WindowsTerminal"),
+];
+
+pub struct Codex;
+
+impl QuotaProvider for Codex {
+    fn needs_account(&self) -> bool {
+        true
+    }
+
+    fn requests(&self, account: Option<&Value>) -> Result<Vec<ApiCall>, String> {
+        let mut call = ApiCall::get(USAGE_URL, HEADERS);
+        if let Some(account_id) = account.and_then(chatgpt_account_id) {
+            call = call.header("Chatgpt-Account-Id", account_id);
+        }
+        Ok(vec![call])
+    }
+
+    fn parse(&self, body: &Value, now: DateTime<Utc>) -> Result<Quota, String> {
+        if !body.is_object() {
+            return Ok(Quota::default());
+        }
+        let plan = str_field(body, &["plan_type", "planType"]).unwrap_or("Plus");
+
+        let mut windows = Vec::new();
+        let mut add = |name: &str, window: Option<&Value>| {
+            if let Some(window) = window.filter(|w| w.is_object()) {
+                windows.push(parse_window(name, window, now));
+            }
+        };
+
+        match body.get("rate_limit").filter(|v| v.is_object()) {
+            Some(limits) => {
+                add("5-hour limit", field(limits, &["primary_window", "primaryWindow"]));
+                add("Weekly limit", field(limits, &["secondary_window", "secondaryWindow"]));
+            }
+            None => {
+                add("5-hour limit", field(body, &["5_hour_window", "fiveHourWindow"]));
+                add("Weekly limit", field(body, &["weekly_window", "weeklyWindow"]));
+            }
+        }
+        match body.get("code_review_rate_limit").filter(|v| v.is_object()) {
+            Some(limits) => add("Code review limit", field(limits, &["primary_window", "primaryWindow"])),
+            None => add("Code review limit", field(body, &["code_review_window", "codeReviewWindow"])),
+        }
+
+        Ok(Quota { plan: Some(plan.to_string()), windows, ..Quota::default() })
+    }
+}
+
+fn parse_window(name: &str, window: &Value, now: DateTime<Utc>) -> QuotaWindow {
+    let remaining = match number_field(window, &["used_percent", "usedPercent"]) {
+        Some(used) => 100.0 - used,
+        None => {
+            let remaining = number_field(window, &["remaining_count", "remainingCount"]).unwrap_or(0.0);
+            let total = number_field(window, &["total_count", "totalCount"]).unwrap_or(1.0);
+            (remaining / total.max(1.0) * 100.0).round()
+        }
+    };
+
+    let reset_at = number_field(window, &["reset_at", "resetAt"]).filter(|at| *at > 0.0);
+    let reset_after = number_field(window, &["reset_after_seconds", "resetAfterSeconds"]).filter(|after| *after > 0.0);
+    let resets_at = match (reset_at, reset_after) {
+        (Some(at), _) => unix_time(at),
+        (None, Some(after)) => Some(now + Duration::seconds(after as i64)),
+        (None, None) => None,
+    };
+
+    QuotaWindow::new(name, remaining, resets_at)
+}
+
+/// `chatgpt_account_id` from the auth file's id token
+fn chatgpt_account_id(file: &Value) -> Option<String> {
+    let candidates = [&file["id_token"], &file["metadata"]["id_token"], &file["attributes"]["id_token"]];
+    candidates.into_iter().find_map(|token| {
+        let payload = id_token_payload(token)?;
+        str_field(&payload, &["chatgpt_account_id", "chatgptAccountId"]).map(str::to_string)
+    })
+}
+
+/// Claims of an id token given as a JWT, a JSON string or an object
+fn id_token_payload(token: &Value) -> Option<Value> {
+    match token {
+        Value::Object(_) => Some(token.clone()),
+        Value::String(text) => {
+            let text = text.trim();
+            if let Ok(payload @ Value::Object(_)) = serde_json::from_str(text) {
+                return Some(payload);
+            }
+            let segment = text.split('.').nth(1)?;
+            let decoded = base64::engine::general_purpose::URL_SAFE_NO_PAD
+                .decode(segment.trim_end_matches('='))
+                .ok()?;
+            serde_json::from_slice::<Value>(&decoded).ok().filter(Value::is_object)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{fixture, parse_time, test_now};
+    use super::*;
+
+    #[test]
+    fn parses_rate_limit_windows() {
+        let quota = Codex.parse(&fixture("codex_usage.json"), test_now()).unwrap();
+        assert_eq!(quota.plan.as_deref(), Some("pro"));
+        let summary: Vec<_> = quota.windows.iter().map(|w| (w.name.as_str(), w.remaining_percent, w.resets_at)).collect();
+        assert_eq!(
+            summary,
+            [
+                ("5-hour limit", 77.0, parse_time("2025-06-01T13:00:00Z")),
+                ("Weekly limit", 96.0, unix_time(1_749_168_000.0)),
+                ("Code review limit", 100.0, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_legacy_windows() {
+        let quota = Codex.parse(&fixture("codex_usage_legacy.json"), test_now()).unwrap();
+        assert_eq!(quota.plan.as_deref(), Some("Plus"));
+        let summary: Vec<_> = quota.windows.iter().map(|w| (w.name.as_str(), w.remaining_percent)).collect();
+        assert_eq!(summary, [("5-hour limit", 25.0), ("Weekly limit", 60.0)]);
+    }
+
+    #[test]
+    fn sends_account_id_from_id_token() {
+        // {"chatgpt_account_id":"acct-123"}
+        let file = serde_json::json!({
+            "metadata": {"id_token": "eyJhbGciOiJub25lIn0.eyJjaGF0Z3B0X2FjY291bnRfaWQiOiJhY2N0LTEyMyJ9.sig"}
+        });
+        let call = &Codex.requests(Some(&file)).unwrap()[0];
+        assert!(call.headers.contains(&("Chatgpt-Account-Id", "acct-123".to_string())));
+
+        let call = &Codex.requests(None).unwrap()[0];
+        assert!(!call.headers.iter().any(|(name, _)| *name == "Chatgpt-Account-Id"));
+    }
+}
@@ -0,0 +1,142 @@
+//! GitHub Copilot entitlement and monthly quotas
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use super::management::{ApiCall, ApiResponse};
+use super::{number_field, parse_time, str_field, Quota, QuotaError, QuotaErrorKind, QuotaProvider, QuotaWindow};
+
+const ENTITLEMENT_URL: &str = "https://api.github.com/copilot_internal/user";
+const HEADERS: &[(&str, &str)] = &[
+    ("Authorization", "Bearer $TOKEN$"),
+    ("Accept", "application/vnd.github+json"),
+    ("X-GitHub-Api-Version", "2022-11-28"),
+];
+
+/// Quota snapshots and their default allowance when `entitlement` is missing
+const SNAPSHOTS: &[(&str, &str, f64)] = &[
+    ("Chat", "chat", 50.0),
+    ("Completions", "completions", 2000.0),
+    ("Premium", "premium_interactions", 50.0),
+];
+
+pub struct Copilot;
+
+impl QuotaProvider for Copilot {
+    fn requests(&self, _account: Option<&Value>) -> Result<Vec<ApiCall>, String> {
+        Ok(vec![ApiCall::get(ENTITLEMENT_URL, HEADERS)])
+    }
+
+    fn parse(&self, body: &Value, _now: DateTime<Utc>) -> Result<Quota, String> {
+        if !body.is_object() {
+            return Ok(Quota::default());
+        }
+        let resets_at = str_field(body, &["quota_reset_date_utc", "quota_reset_date", "limited_user_reset_date"])
+            .and_then(parse_time);
+
+        let mut windows = Vec::new();
+        if let Some(snapshots) = body.get("quota_snapshots").filter(|v| v.is_object()) {
+            for (name, key, default_total) in SNAPSHOTS {
+                let snapshot = &snapshots[key];
+                if !snapshot.is_object() || snapshot["unlimited"] == true {
+                    continue;
+                }
+                let remaining = match snapshot["percent_remaining"].as_f64() {
+                    Some(percent) => percent,
+                    None => {
+                        let remaining = number_field(snapshot, &["remaining"]).unwrap_or(0.0);
+                        let total = number_field(snapshot, &["entitlement"]).unwrap_or(*default_total);
+                        if total > 0.0 { remaining / total * 100.0 } else { 100.0 }
+                    }
+                };
+                windows.push(QuotaWindow::new(*name, remaining.clamp(0.0, 100.0).round(), resets_at));
+            }
+        }
+
+        // Free plans report counters instead of snapshots
+        let limited = &body["limited_user_quotas"];
+        let monthly = &body["monthly_quotas"];
+        if windows.is_empty() && limited.is_object() && monthly.is_object() {
+            for (name, key) in [("Chat", "chat"), ("Completions", "completions")] {
+                let remaining = number_field(limited, &[key]).unwrap_or(0.0);
+                let total = number_field(monthly, &[key]).unwrap_or(0.0);
+                if total > 0.0 {
+                    windows.push(QuotaWindow::new(name, (remaining / total * 100.0).clamp(0.0, 100.0).round(), resets_at));
+                }
+            }
+        }
+
+        if windows.is_empty() {
+            windows.push(QuotaWindow::new("Copilot", 100.0, None));
+        }
+        Ok(Quota { plan: Some(plan(body)), windows, ..Quota::default() })
+    }
+
+    fn on_error(&self, response: &ApiResponse) -> Result<Quota, QuotaError> {
+        let message = "Token invalid or no Copilot subscription";
+        match response.status_code {
+            401 => Err(QuotaError::new(QuotaErrorKind::TokenExpired, message)),
+            403 => Err(QuotaError::new(QuotaErrorKind::AccessDenied, message)),
+            _ => Err(QuotaError::from_response(response)),
+        }
+    }
+}
+
+/// Plan name from the SKU and `copilot_plan`
+fn plan(body: &Value) -> String {
+    let copilot_plan = str_field(body, &["copilot_plan"]).unwrap_or_default();
+    let sku = str_field(body, &["access_type_sku"]).unwrap_or_default().to_lowercase();
+    let plan = copilot_plan.to_lowercase();
+
+    if sku.contains("enterprise") || plan == "enterprise" {
+        "Enterprise".to_string()
+    } else if sku.contains("business") || plan == "business" {
+        "Business".to_string()
+    } else if sku.contains("educational")
+        || sku.contains("pro")
+        || plan.contains("pro")
+        || (plan == "individual" && !sku.contains("free_limited"))
+    {
+        "Pro".to_string()
+    } else if sku.contains("free_limited") || sku == "free" || plan.contains("free") {
+        "Free".to_string()
+    } else if !copilot_plan.is_empty() {
+        let mut chars = copilot_plan.chars();
+        chars.next().map(|first| first.to_uppercase().chain(chars).collect()).unwrap_or_default()
+    } else {
+        "Unknown".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{fixture, test_now};
+    use super::*;
+
+    #[test]
+    fn parses_quota_snapshots() {
+        let quota = Copilot.parse(&fixture("copilot_pro.json"), test_now()).unwrap();
+        assert_eq!(quota.plan.as_deref(), Some("Pro"));
+        let summary: Vec<_> = quota.windows.iter().map(|w| (w.name.as_str(), w.remaining_percent)).collect();
+        // Unlimited chat and completions are left out
+        assert_eq!(summary, [("Premium", 73.0)]);
+        assert_eq!(quota.windows[0].resets_at, parse_time("2025-07-01T00:00:00Z"));
+    }
+
+    #[test]
+    fn parses_free_plan_counters() {
+        let quota = Copilot.parse(&fixture("copilot_free.json"), test_now()).unwrap();
+        assert_eq!(quota.plan.as_deref(), Some("Free"));
+        let summary: Vec<_> = quota.windows.iter().map(|w| (w.name.as_str(), w.remaining_percent)).collect();
+        assert_eq!(summary, [("Chat", 80.0), ("Completions", 25.0)]);
+        assert_eq!(quota.windows[0].resets_at, parse_time("2025-06-15"));
+    }
+
+    #[test]
+    fn reads_plan_names() {
+        assert_eq!(plan(&serde_json::json!({"access_type_sku": "copilot_enterprise_seat"})), "Enterprise");
+        assert_eq!(plan(&serde_json::json!({"copilot_plan": "individual"})), "Pro");
+        assert_eq!(plan(&serde_json::json!({"copilot_plan": "custom"})), "Custom");
+        assert_eq!(plan(&serde_json::json!({})), "Unknown");
+    }
+}
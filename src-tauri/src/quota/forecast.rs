@@ -0,0 +1,173 @@
+//! When a quota runs out at the current burn rate
+//!
+//! The burn rate of a model is the slope of a least-squares line through its
+//! history samples of the last few hours, counted from its latest reset. The
+//! confidence combines how well the samples fit that line, how many there
+//! are and how much time they cover, so a guess from two samples a minute
+//! apart doesn't look as sure as one from a steady afternoon of use.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+use super::history::{Bucket, HistoryRange, QuotaHistory};
+use super::{Provider, QuotaSnapshot};
+
+/// How far back samples are used
+const LOOKBACK_HOURS: i64 = 6;
+/// Samples needed for a burn rate
+const MIN_SAMPLES: usize = 3;
+/// A rise of this many points between samples is a reset, not noise
+const RESET_JUMP: f64 = 5.0;
+/// Slower burn, in percent per hour, counts as idle
+const IDLE_RATE: f64 = 0.01;
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct QuotaForecast {
+    pub provider: Provider,
+    pub account: String,
+    pub model: String,
+    pub remaining_percent: f64,
+    /// Percent per hour, positive while the quota is being used
+    pub burn_rate: Option<f64>,
+    pub predicted_exhaustion_at: Option<DateTime<Utc>>,
+    pub resets_at: Option<DateTime<Utc>>,
+    /// Whether the quota resets before it runs out; `None` without a prediction
+    pub resets_first: Option<bool>,
+    /// 0 to 1
+    pub confidence: f64,
+}
+
+/// Burn rate read from samples
+#[derive(Debug, PartialEq)]
+struct Estimate {
+    burn_rate: f64,
+    confidence: f64,
+}
+
+/// Forecast every window of a snapshot from the account's recent history
+pub fn forecast(history: &QuotaHistory, snapshot: &QuotaSnapshot, now: DateTime<Utc>) -> Result<Vec<QuotaForecast>, String> {
+    let range = HistoryRange {
+        from: now - Duration::hours(LOOKBACK_HOURS),
+        to: Some(now),
+    };
+    let points = history.query(snapshot.provider, &snapshot.auth_index, None, &range, Bucket::Raw)?;
+
+    let forecasts = snapshot
+        .windows
+        .iter()
+        .map(|window| {
+            let samples: Vec<_> = points
+                .iter()
+                .filter(|point| point.model == window.name)
+                .map(|point| (point.at, point.remaining_percent))
+                .collect();
+            let estimate = estimate(&samples);
+            let burn_rate = estimate.as_ref().map(|e| e.burn_rate);
+
+            let predicted_exhaustion_at = burn_rate.filter(|rate| *rate > IDLE_RATE).map(|rate| {
+                let hours = window.remaining_percent / rate;
+                now + Duration::seconds((hours * 3600.0) as i64)
+            });
+            let resets_first =
+                predicted_exhaustion_at.map(|at| window.resets_at.is_some_and(|resets_at| resets_at <= at));
+
+            QuotaForecast {
+                provider: snapshot.provider,
+                account: snapshot.auth_index.clone(),
+                model: window.name.clone(),
+                remaining_percent: window.remaining_percent,
+                burn_rate,
+                predicted_exhaustion_at,
+                resets_at: window.resets_at,
+                resets_first,
+                confidence: estimate.map_or(0.0, |e| e.confidence),
+            }
+        })
+        .collect();
+    Ok(forecasts)
+}
+
+/// Fit a line through the samples since the latest reset, oldest first
+fn estimate(samples: &[(DateTime<Utc>, f64)]) -> Option<Estimate> {
+    let start = samples
+        .windows(2)
+        .rposition(|pair| pair[1].1 > pair[0].1 + RESET_JUMP)
+        .map_or(0, |i| i + 1);
+    let samples = &samples[start..];
+    if samples.len() < MIN_SAMPLES {
+        return None;
+    }
+
+    let origin = samples[0].0;
+    let points: Vec<(f64, f64)> = samples
+        .iter()
+        .map(|(at, pct)| ((*at - origin).num_seconds() as f64 / 3600.0, *pct))
+        .collect();
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let var_x: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    let var_y: f64 = points.iter().map(|p| (p.1 - mean_y).powi(2)).sum();
+    let cov: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    let span_hours = points[points.len() - 1].0;
+    if var_x == 0.0 || span_hours <= 0.0 {
+        return None;
+    }
+
+    let slope = cov / var_x;
+    // A flat series fits perfectly
+    let fit = if var_y == 0.0 { 1.0 } else { (cov * cov / (var_x * var_y)).clamp(0.0, 1.0) };
+    let amount = n / (n + 3.0);
+    let coverage = (span_hours / 2.0).min(1.0);
+
+    Some(Estimate {
+        burn_rate: (-slope).max(0.0),
+        confidence: (fit * amount * coverage * 100.0).round() / 100.0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_now;
+    use super::*;
+
+    fn series(values: &[f64], minutes_apart: i64) -> Vec<(DateTime<Utc>, f64)> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, pct)| (test_now() + Duration::minutes(minutes_apart * i as i64), *pct))
+            .collect()
+    }
+
+    #[test]
+    fn steady_use_gives_a_confident_rate() {
+        // 10 points per half hour for three hours
+        let estimate = estimate(&series(&[90.0, 85.0, 80.0, 75.0, 70.0, 65.0, 60.0], 30)).unwrap();
+        assert!((estimate.burn_rate - 10.0).abs() < 1e-9);
+        assert_eq!(estimate.confidence, 0.7);
+    }
+
+    #[test]
+    fn short_or_noisy_series_is_less_certain() {
+        let short = estimate(&series(&[90.0, 89.0, 88.0], 10)).unwrap();
+        let noisy = estimate(&series(&[90.0, 70.0, 85.0, 60.0, 80.0, 65.0, 70.0], 30)).unwrap();
+        assert!(short.confidence < 0.2, "{:?}", short);
+        assert!(noisy.confidence < 0.5, "{:?}", noisy);
+        assert!(estimate(&series(&[90.0, 80.0], 30)).is_none());
+    }
+
+    #[test]
+    fn starts_after_the_latest_reset() {
+        let estimate = estimate(&series(&[20.0, 10.0, 100.0, 95.0, 90.0, 85.0], 60)).unwrap();
+        assert!((estimate.burn_rate - 5.0).abs() < 1e-9);
+
+        // Too few samples since the reset
+        assert!(super::estimate(&series(&[20.0, 10.0, 5.0, 100.0, 95.0], 60)).is_none());
+    }
+
+    #[test]
+    fn idle_quota_has_no_burn() {
+        let estimate = estimate(&series(&[50.0, 50.0, 50.0, 50.0], 60)).unwrap();
+        assert_eq!(estimate.burn_rate, 0.0);
+    }
+}
@@ -0,0 +1,88 @@
+//! Gemini CLI per-model quota buckets
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use super::management::ApiCall;
+use super::{field, number, parse_time, Quota, QuotaProvider, QuotaWindow};
+
+const QUOTA_URL: &str = "https://cloudcode-pa.googleapis.com/v1internal:retrieveUserQuota";
+const HEADERS: &[(&str, &str)] = &[("Authorization", "Bearer $TOKEN$"), ("Content-Type", "application/json")];
+
+pub struct GeminiCli;
+
+impl QuotaProvider for GeminiCli {
+    fn needs_account(&self) -> bool {
+        true
+    }
+
+    fn requests(&self, account: Option<&Value>) -> Result<Vec<ApiCall>, String> {
+        let project_id = account
+            .and_then(project_id)
+            .ok_or_else(|| "Project ID not found in file".to_string())?;
+        let data = serde_json::json!({ "project": project_id }).to_string();
+        Ok(vec![ApiCall::post(QUOTA_URL, HEADERS, data)])
+    }
+
+    fn parse(&self, body: &Value, _now: DateTime<Utc>) -> Result<Quota, String> {
+        let buckets = body.get("buckets").and_then(Value::as_array).into_iter().flatten();
+        let windows = buckets
+            .map(|bucket| {
+                let model = match field(bucket, &["modelId", "model_id"]) {
+                    Some(Value::String(id)) => id.clone(),
+                    Some(other) => other.to_string(),
+                    None => "Unknown".to_string(),
+                };
+                let remaining = field(bucket, &["remainingFraction", "remaining_fraction"])
+                    .and_then(number)
+                    .unwrap_or(0.0);
+                let resets_at = bucket["resetTime"].as_str().and_then(parse_time);
+                QuotaWindow::new(model, remaining * 100.0, resets_at)
+            })
+            .collect();
+        Ok(Quota { windows, ..Quota::default() })
+    }
+}
+
+/// GCP project in the auth file's account, e.g. `me@example.com (my-project)`
+fn project_id(file: &Value) -> Option<String> {
+    let candidates = [&file["account"], &file["metadata"]["account"], &file["attributes"]["account"]];
+    candidates.into_iter().filter_map(Value::as_str).find_map(|account| {
+        let (_, rest) = account.rsplit_once('(')?;
+        let (id, _) = rest.split_once(')')?;
+        let id = id.trim();
+        (!id.is_empty()).then(|| id.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{fixture, test_now};
+    use super::*;
+
+    #[test]
+    fn parses_buckets() {
+        let quota = GeminiCli.parse(&fixture("gemini_cli_quota.json"), test_now()).unwrap();
+        let summary: Vec<_> = quota.windows.iter().map(|w| (w.name.as_str(), w.remaining_percent)).collect();
+        assert_eq!(summary, [("gemini-2.5-pro", 85.0), ("gemini-2.5-flash", 100.0), ("Unknown", 0.0)]);
+        assert_eq!(quota.windows[0].resets_at, parse_time("2025-06-02T00:00:00Z"));
+        assert_eq!(quota.windows[1].resets_at, None);
+    }
+
+    #[test]
+    fn missing_buckets_is_empty() {
+        let quota = GeminiCli.parse(&serde_json::json!({}), test_now()).unwrap();
+        assert!(quota.windows.is_empty());
+    }
+
+    #[test]
+    fn sends_project_from_account() {
+        let file = serde_json::json!({"account": "me@example.com (first) (my-project-123)"});
+        let call = &GeminiCli.requests(Some(&file)).unwrap()[0];
+        assert_eq!(call.data.as_deref(), Some(r#"{"project":"my-project-123"}"#));
+
+        let file = serde_json::json!({"account": "me@example.com"});
+        assert!(GeminiCli.requests(Some(&file)).is_err());
+        assert!(GeminiCli.requests(None).is_err());
+    }
+}
@@ -0,0 +1,398 @@
+//! Quota history in `~/.zerolimit/quota-history.db`
+//!
+//! Every successful snapshot adds one row per window (model) of the account.
+//! Old rows are compacted into averages per bucket, and rows past the
+//! retention are deleted. Queries group the rows into buckets again, so
+//! charts get one point per bucket however dense the samples are.
+//! Settings live in `~/.zerolimit/quota-history.json`.
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use super::{Provider, QuotaSnapshot};
+use crate::state::AppState;
+
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS samples (
+        provider TEXT NOT NULL,
+        account TEXT NOT NULL,
+        model TEXT NOT NULL,
+        at INTEGER NOT NULL,
+        remaining_percent REAL NOT NULL,
+        min_percent REAL NOT NULL,
+        resets_at INTEGER,
+        resolution INTEGER NOT NULL DEFAULT 0
+    );
+    CREATE INDEX IF NOT EXISTS samples_series ON samples (provider, account, model, at);
+";
+
+/// Width of the points a query returns
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Bucket {
+    /// Every stored row
+    Raw,
+    Hour,
+    Day,
+}
+
+impl Bucket {
+    fn seconds(self) -> i64 {
+        match self {
+            Bucket::Raw => 1,
+            Bucket::Hour => 60 * 60,
+            Bucket::Day => 24 * 60 * 60,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct HistorySettings {
+    pub enabled: bool,
+    /// Days to keep rows; 0 keeps them forever
+    pub retention_days: u32,
+    /// Rows older than this are merged into `compact_bucket` averages
+    pub compact_after_days: u32,
+    pub compact_bucket: Bucket,
+}
+
+impl Default for HistorySettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            retention_days: 180,
+            compact_after_days: 7,
+            compact_bucket: Bucket::Hour,
+        }
+    }
+}
+
+impl HistorySettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.compact_bucket == Bucket::Raw {
+            return Err("Compaction needs an hour or day bucket".to_string());
+        }
+        if self.retention_days != 0 && self.retention_days < self.compact_after_days {
+            return Err("Retention must be longer than the compaction delay".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Time span of a query; `to` defaults to now
+#[derive(Clone, Debug, Deserialize)]
+pub struct HistoryRange {
+    pub from: DateTime<Utc>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// One bucket of one model
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct HistoryPoint {
+    pub model: String,
+    /// Start of the bucket
+    pub at: DateTime<Utc>,
+    /// Average over the bucket
+    pub remaining_percent: f64,
+    /// Lowest value seen in the bucket
+    pub min_percent: f64,
+    pub resets_at: Option<DateTime<Utc>>,
+}
+
+/// `~/.zerolimit/<name>`
+fn data_path(name: &str) -> Option<PathBuf> {
+    let mut path = dirs::home_dir()?;
+    path.push(".zerolimit");
+    path.push(name);
+    Some(path)
+}
+
+/// Stored settings, or the defaults
+pub fn load() -> HistorySettings {
+    data_path("quota-history.json")
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save(settings: &HistorySettings) -> io::Result<()> {
+    let path = data_path("quota-history.json")
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Could not determine user home directory"))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let json = serde_json::to_string_pretty(settings)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    fs::write(path, json)
+}
+
+/// The history database, opened on first use
+pub struct QuotaHistory {
+    db: Mutex<Option<Connection>>,
+    settings: Mutex<HistorySettings>,
+}
+
+impl Default for QuotaHistory {
+    fn default() -> Self {
+        Self {
+            db: Mutex::default(),
+            settings: Mutex::new(load()),
+        }
+    }
+}
+
+impl QuotaHistory {
+    pub fn settings(&self) -> HistorySettings {
+        self.settings.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn apply(&self, settings: HistorySettings) {
+        *self.settings.lock().unwrap_or_else(|e| e.into_inner()) = settings;
+    }
+
+    fn with_db<T>(&self, f: impl FnOnce(&mut Connection) -> rusqlite::Result<T>) -> Result<T, String> {
+        let mut db = self.db.lock().unwrap_or_else(|e| e.into_inner());
+        let conn = match &mut *db {
+            Some(conn) => conn,
+            slot @ None => slot.insert(open()?),
+        };
+        f(conn).map_err(|e| format!("Quota history: {}", e))
+    }
+
+    /// Store a snapshot; failed ones carry no quota and are skipped
+    pub fn record(&self, snapshot: &QuotaSnapshot) {
+        if !self.settings().enabled || snapshot.error.is_some() || snapshot.windows.is_empty() {
+            return;
+        }
+        if let Err(e) = self.with_db(|conn| insert(conn, snapshot)) {
+            println!("Notice: Failed to record quota history: {}", e);
+        }
+    }
+
+    /// Rows of one account; auth file names are only unique per provider
+    pub fn query(
+        &self,
+        provider: Provider,
+        account: &str,
+        model: Option<&str>,
+        range: &HistoryRange,
+        bucket: Bucket,
+    ) -> Result<Vec<HistoryPoint>, String> {
+        self.with_db(|conn| query(conn, provider, account, model, range, bucket))
+    }
+
+    /// Apply retention and compaction
+    pub fn maintain(&self) -> Result<(), String> {
+        let settings = self.settings();
+        self.with_db(|conn| maintain(conn, &settings, Utc::now()))
+    }
+}
+
+fn open() -> Result<Connection, String> {
+    let path = data_path("quota-history.db").ok_or("Could not determine user home directory")?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let conn = Connection::open(&path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    conn.execute_batch(SCHEMA)
+        .map_err(|e| format!("Failed to prepare {}: {}", path.display(), e))?;
+    Ok(conn)
+}
+
+fn insert(conn: &mut Connection, snapshot: &QuotaSnapshot) -> rusqlite::Result<()> {
+    let tx = conn.transaction()?;
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO samples (provider, account, model, at, remaining_percent, min_percent, resets_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?5, ?6)",
+        )?;
+        for window in &snapshot.windows {
+            stmt.execute(params![
+                snapshot.provider.key(),
+                snapshot.auth_index,
+                window.name,
+                snapshot.fetched_at.timestamp(),
+                window.remaining_percent,
+                window.resets_at.map(|at| at.timestamp()),
+            ])?;
+        }
+    }
+    tx.commit()
+}
+
+fn query(
+    conn: &mut Connection,
+    provider: Provider,
+    account: &str,
+    model: Option<&str>,
+    range: &HistoryRange,
+    bucket: Bucket,
+) -> rusqlite::Result<Vec<HistoryPoint>> {
+    let to = range.to.unwrap_or_else(Utc::now);
+    let mut stmt = conn.prepare(
+        "SELECT model, (at / ?1) * ?1 AS bucket, AVG(remaining_percent), MIN(min_percent), MAX(resets_at)
+         FROM samples
+         WHERE provider = ?2 AND account = ?3 AND (?4 IS NULL OR model = ?4) AND at >= ?5 AND at <= ?6
+         GROUP BY model, bucket
+         ORDER BY model, bucket",
+    )?;
+    let rows = stmt.query_map(
+        params![bucket.seconds(), provider.key(), account, model, range.from.timestamp(), to.timestamp()],
+        |row| {
+            Ok(HistoryPoint {
+                model: row.get(0)?,
+                at: timestamp(row.get(1)?).unwrap_or_default(),
+                remaining_percent: row.get(2)?,
+                min_percent: row.get(3)?,
+                resets_at: row.get::<_, Option<i64>>(4)?.and_then(timestamp),
+            })
+        },
+    )?;
+    rows.collect()
+}
+
+fn maintain(conn: &mut Connection, settings: &HistorySettings, now: DateTime<Utc>) -> rusqlite::Result<()> {
+    let tx = conn.transaction()?;
+    if settings.retention_days > 0 {
+        let cutoff = now.timestamp() - i64::from(settings.retention_days) * 24 * 60 * 60;
+        tx.execute("DELETE FROM samples WHERE at < ?1", params![cutoff])?;
+    }
+
+    // Only whole buckets, so a bucket is never split between raw and compacted rows
+    let size = settings.compact_bucket.seconds();
+    let cutoff = (now.timestamp() - i64::from(settings.compact_after_days) * 24 * 60 * 60) / size * size;
+    tx.execute(
+        "INSERT INTO samples (provider, account, model, at, remaining_percent, min_percent, resets_at, resolution)
+         SELECT provider, account, model, (at / ?1) * ?1, AVG(remaining_percent), MIN(min_percent), MAX(resets_at), ?1
+         FROM samples WHERE resolution < ?1 AND at < ?2
+         GROUP BY provider, account, model, at / ?1",
+        params![size, cutoff],
+    )?;
+    tx.execute("DELETE FROM samples WHERE resolution < ?1 AND at < ?2", params![size, cutoff])?;
+    tx.commit()
+}
+
+fn timestamp(secs: i64) -> Option<DateTime<Utc>> {
+    Utc.timestamp_opt(secs, 0).single()
+}
+
+/// Apply retention and compaction now and every few hours
+pub fn spawn_maintenance(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let app = app.clone();
+            let result = tauri::async_runtime::spawn_blocking(move || app.state::<AppState>().history().maintain()).await;
+            match result {
+                Ok(Err(e)) => println!("Notice: Quota history maintenance failed: {}", e),
+                Ok(Ok(())) => {}
+                Err(_) => return,
+            }
+            tokio::time::sleep(MAINTENANCE_INTERVAL).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{test_now, QuotaWindow};
+    use super::*;
+
+    fn database() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(SCHEMA).unwrap();
+        conn
+    }
+
+    fn snapshot(at: DateTime<Utc>, windows: &[(&str, f64)]) -> QuotaSnapshot {
+        QuotaSnapshot {
+            provider: Provider::Codex,
+            auth_index: "codex-me.json".to_string(),
+            fetched_at: at,
+            plan: None,
+            email: None,
+            windows: windows.iter().map(|(name, pct)| QuotaWindow::new(*name, *pct, None)).collect(),
+            error: None,
+        }
+    }
+
+    fn minutes(n: i64) -> chrono::Duration {
+        chrono::Duration::minutes(n)
+    }
+
+    #[test]
+    fn buckets_samples_per_model() {
+        let mut conn = database();
+        let start = test_now();
+        for (i, pct) in [90.0, 80.0, 70.0].iter().enumerate() {
+            let at = start + minutes(20 * i as i64);
+            insert(&mut conn, &snapshot(at, &[("5-hour limit", *pct), ("Weekly limit", 50.0)])).unwrap();
+        }
+        let range = HistoryRange { from: start, to: Some(start + minutes(60)) };
+
+        let points = query(&mut conn, Provider::Codex, "codex-me.json", Some("5-hour limit"), &range, Bucket::Hour).unwrap();
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0].at, start);
+        assert_eq!(points[0].remaining_percent, 80.0);
+        assert_eq!(points[0].min_percent, 70.0);
+
+        let points = query(&mut conn, Provider::Codex, "codex-me.json", None, &range, Bucket::Raw).unwrap();
+        assert_eq!(points.len(), 6);
+        assert!(query(&mut conn, Provider::Codex, "other.json", None, &range, Bucket::Raw).unwrap().is_empty());
+    }
+
+    #[test]
+    fn keeps_providers_apart() {
+        let mut conn = database();
+        let start = test_now();
+        insert(&mut conn, &snapshot(start, &[("Weekly limit", 40.0)])).unwrap();
+        let mut other = snapshot(start, &[("Weekly limit", 90.0)]);
+        other.provider = Provider::Kiro;
+        insert(&mut conn, &other).unwrap();
+        let range = HistoryRange { from: start, to: Some(start) };
+
+        let points = query(&mut conn, Provider::Kiro, "codex-me.json", None, &range, Bucket::Raw).unwrap();
+        assert_eq!(points.iter().map(|p| p.remaining_percent).collect::<Vec<_>>(), [90.0]);
+    }
+
+    #[test]
+    fn compacts_and_expires_old_rows() {
+        let mut conn = database();
+        let now = test_now();
+        let old = now - chrono::Duration::days(10);
+        let expired = now - chrono::Duration::days(200);
+        for at in [old, old + minutes(10), old + minutes(20), expired, now] {
+            insert(&mut conn, &snapshot(at, &[("Weekly limit", 60.0)])).unwrap();
+        }
+
+        maintain(&mut conn, &HistorySettings::default(), now).unwrap();
+
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM samples", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 2);
+        let range = HistoryRange { from: expired, to: Some(now) };
+        let points = query(&mut conn, Provider::Codex, "codex-me.json", None, &range, Bucket::Raw).unwrap();
+        assert_eq!(points.iter().map(|p| p.at).collect::<Vec<_>>(), [old, now]);
+
+        // Running again leaves compacted rows alone
+        maintain(&mut conn, &HistorySettings::default(), now).unwrap();
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM samples", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn rejects_raw_compaction() {
+        assert!(HistorySettings::default().validate().is_ok());
+        let settings = HistorySettings { compact_bucket: Bucket::Raw, ..HistorySettings::default() };
+        assert!(settings.validate().is_err());
+    }
+}
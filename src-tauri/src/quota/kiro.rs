@@ -0,0 +1,125 @@
+//! Kiro (CodeWhisperer) usage limits, including free trial bonus credits
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use super::management::{ApiCall, ApiResponse};
+use super::{number_field, str_field, unix_time, Quota, QuotaError, QuotaProvider, QuotaWindow};
+
+const USAGE_URL: &str = "https://codewhisperer.us-east-1.amazonaws.com/getUsageLimits?isEmailRequired=true&origin=AI_EDITOR&resourceType=AGENTIC_REQUEST";
+const HEADERS: &[(&str, &str)] = &[
+    ("Authorization", "Bearer $TOKEN$"),
+    ("Content-Type", "application/json"),
+    ("User-Agent", "aws-sdk-js/3.0.0 KiroIDE-0.1.0 os/windows lang/js md/nodejs/18.0.0"),
+    ("x-amz-user-agent", "aws-sdk-js/3.0.0"),
+];
+
+pub struct Kiro;
+
+impl QuotaProvider for Kiro {
+    fn requests(&self, _account: Option<&Value>) -> Result<Vec<ApiCall>, String> {
+        Ok(vec![ApiCall::get(USAGE_URL, HEADERS)])
+    }
+
+    fn parse(&self, body: &Value, _now: DateTime<Utc>) -> Result<Quota, String> {
+        let mut windows = Vec::new();
+        let plan = str_field(&body["subscriptionInfo"], &["subscriptionTitle"]).unwrap_or("Standard");
+
+        let breakdowns = body.get("usageBreakdownList").and_then(Value::as_array).into_iter().flatten();
+        for breakdown in breakdowns {
+            let name = str_field(breakdown, &["displayName", "resourceType"]).unwrap_or("Usage");
+            let plural = str_field(breakdown, &["displayNamePlural"])
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("{}s", name));
+            let resets_at = number_field(breakdown, &["nextDateReset"])
+                .or_else(|| number_field(body, &["nextDateReset"]))
+                .and_then(unix_time);
+
+            let trial = &breakdown["freeTrialInfo"];
+            let has_trial = trial["freeTrialStatus"] == "ACTIVE";
+            if has_trial {
+                let expires_at = number_field(trial, &["freeTrialExpiry"]).and_then(unix_time);
+                windows.push(QuotaWindow::new(format!("Bonus {}", plural), remaining_percent(trial), expires_at));
+            }
+
+            let total = number_field(breakdown, &["usageLimitWithPrecision", "usageLimit"]).unwrap_or(0.0).round();
+            if total > 0.0 {
+                let name = if has_trial { format!("Base {}", plural) } else { plural };
+                windows.push(QuotaWindow::new(name, remaining_percent(breakdown), resets_at));
+            }
+        }
+
+        if windows.is_empty() {
+            windows.push(QuotaWindow::new("kiro-standard", 100.0, None));
+        }
+        let email = str_field(&body["userInfo"], &["email"]).map(str::to_string);
+        Ok(Quota { plan: Some(plan.to_string()), email, windows })
+    }
+
+    /// A suspended account answers 403 with the reason
+    fn on_error(&self, response: &ApiResponse) -> Result<Quota, QuotaError> {
+        if response.status_code != 403 {
+            return Err(QuotaError::from_response(response));
+        }
+        let reason = response.body["reason"].as_str().unwrap_or_default().replace('_', " ").to_lowercase();
+        let mut chars = reason.chars();
+        let reason = match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => "Suspended".to_string(),
+        };
+        let mut window = QuotaWindow::new("Kiro", 100.0, None);
+        window.detail = Some(reason);
+        Ok(Quota { plan: Some("Suspended".to_string()), windows: vec![window], ..Quota::default() })
+    }
+}
+
+/// Remaining share of a usage block, in whole percent like the Kiro IDE shows it
+fn remaining_percent(usage: &Value) -> f64 {
+    let used = number_field(usage, &["currentUsageWithPrecision", "currentUsage"]).unwrap_or(0.0).round();
+    let total = number_field(usage, &["usageLimitWithPrecision", "usageLimit"]).unwrap_or(0.0).round();
+    if total > 0.0 {
+        ((total - used) / total * 100.0).round()
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{fixture, test_now};
+    use super::*;
+
+    #[test]
+    fn parses_trial_and_base_credits() {
+        let quota = Kiro.parse(&fixture("kiro_usage.json"), test_now()).unwrap();
+        assert_eq!(quota.plan.as_deref(), Some("KIRO FREE"));
+        assert_eq!(quota.email.as_deref(), Some("me@example.com"));
+        let summary: Vec<_> = quota.windows.iter().map(|w| (w.name.as_str(), w.remaining_percent, w.resets_at)).collect();
+        assert_eq!(
+            summary,
+            [
+                ("Bonus Credits", 60.0, unix_time(1_750_000_000.0)),
+                ("Base Credits", 88.0, unix_time(1_751_328_000.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn falls_back_without_usage() {
+        let quota = Kiro.parse(&serde_json::json!({}), test_now()).unwrap();
+        assert_eq!(quota.plan.as_deref(), Some("Standard"));
+        assert_eq!(quota.windows[0].name, "kiro-standard");
+    }
+
+    #[test]
+    fn reports_suspension() {
+        let response = ApiResponse {
+            status_code: 403,
+            body: fixture("kiro_suspended.json"),
+            body_text: String::new(),
+        };
+        let quota = Kiro.on_error(&response).unwrap();
+        assert_eq!(quota.plan.as_deref(), Some("Suspended"));
+        assert_eq!(quota.windows[0].detail.as_deref(), Some("Temporarily suspended"));
+    }
+}
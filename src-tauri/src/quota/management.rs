@@ -0,0 +1,230 @@
+//! Calls to the proxy's management API on behalf of an account
+//!
+//! `/v0/management/api-call` performs an upstream request with the token of
+//! the auth file behind an auth index; `$TOKEN$` in a header is replaced by
+//! that token.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Where the management API is and the key to use it, as the frontend logged in
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManagementConnection {
+    pub api_base: String,
+    pub management_key: String,
+}
+
+/// One upstream request
+#[derive(Clone, Debug, PartialEq)]
+pub struct ApiCall {
+    pub method: &'static str,
+    pub url: String,
+    pub headers: Vec<(&'static str, String)>,
+    pub data: Option<String>,
+}
+
+impl ApiCall {
+    pub fn get(url: impl Into<String>, headers: &[(&'static str, &str)]) -> Self {
+        Self {
+            method: "GET",
+            url: url.into(),
+            headers: headers.iter().map(|(name, value)| (*name, value.to_string())).collect(),
+            data: None,
+        }
+    }
+
+    pub fn post(url: impl Into<String>, headers: &[(&'static str, &str)], data: impl Into<String>) -> Self {
+        Self { method: "POST", data: Some(data.into()), ..Self::get(url, headers) }
+    }
+
+    pub fn header(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        self.headers.push((name, value.into()));
+        self
+    }
+}
+
+/// The upstream answer as relayed by the proxy
+#[derive(Clone, Debug)]
+pub struct ApiResponse {
+    pub status_code: u16,
+    /// JSON body, or the raw text as a string when it isn't JSON
+    pub body: Value,
+    pub body_text: String,
+}
+
+#[derive(Deserialize)]
+struct RawApiResponse {
+    #[serde(alias = "statusCode")]
+    status_code: Option<u16>,
+    body: Option<Value>,
+}
+
+impl ApiResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status_code)
+    }
+
+    /// The upstream's error message, prefixed with the status
+    pub fn error_message(&self) -> String {
+        let message = match &self.body {
+            Value::Object(_) => {
+                let error = self.body.get("error");
+                error
+                    .and_then(|e| e.get("message"))
+                    .or(error)
+                    .or_else(|| self.body.get("message"))
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string()
+            }
+            Value::String(text) => text.clone(),
+            _ => String::new(),
+        };
+        let message = if message.is_empty() { self.body_text.trim().to_string() } else { message };
+        match (self.status_code, message.is_empty()) {
+            (0, true) => "Request failed".to_string(),
+            (0, false) => message,
+            (status, true) => format!("HTTP {}", status),
+            (status, false) => format!("{} {}", status, message),
+        }
+    }
+
+    fn from_raw(raw: RawApiResponse) -> Self {
+        // The proxy passes the upstream body through as a string
+        let (body, body_text) = match raw.body {
+            None | Some(Value::Null) => (Value::Null, String::new()),
+            Some(Value::String(text)) => {
+                let body = match text.trim() {
+                    "" => Value::Null,
+                    trimmed => serde_json::from_str(trimmed).unwrap_or_else(|_| Value::String(text.clone())),
+                };
+                (body, text)
+            }
+            Some(body) => {
+                let text = body.to_string();
+                (body, text)
+            }
+        };
+        Self {
+            status_code: raw.status_code.unwrap_or(0),
+            body,
+            body_text,
+        }
+    }
+}
+
+/// Management API client for one connection
+pub struct ManagementClient {
+    http: reqwest::Client,
+    base_url: String,
+    management_key: String,
+}
+
+impl ManagementClient {
+    pub fn new(http: reqwest::Client, connection: &ManagementConnection) -> Self {
+        let base = connection.api_base.trim().trim_end_matches('/');
+        let base = base.strip_suffix("/v0/management").unwrap_or(base);
+        let base_url = if base.starts_with("http://") || base.starts_with("https://") {
+            format!("{}/v0/management", base)
+        } else {
+            format!("http://{}/v0/management", base)
+        };
+        Self { http, base_url, management_key: connection.management_key.clone() }
+    }
+
+    /// Perform `call` with the token of the account behind `auth_index`
+    pub async fn api_call(&self, auth_index: &str, call: &ApiCall) -> Result<ApiResponse, String> {
+        let header: HashMap<&str, &str> = call.headers.iter().map(|(name, value)| (*name, value.as_str())).collect();
+        let mut payload = serde_json::json!({
+            "authIndex": auth_index,
+            "method": call.method,
+            "url": call.url,
+            "header": header,
+        });
+        if let Some(data) = &call.data {
+            payload["data"] = Value::String(data.clone());
+        }
+
+        let raw: RawApiResponse = self
+            .http
+            .post(format!("{}/api-call", self.base_url))
+            .bearer_auth(&self.management_key)
+            .json(&payload)
+            .timeout(REQUEST_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| format!("Request failed: {}", e))?
+            .error_for_status()
+            .map_err(|e| format!("Management API error: {}", e))?
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse response: {}", e))?;
+        Ok(ApiResponse::from_raw(raw))
+    }
+
+    /// Auth files known to the proxy, as returned by `/auth-files`
+    pub async fn auth_files(&self) -> Result<Vec<Value>, String> {
+        let body: Value = self
+            .http
+            .get(format!("{}/auth-files", self.base_url))
+            .bearer_auth(&self.management_key)
+            .timeout(REQUEST_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| format!("Request failed: {}", e))?
+            .error_for_status()
+            .map_err(|e| format!("Management API error: {}", e))?
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+        let files = match body {
+            Value::Array(files) => files,
+            Value::Object(mut map) => match map.remove("files").or_else(|| map.remove("items")) {
+                Some(Value::Array(files)) => files,
+                _ => Vec::new(),
+            },
+            _ => Vec::new(),
+        };
+        Ok(files)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_string_bodies() {
+        let raw: RawApiResponse = serde_json::from_str(r#"{"status_code": 200, "body": "{\"a\": 1}"}"#).unwrap();
+        let response = ApiResponse::from_raw(raw);
+        assert!(response.is_success());
+        assert_eq!(response.body["a"], 1);
+
+        let raw: RawApiResponse = serde_json::from_str(r#"{"statusCode": 502, "body": "Bad gateway"}"#).unwrap();
+        let response = ApiResponse::from_raw(raw);
+        assert_eq!(response.body, Value::String("Bad gateway".into()));
+        assert_eq!(response.error_message(), "502 Bad gateway");
+    }
+
+    #[test]
+    fn reads_nested_error_messages() {
+        let raw: RawApiResponse =
+            serde_json::from_str(r#"{"status_code": 401, "body": {"error": {"message": "token expired"}}}"#).unwrap();
+        assert_eq!(ApiResponse::from_raw(raw).error_message(), "401 token expired");
+    }
+
+    #[test]
+    fn normalizes_api_base() {
+        let connection = |base: &str| ManagementConnection { api_base: base.into(), management_key: String::new() };
+        let client = ManagementClient::new(reqwest::Client::new(), &connection("localhost:8317/"));
+        assert_eq!(client.base_url, "http://localhost:8317/v0/management");
+        let client = ManagementClient::new(reqwest::Client::new(), &connection("https://host/v0/management"));
+        assert_eq!(client.base_url, "https://host/v0/management");
+    }
+}
@@ -0,0 +1,381 @@
+//! Account quotas of the upstream providers
+//!
+//! Every provider implements `QuotaProvider`: which upstream endpoints to
+//! call and how to read their answer. The calls go through the proxy's
+//! `/v0/management/api-call`, which fills in the account's token for the
+//! given auth index, so the app never sees provider credentials. Results of
+//! all providers share one `QuotaSnapshot` shape.
+
+pub mod alerts;
+pub mod antigravity;
+pub mod claude;
+pub mod codex;
+pub mod copilot;
+pub mod forecast;
+pub mod gemini_cli;
+pub mod history;
+pub mod kiro;
+pub mod management;
+pub mod poller;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use management::{ApiCall, ApiResponse, ManagementClient};
+
+/// Providers with a quota endpoint, named like the frontend's provider keys
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Provider {
+    #[serde(rename = "anthropic", alias = "claude")]
+    Claude,
+    Antigravity,
+    Codex,
+    GeminiCli,
+    Kiro,
+    Copilot,
+}
+
+impl Provider {
+    fn implementation(self) -> &'static dyn QuotaProvider {
+        match self {
+            Provider::Claude => &claude::Claude,
+            Provider::Antigravity => &antigravity::Antigravity,
+            Provider::Codex => &codex::Codex,
+            Provider::GeminiCli => &gemini_cli::GeminiCli,
+            Provider::Kiro => &kiro::Kiro,
+            Provider::Copilot => &copilot::Copilot,
+        }
+    }
+
+    /// Serialized name, e.g. `gemini-cli`
+    pub fn key(self) -> &'static str {
+        match self {
+            Provider::Claude => "anthropic",
+            Provider::Antigravity => "antigravity",
+            Provider::Codex => "codex",
+            Provider::GeminiCli => "gemini-cli",
+            Provider::Kiro => "kiro",
+            Provider::Copilot => "copilot",
+        }
+    }
+
+    /// Provider of an auth file, from its name first and its `provider` field second
+    pub fn of_auth_file(file: &Value) -> Option<Provider> {
+        let name = str_field(file, &["filename", "id"]).unwrap_or_default().to_lowercase();
+        let by_name = [
+            ("antigravity", Provider::Antigravity),
+            ("codex", Provider::Codex),
+            ("gemini", Provider::GeminiCli),
+            ("kiro", Provider::Kiro),
+            ("copilot", Provider::Copilot),
+            ("claude", Provider::Claude),
+            ("anthropic", Provider::Claude),
+        ];
+        let provider = str_field(file, &["provider", "type"]).unwrap_or_default().to_lowercase();
+        let by_provider = [("github", Provider::Copilot)];
+        by_name
+            .iter()
+            .find(|(key, _)| name.contains(key))
+            .or_else(|| by_name.iter().chain(&by_provider).find(|(key, _)| provider.contains(key)))
+            .map(|(_, provider)| *provider)
+    }
+}
+
+/// One limit of an account: a model, a rolling window or a monthly allowance
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct QuotaWindow {
+    pub name: String,
+    /// 0 to 100
+    pub remaining_percent: f64,
+    pub resets_at: Option<DateTime<Utc>>,
+    /// Extra detail, e.g. `12 / 50` credits or a suspension reason
+    pub detail: Option<String>,
+}
+
+impl QuotaWindow {
+    pub fn new(name: impl Into<String>, remaining_percent: f64, resets_at: Option<DateTime<Utc>>) -> Self {
+        Self {
+            name: name.into(),
+            remaining_percent: remaining_percent.clamp(0.0, 100.0),
+            resets_at,
+            detail: None,
+        }
+    }
+}
+
+/// What a provider's parser reads from a response
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Quota {
+    pub plan: Option<String>,
+    pub email: Option<String>,
+    pub windows: Vec<QuotaWindow>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaErrorKind {
+    /// The account has to sign in again
+    TokenExpired,
+    AccessDenied,
+    RateLimited,
+    /// The upstream answered with something we can't read
+    InvalidResponse,
+    /// The proxy or upstream couldn't be reached
+    Request,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct QuotaError {
+    pub kind: QuotaErrorKind,
+    pub message: String,
+}
+
+impl QuotaError {
+    pub fn new(kind: QuotaErrorKind, message: impl Into<String>) -> Self {
+        Self { kind, message: message.into() }
+    }
+
+    /// Classify a failed upstream response
+    pub fn from_response(response: &ApiResponse) -> Self {
+        let status = response.status_code;
+        let message = response.error_message();
+        match status {
+            401 | 403 => {
+                if ["token", "auth", "credential"].iter().any(|word| message.contains(word)) {
+                    Self::new(QuotaErrorKind::TokenExpired, format!("Token invalid or expired ({})", status))
+                } else {
+                    Self::new(QuotaErrorKind::AccessDenied, format!("Access denied ({})", status))
+                }
+            }
+            429 => Self::new(QuotaErrorKind::RateLimited, "Rate limit exceeded"),
+            _ if message.chars().count() > 100 => {
+                let short: String = message.chars().take(97).collect();
+                Self::new(QuotaErrorKind::Request, format!("{}...", short))
+            }
+            _ => Self::new(QuotaErrorKind::Request, message),
+        }
+    }
+}
+
+/// Quota of one account at one point in time, the same shape for every provider
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct QuotaSnapshot {
+    pub provider: Provider,
+    pub auth_index: String,
+    pub fetched_at: DateTime<Utc>,
+    pub plan: Option<String>,
+    pub email: Option<String>,
+    pub windows: Vec<QuotaWindow>,
+    /// Set when the quota couldn't be read; `windows` is empty then
+    pub error: Option<QuotaError>,
+}
+
+/// A provider's quota endpoint
+pub trait QuotaProvider: Send + Sync {
+    /// Whether `requests` needs the account's auth file entry
+    fn needs_account(&self) -> bool {
+        false
+    }
+
+    /// Upstream calls to try in order until one is answered and parsed
+    fn requests(&self, account: Option<&Value>) -> Result<Vec<ApiCall>, String>;
+
+    /// Read a successful response body
+    fn parse(&self, body: &Value, now: DateTime<Utc>) -> Result<Quota, String>;
+
+    /// Handle a non-2xx response, by default an error
+    fn on_error(&self, response: &ApiResponse) -> Result<Quota, QuotaError> {
+        Err(QuotaError::from_response(response))
+    }
+}
+
+/// Fetch the quota of the account behind `auth_index`
+///
+/// Failures end up in the snapshot's `error`, so callers always get one.
+pub async fn fetch(client: &ManagementClient, auth_index: &str, provider: Provider) -> QuotaSnapshot {
+    let result = fetch_quota(client, auth_index, provider).await;
+    let (quota, error) = match result {
+        Ok(quota) => (quota, None),
+        Err(error) => (Quota::default(), Some(error)),
+    };
+    QuotaSnapshot {
+        provider,
+        auth_index: auth_index.to_string(),
+        fetched_at: Utc::now(),
+        plan: quota.plan,
+        email: quota.email,
+        windows: quota.windows,
+        error,
+    }
+}
+
+async fn fetch_quota(client: &ManagementClient, auth_index: &str, provider: Provider) -> Result<Quota, QuotaError> {
+    let implementation = provider.implementation();
+    let account = if implementation.needs_account() {
+        let files = client
+            .auth_files()
+            .await
+            .map_err(|e| QuotaError::new(QuotaErrorKind::Request, e))?;
+        files.into_iter().find(|file| auth_index_of(file).as_deref() == Some(auth_index))
+    } else {
+        None
+    };
+    let requests = implementation
+        .requests(account.as_ref())
+        .map_err(|e| QuotaError::new(QuotaErrorKind::InvalidResponse, e))?;
+
+    let mut last_error = QuotaError::new(QuotaErrorKind::Request, "Failed to fetch quota");
+    for request in requests {
+        let response = match client.api_call(auth_index, &request).await {
+            Ok(response) => response,
+            Err(e) => {
+                last_error = QuotaError::new(QuotaErrorKind::Request, e);
+                continue;
+            }
+        };
+        if !response.is_success() {
+            match implementation.on_error(&response) {
+                Ok(quota) => return Ok(quota),
+                Err(error) => last_error = error,
+            }
+            continue;
+        }
+        match implementation.parse(&response.body, Utc::now()) {
+            Ok(quota) => return Ok(quota),
+            Err(e) => last_error = QuotaError::new(QuotaErrorKind::InvalidResponse, e),
+        }
+    }
+    Err(last_error)
+}
+
+/// The auth index the proxy knows an auth file by
+pub fn auth_index_of(file: &Value) -> Option<String> {
+    ["auth_index", "authIndex", "id", "filename"]
+        .iter()
+        .find_map(|key| match file.get(key)? {
+            Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+            Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        })
+}
+
+// Lenient readers for the loosely typed upstream payloads
+
+/// First non-empty string among `keys`
+fn str_field<'a>(value: &'a Value, keys: &[&str]) -> Option<&'a str> {
+    keys.iter()
+        .find_map(|key| value.get(key)?.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+/// A number, or a string holding one
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+    .filter(|n: &f64| n.is_finite())
+}
+
+/// First field among `keys` that holds a number
+fn number_field(value: &Value, keys: &[&str]) -> Option<f64> {
+    keys.iter().find_map(|key| value.get(key).and_then(number))
+}
+
+/// First field among `keys` that is present and not null
+fn field<'a>(value: &'a Value, keys: &[&str]) -> Option<&'a Value> {
+    keys.iter().find_map(|key| value.get(key).filter(|v| !v.is_null()))
+}
+
+/// RFC 3339 timestamp or plain date
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Some(time.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|time| time.and_utc())
+}
+
+/// Unix time in seconds, or milliseconds for large values
+fn unix_time(value: f64) -> Option<DateTime<Utc>> {
+    let millis = if value < 10_000_000_000.0 { value * 1000.0 } else { value };
+    DateTime::from_timestamp_millis(millis as i64)
+}
+
+#[cfg(test)]
+fn fixture(name: &str) -> Value {
+    let path = std::path::Path::new(file!()).with_file_name("fixtures").join(name);
+    let content = std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+    serde_json::from_str(&content).unwrap()
+}
+
+#[cfg(test)]
+fn test_now() -> DateTime<Utc> {
+    parse_time("2025-06-01T12:00:00Z").unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status_code: u16, body: Value) -> ApiResponse {
+        let body_text = body.to_string();
+        ApiResponse { status_code, body, body_text }
+    }
+
+    #[test]
+    fn detects_provider_of_auth_file() {
+        let of = |file: Value| Provider::of_auth_file(&file);
+        assert_eq!(of(serde_json::json!({"filename": "codex-me@example.com.json"})), Some(Provider::Codex));
+        assert_eq!(of(serde_json::json!({"id": "gemini-cli-me.json"})), Some(Provider::GeminiCli));
+        assert_eq!(of(serde_json::json!({"filename": "x.json", "provider": "github"})), Some(Provider::Copilot));
+        assert_eq!(of(serde_json::json!({"filename": "x.json", "provider": "openai-compat"})), None);
+    }
+
+    #[test]
+    fn classifies_auth_failures() {
+        let expired = QuotaError::from_response(&response(401, serde_json::json!({"error": {"message": "invalid token"}})));
+        assert_eq!(expired.kind, QuotaErrorKind::TokenExpired);
+        assert_eq!(expired.message, "Token invalid or expired (401)");
+
+        let denied = QuotaError::from_response(&response(403, serde_json::json!({"message": "forbidden"})));
+        assert_eq!(denied.kind, QuotaErrorKind::AccessDenied);
+
+        let limited = QuotaError::from_response(&response(429, Value::Null));
+        assert_eq!(limited.kind, QuotaErrorKind::RateLimited);
+    }
+
+    #[test]
+    fn truncates_long_messages() {
+        let error = QuotaError::from_response(&response(500, Value::String("x".repeat(150))));
+        assert_eq!(error.message.chars().count(), 100);
+        assert!(error.message.ends_with("..."));
+    }
+
+    #[test]
+    fn reads_auth_index() {
+        assert_eq!(auth_index_of(&serde_json::json!({"auth_index": 3, "id": "a"})).as_deref(), Some("3"));
+        assert_eq!(auth_index_of(&serde_json::json!({"id": " ", "filename": "f.json"})).as_deref(), Some("f.json"));
+    }
+
+    #[test]
+    fn parses_times() {
+        assert_eq!(parse_time("2025-06-01").unwrap(), parse_time("2025-06-01T00:00:00Z").unwrap());
+        assert_eq!(unix_time(1_748_779_200.0), parse_time("2025-06-01T12:00:00Z"));
+        assert_eq!(unix_time(1_748_779_200_000.0), parse_time("2025-06-01T12:00:00Z"));
+    }
+
+    #[test]
+    fn provider_names_match_frontend_keys() {
+        assert_eq!(serde_json::to_value(Provider::GeminiCli).unwrap(), "gemini-cli");
+        assert_eq!(serde_json::to_value(Provider::Claude).unwrap(), "anthropic");
+        assert_eq!(serde_json::from_value::<Provider>("claude".into()).unwrap(), Provider::Claude);
+    }
+}
@@ -0,0 +1,307 @@
+//! Background quota polling
+//!
+//! Refreshes the quota of every account the proxy knows on a timer, so the
+//! data stays current while the window is hidden to the tray. Each account
+//! has its own due time: the provider's interval with some random spread, so
+//! accounts don't all hit their provider at once. An account that keeps
+//! failing is paused for a while instead of being retried every interval.
+//! Results are cached in `QuotaPoller` and are emitted as `quota://updated`.
+//! Settings live in `~/.zerolimit/quota-poll.json`.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::Notify;
+
+use super::management::{ManagementClient, ManagementConnection};
+use super::{auth_index_of, Provider, QuotaSnapshot};
+use crate::state::AppState;
+
+pub const EVENT_UPDATED: &str = "quota://updated";
+/// How often due accounts are looked for
+const TICK: Duration = Duration::from_secs(15);
+/// How often the account list is read from the proxy
+const ACCOUNTS_INTERVAL: Duration = Duration::from_secs(60);
+const MIN_INTERVAL_SECS: u64 = 60;
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct PollSettings {
+    pub enabled: bool,
+    pub interval_secs: u64,
+    /// Overrides of `interval_secs`, e.g. a longer one for rate limited providers
+    pub provider_intervals: HashMap<Provider, u64>,
+    /// Random spread of every interval, in percent either way
+    pub jitter_percent: u8,
+    /// Failures in a row before an account is paused
+    pub max_errors: u32,
+    pub pause_secs: u64,
+}
+
+impl Default for PollSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 300,
+            provider_intervals: HashMap::new(),
+            jitter_percent: 10,
+            max_errors: 3,
+            pause_secs: 1800,
+        }
+    }
+}
+
+impl PollSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        let intervals = std::iter::once(&self.interval_secs).chain(self.provider_intervals.values());
+        if intervals.into_iter().any(|secs| *secs < MIN_INTERVAL_SECS) {
+            return Err(format!("Polling intervals must be at least {} seconds", MIN_INTERVAL_SECS));
+        }
+        if self.jitter_percent > 50 {
+            return Err("Jitter can be at most 50%".to_string());
+        }
+        if self.max_errors == 0 {
+            return Err("Allow at least one failure before pausing".to_string());
+        }
+        Ok(())
+    }
+
+    fn interval(&self, provider: Provider) -> Duration {
+        let secs = self.provider_intervals.get(&provider).copied().unwrap_or(self.interval_secs);
+        Duration::from_secs(secs.max(MIN_INTERVAL_SECS))
+    }
+
+    /// Wait before the next refresh of an account that just succeeded
+    fn next_delay(&self, provider: Provider) -> Duration {
+        let interval = self.interval(provider).as_secs_f64();
+        let spread = interval * f64::from(self.jitter_percent) / 100.0;
+        Duration::from_secs_f64(interval + spread * (fastrand::f64() * 2.0 - 1.0))
+    }
+}
+
+/// `~/.zerolimit/quota-poll.json`
+fn settings_path() -> Option<PathBuf> {
+    let mut path = dirs::home_dir()?;
+    path.push(".zerolimit");
+    path.push("quota-poll.json");
+    Some(path)
+}
+
+/// Stored settings, or the defaults
+pub fn load() -> PollSettings {
+    settings_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save(settings: &PollSettings) -> io::Result<()> {
+    let path = settings_path()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Could not determine user home directory"))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let json = serde_json::to_string_pretty(settings)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    fs::write(path, json)
+}
+
+type AccountKey = (Provider, String);
+
+/// The poller's settings and the latest snapshot of every account
+pub struct QuotaPoller {
+    settings: Mutex<PollSettings>,
+    snapshots: Mutex<HashMap<AccountKey, QuotaSnapshot>>,
+    /// Wakes the poller early when settings or the connection change
+    wake: Notify,
+}
+
+impl Default for QuotaPoller {
+    fn default() -> Self {
+        Self {
+            settings: Mutex::new(load()),
+            snapshots: Mutex::default(),
+            wake: Notify::new(),
+        }
+    }
+}
+
+impl QuotaPoller {
+    pub fn settings(&self) -> PollSettings {
+        self.settings.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn apply(&self, settings: PollSettings) {
+        *self.settings.lock().unwrap_or_else(|e| e.into_inner()) = settings;
+        self.wake();
+    }
+
+    pub fn wake(&self) {
+        self.wake.notify_one();
+    }
+
+    /// Every cached snapshot, by provider and auth index
+    pub fn snapshots(&self) -> Vec<QuotaSnapshot> {
+        let snapshots = self.snapshots.lock().unwrap_or_else(|e| e.into_inner());
+        let mut list: Vec<_> = snapshots.values().cloned().collect();
+        list.sort_by(|a, b| (a.provider, &a.auth_index).cmp(&(b.provider, &b.auth_index)));
+        list
+    }
+
+    fn insert(&self, snapshot: QuotaSnapshot) {
+        let mut snapshots = self.snapshots.lock().unwrap_or_else(|e| e.into_inner());
+        snapshots.insert((snapshot.provider, snapshot.auth_index.clone()), snapshot);
+    }
+
+    fn retain(&self, keep: impl Fn(&AccountKey) -> bool) {
+        let mut snapshots = self.snapshots.lock().unwrap_or_else(|e| e.into_inner());
+        snapshots.retain(|key, _| keep(key));
+    }
+}
+
+/// Cache a fresh snapshot, add it to the history, raise its alerts and tell the frontend about it
+///
+/// The history write and notifications block, so they run off the async workers.
+pub async fn publish(app: &AppHandle, snapshot: QuotaSnapshot) {
+    let _ = app.emit(EVENT_UPDATED, &snapshot);
+    let app = app.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        super::alerts::notify(&app, &snapshot);
+        let state = app.state::<AppState>();
+        state.history().record(&snapshot);
+        state.quota().insert(snapshot);
+    })
+    .await;
+    if let Err(e) = result {
+        println!("Notice: Failed to publish quota snapshot: {}", e);
+    }
+}
+
+/// When an account is refreshed next and how often it failed in a row
+struct Schedule {
+    due: Instant,
+    errors: u32,
+}
+
+/// Poll quotas until the app exits
+pub fn spawn(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut accounts: HashMap<AccountKey, Schedule> = HashMap::new();
+        let mut connected: Option<ManagementConnection> = None;
+        let mut listed_at: Option<Instant> = None;
+
+        loop {
+            let state = app.state::<AppState>();
+            let settings = state.quota().settings();
+            let connection = state.management();
+
+            if connection != connected {
+                // Another proxy or a logout: nothing cached belongs to it
+                accounts.clear();
+                state.quota().retain(|_| false);
+                connected = connection.clone();
+                listed_at = None;
+            }
+
+            if let Some(connection) = connection.filter(|_| settings.enabled) {
+                let client = ManagementClient::new(state.http().client(), &connection);
+
+                if listed_at.is_none_or(|at| at.elapsed() >= ACCOUNTS_INTERVAL) {
+                    listed_at = Some(Instant::now());
+                    match client.auth_files().await {
+                        Ok(files) => {
+                            let current: Vec<AccountKey> = files
+                                .iter()
+                                .filter_map(|file| Some((Provider::of_auth_file(file)?, auth_index_of(file)?)))
+                                .collect();
+                            accounts.retain(|key, _| current.contains(key));
+                            state.quota().retain(|key| current.contains(key));
+                            for key in current {
+                                accounts.entry(key).or_insert(Schedule { due: Instant::now(), errors: 0 });
+                            }
+                        }
+                        Err(e) => println!("Notice: Quota polling could not list accounts: {}", e),
+                    }
+                }
+
+                for ((provider, auth_index), schedule) in accounts.iter_mut() {
+                    if schedule.due > Instant::now() {
+                        continue;
+                    }
+                    let snapshot = super::fetch(&client, auth_index, *provider).await;
+                    let delay = match &snapshot.error {
+                        None => {
+                            schedule.errors = 0;
+                            settings.next_delay(*provider)
+                        }
+                        Some(error) => {
+                            schedule.errors += 1;
+                            if schedule.errors >= settings.max_errors {
+                                println!(
+                                    "Notice: Pausing quota polling of {} for {}s after {} failures: {}",
+                                    auth_index, settings.pause_secs, schedule.errors, error.message
+                                );
+                                Duration::from_secs(settings.pause_secs)
+                            } else {
+                                settings.next_delay(*provider)
+                            }
+                        }
+                    };
+                    schedule.due = Instant::now() + delay;
+                    publish(&app, snapshot).await;
+                }
+            }
+
+            tokio::select! {
+                _ = tokio::time::sleep(TICK) => {}
+                _ = state.quota().wake.notified() => {}
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jitter_stays_within_spread() {
+        let settings = PollSettings {
+            provider_intervals: HashMap::from([(Provider::Copilot, 600)]),
+            ..PollSettings::default()
+        };
+        for _ in 0..100 {
+            let delay = settings.next_delay(Provider::Claude).as_secs_f64();
+            assert!((270.0..=330.0).contains(&delay), "{}", delay);
+            let delay = settings.next_delay(Provider::Copilot).as_secs_f64();
+            assert!((540.0..=660.0).contains(&delay), "{}", delay);
+        }
+    }
+
+    #[test]
+    fn rejects_short_intervals() {
+        assert!(PollSettings::default().validate().is_ok());
+        let settings = PollSettings { interval_secs: 10, ..PollSettings::default() };
+        assert!(settings.validate().is_err());
+        let settings = PollSettings {
+            provider_intervals: HashMap::from([(Provider::Kiro, 5)]),
+            ..PollSettings::default()
+        };
+        assert!(settings.validate().is_err());
+    }
+
+    #[test]
+    fn reads_provider_intervals_by_name() {
+        let settings: PollSettings =
+            serde_json::from_str(r#"{"interval_secs": 120, "provider_intervals": {"gemini-cli": 900}}"#).unwrap();
+        assert_eq!(settings.interval(Provider::GeminiCli), Duration::from_secs(900));
+        assert_eq!(settings.interval(Provider::Codex), Duration::from_secs(120));
+        assert!(settings.enabled);
+    }
+}
@@ -1,21 +1,113 @@
-//! Global state management
+//! Application state management
+//!
+//! `AppState` is registered with `tauri::Builder::manage` and reached from
+//! commands through `tauri::State`.
 
-use std::process::Child;
-use std::sync::Mutex;
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 
-/// CLI Proxy process state
-pub static CLI_PROXY_PROCESS: Mutex<Option<Child>> = Mutex::new(None);
-/// CLI Proxy executable name (for fallback kill)
-pub static CLI_PROXY_NAME: Mutex<Option<String>> = Mutex::new(None);
+use crate::install::download::DownloadRegistry;
+use crate::net::HttpClient;
+use crate::proxy::instance::ProxyInstance;
+use crate::proxy::stats::StatsSampler;
+use crate::quota::alerts::AlertEngine;
+use crate::quota::history::QuotaHistory;
+use crate::quota::management::ManagementConnection;
+use crate::quota::poller::QuotaPoller;
 
-/// Run in background setting (hide to tray on close) - default false, synced from frontend on startup
-pub static RUN_IN_BACKGROUND: AtomicBool = AtomicBool::new(false);
+/// Instance id used when the frontend doesn't pass one
+pub const DEFAULT_INSTANCE_ID: &str = "default";
 
-pub fn get_run_in_background() -> bool {
-    RUN_IN_BACKGROUND.load(Ordering::Relaxed)
+#[derive(Default)]
+pub struct AppState {
+    /// Managed CLI Proxy instances keyed by instance id
+    instances: Mutex<HashMap<String, Arc<ProxyInstance>>>,
+    /// Run in background setting (hide to tray on close) - default false, synced from frontend on startup
+    run_in_background: AtomicBool,
+    /// Previous CPU samples for `get_proxy_stats` and `proxy://stats`
+    stats: StatsSampler,
+    /// Running proxy downloads, for `cancel_proxy_download`
+    downloads: DownloadRegistry,
+    /// Shared client for outbound requests, rebuilt when its settings change
+    http: HttpClient,
+    /// Management API the frontend is logged in to, for quota requests
+    management: Mutex<Option<ManagementConnection>>,
+    /// Background quota refresh and its cached snapshots
+    quota: QuotaPoller,
+    /// Stored snapshots for history charts
+    history: QuotaHistory,
+    /// Notification rules checked against every fresh snapshot
+    alerts: AlertEngine,
 }
 
-pub fn set_run_in_background(enabled: bool) {
-    RUN_IN_BACKGROUND.store(enabled, Ordering::Relaxed);
+impl AppState {
+    /// Get the instance with this id, registering it if it doesn't exist yet
+    pub fn instance(&self, id: &str) -> Arc<ProxyInstance> {
+        let mut instances = self.instances.lock().unwrap_or_else(|e| e.into_inner());
+        instances
+            .entry(id.to_string())
+            .or_insert_with(|| Arc::new(ProxyInstance::new(id.to_string())))
+            .clone()
+    }
+
+    pub fn find_instance(&self, id: &str) -> Option<Arc<ProxyInstance>> {
+        let instances = self.instances.lock().unwrap_or_else(|e| e.into_inner());
+        instances.get(id).cloned()
+    }
+
+    /// All registered instances, sorted by id
+    pub fn instances(&self) -> Vec<Arc<ProxyInstance>> {
+        let instances = self.instances.lock().unwrap_or_else(|e| e.into_inner());
+        let mut list: Vec<_> = instances.values().cloned().collect();
+        list.sort_by(|a, b| a.id.cmp(&b.id));
+        list
+    }
+
+    pub fn stats(&self) -> &StatsSampler {
+        &self.stats
+    }
+
+    pub fn downloads(&self) -> &DownloadRegistry {
+        &self.downloads
+    }
+
+    pub fn http(&self) -> &HttpClient {
+        &self.http
+    }
+
+    pub fn quota(&self) -> &QuotaPoller {
+        &self.quota
+    }
+
+    pub fn history(&self) -> &QuotaHistory {
+        &self.history
+    }
+
+    pub fn alerts(&self) -> &AlertEngine {
+        &self.alerts
+    }
+
+    pub fn management(&self) -> Option<ManagementConnection> {
+        self.management.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn set_management(&self, connection: Option<ManagementConnection>) {
+        *self.management.lock().unwrap_or_else(|e| e.into_inner()) = connection;
+        self.quota.wake();
+    }
+
+    pub fn get_run_in_background(&self) -> bool {
+        self.run_in_background.load(Ordering::Relaxed)
+    }
+
+    pub fn set_run_in_background(&self, enabled: bool) {
+        self.run_in_background.store(enabled, Ordering::Relaxed);
+    }
+}
+
+/// Resolve an optional instance id from the frontend
+pub fn instance_id(id: Option<String>) -> String {
+    id.filter(|s| !s.is_empty())
+        .unwrap_or_else(|| DEFAULT_INSTANCE_ID.to_string())
 }
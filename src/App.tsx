@@ -9,12 +9,16 @@ import { LoginPage } from '@/features/auth/LoginPage'
 import { OnboardingFlow } from '@/features/onboarding/OnboardingFlow'
 import Default from './layouts/DefaultLayout'
 import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
 import { Toaster } from '@/shared/components/ui/sonner'
 
+// Instance id the backend uses when none is passed to the proxy commands
+const DEFAULT_PROXY_INSTANCE = 'default'
+
 function App() {
   const { isAuthenticated, restoreSession, connectionStatus } = useAuthStore()
   const { theme, setTheme } = useThemeStore()
-  const { exePath, autoStart, runInBackground, startServer, hasCompletedOnboarding, checkForProxyUpdate } = useCliProxyStore()
+  const { exePath, autoStart, runInBackground, startServer, checkServerStatus, hasCompletedOnboarding, checkForProxyUpdate } = useCliProxyStore()
   const { checkForUpdates } = useUpdateStore()
 
   useEffect(() => {
@@ -22,8 +26,11 @@ function App() {
     setTheme(theme)
 
     invoke('set_run_in_background', { enabled: runInBackground }).catch(console.error)
+    // start_cli_proxy returns the pid of a proxy adopted from a previous session
     if (autoStart && exePath) {
       startServer()
+    } else {
+      checkServerStatus()
     }
 
     checkForUpdates().catch(() => {
@@ -31,6 +38,34 @@ function App() {
     checkForProxyUpdate().catch(() => {})
   }, [])
 
+  useEffect(() => {
+    const unlisteners = [
+      listen<{ instance_id: string; will_restart: boolean }>('proxy://exited', ({ payload }) => {
+        if (payload.instance_id !== DEFAULT_PROXY_INSTANCE) return
+        if (!payload.will_restart) {
+          useCliProxyStore.setState({ isServerRunning: false, isApiHealthy: false, serverPid: null })
+        } else {
+          useCliProxyStore.setState({ isApiHealthy: false })
+        }
+      }),
+      listen<{ instance_id: string; pid: number }>('proxy://restarted', ({ payload }) => {
+        if (payload.instance_id !== DEFAULT_PROXY_INSTANCE) return
+        useCliProxyStore.setState({ isServerRunning: true, serverPid: payload.pid })
+      }),
+      listen<{ id: string; pid: number | null }>('proxy://adopted', ({ payload }) => {
+        if (payload.id !== DEFAULT_PROXY_INSTANCE) return
+        useCliProxyStore.setState({ isServerRunning: true, serverPid: payload.pid })
+      }),
+      listen<{ instance_id: string }>('proxy://gave-up', ({ payload }) => {
+        if (payload.instance_id !== DEFAULT_PROXY_INSTANCE) return
+        useCliProxyStore.setState({ isServerRunning: false, isApiHealthy: false, serverPid: null })
+      }),
+    ]
+    return () => {
+      unlisteners.forEach((p) => p.then((unlisten) => unlisten()))
+    }
+  }, [])
+
   if (connectionStatus === 'connecting') {
     return (
       <div className="flex h-screen items-center justify-center bg-background">
@@ -1,4 +1,5 @@
 import { create } from 'zustand';
+import { invoke } from '@tauri-apps/api/core';
 import { persist, createJSONStorage } from 'zustand/middleware';
 import type { AuthState, LoginCredentials, ConnectionStatus } from '@/types';
 import { STORAGE_KEY_AUTH } from '@/constants';
@@ -20,6 +21,16 @@ interface AuthStoreState extends AuthState {
 
 let restoreSessionPromise: Promise<boolean> | null = null;
 
+/** Share the management connection with the backend, which fetches quotas itself */
+async function syncManagementConnection(apiBase: string | null, managementKey = '') {
+  const connection = apiBase ? { api_base: apiBase, management_key: managementKey } : null;
+  try {
+    await invoke('set_management_connection', { connection });
+  } catch (error) {
+    console.warn('Failed to share management connection:', error);
+  }
+}
+
 export const useAuthStore = create<AuthStoreState>()(
   persist(
     (set, get) => ({
@@ -85,6 +96,7 @@ export const useAuthStore = create<AuthStoreState>()(
           set({ connectionStatus: 'connecting' });
           apiClient.setConfig({ apiBase, managementKey });
           await useConfigStore.getState().fetchConfig(true);
+          await syncManagementConnection(apiBase, managementKey);
 
           set({
             isAuthenticated: true,
@@ -112,6 +124,7 @@ export const useAuthStore = create<AuthStoreState>()(
       logout: () => {
         restoreSessionPromise = null;
         useConfigStore.getState().clearCache();
+        void syncManagementConnection(null);
         set({
           isAuthenticated: false,
           apiBase: '',
@@ -134,6 +147,7 @@ export const useAuthStore = create<AuthStoreState>()(
         try {
           apiClient.setConfig({ apiBase, managementKey });
           await useConfigStore.getState().fetchConfig();
+          await syncManagementConnection(apiBase, managementKey);
           set({
             isAuthenticated: true,
             connectionStatus: 'connected',
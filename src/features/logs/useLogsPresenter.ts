@@ -3,6 +3,7 @@ import { logsApi, type LogFile, type ServerLogsResponse } from '@/services/api/l
 import { toast } from 'sonner'
 import { save } from '@tauri-apps/plugin-dialog'
 import { writeTextFile } from '@tauri-apps/plugin-fs'
+import { invoke } from '@tauri-apps/api/core'
 
 export type LogTab = 'server' | 'error'
 
@@ -57,6 +58,20 @@ export function useLogsPresenter() {
       setServerLogs(logs)
     } catch (error) {
       console.error('Failed to fetch server logs:', error)
+      // Fall back to the process output captured by the Tauri backend
+      try {
+        const output = await invoke<{ seq: number; line: string; timestamp: number }[]>('get_proxy_output', { sinceSeq: null })
+        if (output.length > 0) {
+          setServerLogs({
+            lines: output.map((entry) => entry.line),
+            'line-count': output.length,
+            'latest-timestamp': Math.floor(output[output.length - 1].timestamp / 1000),
+          })
+          return
+        }
+      } catch (outputError) {
+        console.error('Failed to fetch proxy output:', outputError)
+      }
       toast.error('Error fetching server logs', {
         description: 'Could not load server logs.',
       })
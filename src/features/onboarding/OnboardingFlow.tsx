@@ -5,8 +5,7 @@ import { Button } from '@/shared/components/ui/button';
 import { Card, CardContent } from '@/shared/components/ui/card';
 import { Download, FolderOpen, Zap, ShieldCheck, ArrowRight, Loader2, Key } from 'lucide-react';
 import { toast } from 'sonner';
-import { type } from '@tauri-apps/plugin-os';
-import { homeDir, dirname, join } from '@tauri-apps/api/path';
+import { homeDir } from '@tauri-apps/api/path';
 import { invoke } from '@tauri-apps/api/core';
 import { readTextFile, writeTextFile } from '@tauri-apps/plugin-fs';
 import { Input } from '@/shared/components/ui/input';
@@ -74,30 +73,22 @@ export function OnboardingFlow() {
     setIsFetchingVersion(true);
     try {
       if (selectedMode === 'auto_download') {
-        const repo = version === 'plus' ? 'CLIProxyAPIPlus' : 'CLIProxyAPI';
-        const res = await fetch(`https://api.github.com/repos/router-for-me/${repo}/releases/latest`, {
-          headers: { 'User-Agent': 'CLIProxyAPI' }
-        });
-        if (res.ok) {
-          const data = await res.json();
-          setCliProxyLatestVersion(data.tag_name || data.name);
-          const osType = await type();
-          let osName = 'windows';
-          if (osType === 'macos') osName = 'darwin';
-          if (osType === 'linux') osName = 'linux';
-          const searchString = `${osName}_`;
-          const asset = data.assets?.find((a: any) =>
-            a.name.toLowerCase().includes(searchString) &&
-            (a.name.endsWith('.zip') || a.name.endsWith('.tar.gz') || a.name.endsWith('.tgz'))
+        try {
+          const release = await invoke<{ tag: string; download_url: string; checksums_url: string | null }>(
+            'resolve_proxy_release', { edition: version }
           );
-          if (asset?.browser_download_url) {
-            toast.info('Downloading Proxy...');
-            try {
-              const exePath = await invoke<string>('download_and_extract_proxy', { url: asset.browser_download_url });
-              if (exePath) { useCliProxyStore.getState().setExePath(exePath); toast.success('Proxy downloaded!'); }
-            } catch (e) { console.error(e); toast.error('Extraction failed.'); }
-          } else { toast.error('No compatible package found.'); }
-        } else { toast.error('Failed to fetch version info.'); }
+          setCliProxyLatestVersion(release.tag);
+          toast.info('Downloading Proxy...');
+          try {
+            const { exe_path: exePath } = await invoke<{ exe_path: string }>('download_and_extract_proxy', {
+              url: release.download_url,
+              tag: release.tag,
+              verify: release.checksums_url ? { checksums_url: release.checksums_url } : null,
+              mirrors: useCliProxyStore.getState().downloadMirrors,
+            });
+            if (exePath) { useCliProxyStore.getState().setExePath(exePath); toast.success('Proxy downloaded!'); }
+          } catch (e) { console.error(e); toast.error('Extraction failed.'); }
+        } catch (e) { console.error(e); toast.error('No compatible package found.'); }
       }
       setCliProxyMode(selectedMode);
       setCliProxyVersion(version);
@@ -117,9 +108,11 @@ export function OnboardingFlow() {
     try {
       const { exePath } = useCliProxyStore.getState();
       if (!exePath) throw new Error('No proxy executable path found.');
-      const proxyDir = await dirname(exePath);
-      const configPath = await join(proxyDir, 'config.yaml');
-      const exampleConfigPath = await join(proxyDir, 'config.example.yaml');
+      // Versioned builds share config.yaml in the install root, not next to the exe
+      const { config_path: configPath, example_path: exampleConfigPath } = await invoke<{
+        config_path: string;
+        example_path: string;
+      }>('get_proxy_config_path', { exePath });
       let configContent = '';
       try { configContent = await readTextFile(configPath); } catch { configContent = await readTextFile(exampleConfigPath); }
       configContent = configContent.replace(/secret-key:\s*["'][^"']*["']/, `secret-key: "${managementKey}"`);
@@ -129,25 +122,34 @@ export function OnboardingFlow() {
       configContent = configContent.replace(/auth-dir:\s*.*$/, `auth-dir: "${authDir}"`);
       await writeTextFile(configPath, configContent);
       const started = await startServer();
+      // Where start_cli_proxy put the proxy, which may not be the default port
+      const apiBase = useCliProxyStore.getState().serverApiBase || useAuthStore.getState().apiBase;
       if (!started) {
-        const healthy = await checkApiHealth();
+        const healthy = await checkApiHealth(apiBase || undefined);
         if (!healthy) { toast.error('Proxy failed to start.'); }
         else { toast.success('CLI Proxy configuration complete!'); }
       } else {
-        // Give server a moment to boot
-        await new Promise(r => setTimeout(r, 1500));
-        toast.success('CLI Proxy configuration complete!');
+        const readiness = await invoke<{ status: string }>('wait_for_proxy_ready', {
+          apiBase,
+          managementKey,
+          timeoutMs: 15000,
+        });
+        if (readiness.status === 'ready') { toast.success('CLI Proxy configuration complete!'); }
+        else if (readiness.status === 'auth_rejected') { toast.error('Proxy rejected the management key.'); }
+        else { toast.error('Proxy did not become ready in time.'); }
       }
 
       // Auto-login with the management key just entered
-      try {
-        await useAuthStore.getState().login({
-          apiBase: 'http://localhost:8317',
-          managementKey,
-          rememberPassword: true,
-        });
-      } catch (loginErr) {
-        console.warn('Auto-login after onboarding failed:', loginErr);
+      if (apiBase) {
+        try {
+          await useAuthStore.getState().login({
+            apiBase,
+            managementKey,
+            rememberPassword: true,
+          });
+        } catch (loginErr) {
+          console.warn('Auto-login after onboarding failed:', loginErr);
+        }
       }
     } catch (err) { console.error(err); toast.error('Failed to update config.'); }
     finally { setIsSettingKey(false); setHasCompletedOnboarding(true); }
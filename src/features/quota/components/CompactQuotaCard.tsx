@@ -15,6 +15,7 @@ import {
 } from '@/shared/components/ui/dialog'
 import { maskEmail } from '@/shared/utils/privacy';
 import type { QuotaModel } from '@/types';
+import { ExhaustionHint } from '@/features/quota/components/ExhaustionHint';
 
 
 interface CompactQuotaCardProps {
@@ -236,6 +237,7 @@ export function CompactQuotaCard({
                               <span>Reset: {item.resetTime}</span>
                             </div>
                           )}
+                          <ExhaustionHint exhaustion={item.exhaustion} />
                         </div>
                       ))}
                     </div>
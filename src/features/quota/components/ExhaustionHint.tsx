@@ -0,0 +1,17 @@
+import { TrendingDown } from 'lucide-react';
+import { useTranslation } from 'react-i18next';
+import { formatTimeUntil } from '@/shared/utils/quota.helpers';
+import type { QuotaModel } from '@/types';
+
+/** "Runs out in …" for a model forecast to be used up before its reset */
+export function ExhaustionHint({ exhaustion }: { exhaustion?: QuotaModel['exhaustion'] }) {
+  const { t } = useTranslation();
+  if (!exhaustion) return null;
+
+  return (
+    <div className="flex items-center gap-1.5 text-xs text-orange-500" title={t('quotaCard.forecastConfidence', { percent: Math.round(exhaustion.confidence * 100) })}>
+      <TrendingDown className="h-3 w-3" />
+      <span>{t('quotaCard.runsOut', { time: formatTimeUntil(exhaustion.at) })}</span>
+    </div>
+  );
+}
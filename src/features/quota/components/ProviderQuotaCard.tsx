@@ -13,6 +13,7 @@ import {
 import { useMemo } from 'react';
 import { maskEmail, maskFolder } from '@/shared/utils/privacy';
 import type { QuotaModel } from '@/types';
+import { ExhaustionHint } from '@/features/quota/components/ExhaustionHint';
 
 
 interface ProviderQuotaCardProps {
@@ -206,6 +207,7 @@ export function ProviderQuotaCard({
                                                         <span>Reset: {item.resetTime}</span>
                                                     </div>
                                                 )}
+                                                <ExhaustionHint exhaustion={item.exhaustion} />
                                             </div>
                                         ))}
                                     </div>
@@ -1,10 +1,11 @@
 import { useState, useEffect, useCallback, useMemo } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { useAuthStore } from '@/features/auth/auth.store';
 import { authFilesApi } from '@/services/api/auth.service';
-import { quotaApi } from '@/services/api/quota.service';
-import type { AuthFile, FileQuota, ProviderSection } from '@/types';
+import type { AuthFile, FileQuota, ProviderSection, QuotaForecast, QuotaModel, QuotaSnapshot } from '@/types';
 import type { ProviderFilterItem } from '@/features/quota/components/ProviderFilter';
-import { resolveCodexChatgptAccountId, resolveCodexPlanType, resolveGeminiCliProjectId } from '@/shared/utils/quota.helpers';
+import { formatTimeUntil, resolveCodexPlanType } from '@/shared/utils/quota.helpers';
 
 function getProviderType(file: AuthFile): 'antigravity' | 'codex' | 'gemini-cli' | 'kiro' | 'copilot' | 'anthropic' | 'unknown' {
   const filename = (file?.filename || file?.id || '').toLowerCase();
@@ -27,6 +28,46 @@ function getProviderType(file: AuthFile): 'antigravity' | 'codex' | 'gemini-cli'
   return 'unknown';
 }
 
+function toQuotaModels(snapshot: QuotaSnapshot): QuotaModel[] {
+  return snapshot.windows.map((w) => ({
+    name: w.name,
+    percentage: Math.round(w.remaining_percent),
+    // A suspended Kiro account has no reset, only the reason
+    resetTime: w.resets_at ? formatTimeUntil(w.resets_at) : snapshot.plan === 'Suspended' ? w.detail ?? undefined : undefined,
+    displayValue: w.detail ?? undefined,
+  }));
+}
+
+function authIndexOf(file?: AuthFile): string | undefined {
+  return (file?.['auth_index'] as string) || (file?.['authIndex'] as string) || file?.id || file?.filename;
+}
+
+/** Fields of a file's quota entry that come from a snapshot */
+function fromSnapshot(snapshot: QuotaSnapshot, quota: FileQuota): Partial<FileQuota> {
+  const models = toQuotaModels(snapshot);
+  const fields: Partial<FileQuota> = snapshot.provider === 'codex'
+    ? { plan: snapshot.plan || (quota.originalFile && resolveCodexPlanType(quota.originalFile)) || 'Plus', limits: models }
+    : { plan: snapshot.plan ?? undefined, models };
+  return {
+    ...fields,
+    loading: false,
+    email: snapshot.email ?? quota.email,
+    error: snapshot.error?.message,
+  };
+}
+
+/** Mark the models that are forecast to run out before they reset */
+function withForecasts(models: QuotaModel[] | undefined, forecasts: QuotaForecast[]): QuotaModel[] | undefined {
+  return models?.map((m) => {
+    const forecast = forecasts.find(f => f.model === m.name);
+    if (!forecast) return m;
+    const exhaustion = forecast.predicted_exhaustion_at && forecast.resets_first === false
+      ? { at: forecast.predicted_exhaustion_at, confidence: forecast.confidence }
+      : undefined;
+    return { ...m, exhaustion };
+  });
+}
+
 function formatFilename(name: string): string {
   return name.replace(/_gmail_com/g, '').replace(/\.json$/g, '');
 }
@@ -59,6 +100,26 @@ export function useQuotaPresenter() {
   const [viewMode, setViewMode] = useState<'list' | 'card'>('list');
   const [isPrivacyMode, setIsPrivacyMode] = useState(true);
 
+  const applySnapshot = useCallback((snapshot: QuotaSnapshot) => {
+    setSections((prev) => prev.map(s => s.provider === snapshot.provider ? {
+      ...s,
+      files: s.files.map(f => authIndexOf(f.originalFile) === snapshot.auth_index ? { ...f, ...fromSnapshot(snapshot, f) } : f)
+    } : s));
+  }, []);
+
+  const loadForecasts = useCallback(async (account?: string) => {
+    const forecasts = await invoke<QuotaForecast[]>('forecast_quota', { account: account ?? null }).catch(() => [] as QuotaForecast[]);
+    if (forecasts.length === 0) return;
+    setSections((prev) => prev.map(s => ({
+      ...s,
+      files: s.files.map(f => {
+        const own = forecasts.filter(fc => fc.provider === s.provider && fc.account === authIndexOf(f.originalFile));
+        if (own.length === 0) return f;
+        return { ...f, models: withForecasts(f.models, own), limits: withForecasts(f.limits, own) };
+      })
+    })));
+  }, []);
+
   const fetchQuotaForFile = useCallback(async (fileId: string, providedFile?: AuthFile) => {
     let targetProvider: string | undefined;
 
@@ -96,81 +157,20 @@ export function useQuotaPresenter() {
 
       if (!file) throw new Error('File not found');
 
-      const authIndex = (file['auth_index'] as string) || (file['authIndex'] as string) || file.id || file.filename;
+      const authIndex = authIndexOf(file);
       if (!authIndex) throw new Error('No auth index (auth_index, id or filename) found');
 
-      if (targetProvider === 'antigravity') {
-        const result = await quotaApi.fetchAntigravity(authIndex);
-        setSections((prev) => prev.map(s => s.provider === 'antigravity' ? {
-          ...s,
-          files: s.files.map(f => f.fileId === fileId ? {
-            ...f, loading: false, models: result.models, error: result.error
-          } : f)
-        } : s));
-
-      } else if (targetProvider === 'codex') {
-        const accountId = resolveCodexChatgptAccountId(file);
-        const result = await quotaApi.fetchCodex(authIndex, accountId || undefined);
-        const plan = result.plan || resolveCodexPlanType(file) || 'Plus';
-
-        setSections((prev) => prev.map(s => s.provider === 'codex' ? {
-          ...s,
-          files: s.files.map(f => f.fileId === fileId ? {
-            ...f, loading: false, plan, limits: result.limits, error: result.error
-          } : f)
-        } : s));
-
-      } else if (targetProvider === 'gemini-cli') {
-        const projectId = resolveGeminiCliProjectId(file);
-        if (!projectId) throw new Error('Project ID not found in file');
-        const result = await quotaApi.fetchGeminiCli(authIndex, projectId);
-
-        setSections((prev) => prev.map(s => s.provider === 'gemini-cli' ? {
-          ...s,
-          files: s.files.map(f => f.fileId === fileId ? {
-            ...f,
-            loading: false,
-            models: result.buckets.map(b => ({
-              name: b.modelId, percentage: b.percentage, resetTime: b.resetTime
-            })),
-            error: result.error
-          } : f)
-        } : s));
-
-      } else if (targetProvider === 'kiro') {
-        const result = await quotaApi.fetchKiro(authIndex);
-        setSections((prev) => prev.map(s => s.provider === 'kiro' ? {
-          ...s,
-          files: s.files.map(f => f.fileId === fileId ? {
-            ...f, loading: false, plan: result.plan, models: result.models, email: result.email, error: result.error
-          } : f)
-        } : s));
-
-      } else if (targetProvider === 'copilot') {
-        const result = await quotaApi.fetchCopilot(authIndex);
-        setSections((prev) => prev.map(s => s.provider === 'copilot' ? {
-          ...s,
-          files: s.files.map(f => f.fileId === fileId ? {
-            ...f, loading: false, plan: result.plan, models: result.models, error: result.error
-          } : f)
-        } : s));
-      } else if (targetProvider === 'anthropic') {
-        const result = await quotaApi.fetchClaude(authIndex);
-        setSections((prev) => prev.map(s => s.provider === 'anthropic' ? {
-          ...s,
-          files: s.files.map(f => f.fileId === fileId ? {
-            ...f, loading: false, email: result.email, models: result.models, error: result.error
-          } : f)
-        } : s));
-      }
+      const snapshot = await invoke<QuotaSnapshot>('fetch_quota', { authIndex, provider: targetProvider });
+      applySnapshot(snapshot);
     } catch (err) {
-      const msg = (err as Error).message;
+      // Tauri commands reject with a `CommandError` object
+      const msg = (err as { message?: string })?.message || String(err);
       setSections((prev) => prev.map(section => ({
         ...section,
         files: section.files.map(f => f.fileId === fileId ? { ...f, loading: false, error: msg } : f)
       })));
     }
-  }, [sections]);
+  }, [sections, applySnapshot]);
 
   const loadAuthFiles = useCallback(async () => {
     if (!isAuthenticated) return;
@@ -207,8 +207,14 @@ export function useQuotaPresenter() {
         files: grouped[p.key] || [],
       })));
 
+      // The background poller may already have fresh data; fetch only what it lacks
+      const cached = await invoke<QuotaSnapshot[]>('get_cached_quotas').catch(() => [] as QuotaSnapshot[]);
+      cached.forEach(applySnapshot);
+      if (cached.length > 0) loadForecasts();
+      const cachedKeys = new Set(cached.map(c => `${c.provider}:${c.auth_index}`));
+
       files.forEach((file) => {
-        if (file?.id) {
+        if (file?.id && !cachedKeys.has(`${getProviderType(file)}:${authIndexOf(file)}`)) {
           setTimeout(() => fetchQuotaForFile(file.id, file), 0);
         }
       });
@@ -223,6 +229,16 @@ export function useQuotaPresenter() {
     loadAuthFiles();
   }, [loadAuthFiles]);
 
+  useEffect(() => {
+    const unlisten = listen<QuotaSnapshot>('quota://updated', ({ payload }) => {
+      applySnapshot(payload);
+      if (!payload.error) loadForecasts(payload.auth_index);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [applySnapshot, loadForecasts]);
+
   const filterItems: ProviderFilterItem[] = useMemo(() => {
     return sections
       .filter(s => s.files.length > 0)
@@ -0,0 +1,159 @@
+/**
+ * Outbound network settings: proxy, custom CA, timeouts and download mirrors
+ */
+
+import { useEffect, useState } from 'react';
+import { useTranslation } from 'react-i18next';
+import { Network, FolderOpen, Save, Loader2 } from 'lucide-react';
+import { toast } from 'sonner';
+import { useNetworkStore, type HttpSettings } from '@/features/settings/network.store';
+import { useCliProxyStore } from '@/features/settings/cliProxy.store';
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from '@/shared/components/ui/card';
+import { Button } from '@/shared/components/ui/button';
+import { Input } from '@/shared/components/ui/input';
+import { Label } from '@/shared/components/ui/label';
+
+const emptyToNull = (value: string | null) => (value && value.trim() ? value.trim() : null);
+
+export function NetworkSettingsCard() {
+  const { t } = useTranslation();
+  const { settings, saving, fetchSettings, saveSettings } = useNetworkStore();
+  const { downloadMirrors, setDownloadMirrors } = useCliProxyStore();
+  const [form, setForm] = useState<HttpSettings | null>(settings);
+  const [mirrorsText, setMirrorsText] = useState(downloadMirrors.join('\n'));
+
+  useEffect(() => {
+    fetchSettings().catch((err) => console.error('Failed to load network settings:', err));
+  }, [fetchSettings]);
+
+  useEffect(() => {
+    setForm(settings);
+  }, [settings]);
+
+  if (!form) return null;
+
+  const update = (patch: Partial<HttpSettings>) => setForm({ ...form, ...patch });
+
+  const browseForCaBundle = async () => {
+    const { open } = await import('@tauri-apps/plugin-dialog');
+    const file = await open({
+      multiple: false,
+      filters: [
+        { name: 'Certificates', extensions: ['pem', 'crt', 'cer'] },
+        { name: 'All files', extensions: ['*'] },
+      ],
+    });
+    if (file && typeof file === 'string') update({ ca_bundle_path: file });
+  };
+
+  const handleSave = async () => {
+    try {
+      await saveSettings({
+        ...form,
+        proxy_url: emptyToNull(form.proxy_url),
+        no_proxy: emptyToNull(form.no_proxy),
+        ca_bundle_path: emptyToNull(form.ca_bundle_path),
+        user_agent: emptyToNull(form.user_agent),
+      });
+      setDownloadMirrors(mirrorsText.split('\n').map((line) => line.trim()).filter(Boolean));
+      toast.success(t('network.saved'));
+    } catch (err: any) {
+      toast.error(`${t('network.error')}: ${err?.message || err || 'Unknown error'}`);
+    }
+  };
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="flex items-center gap-2">
+          <Network className="h-5 w-5" />
+          {t('network.title')}
+        </CardTitle>
+        <CardDescription>{t('network.description')}</CardDescription>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <div className="space-y-2">
+          <Label>{t('network.proxyUrl')}</Label>
+          <Input
+            value={form.proxy_url ?? ''}
+            placeholder="http://proxy.example.com:8080"
+            onChange={(e) => update({ proxy_url: e.target.value })}
+          />
+        </div>
+
+        <div className="space-y-2">
+          <Label>{t('network.noProxy')}</Label>
+          <Input
+            value={form.no_proxy ?? ''}
+            placeholder="intranet.example.com,10.0.0.0/8"
+            onChange={(e) => update({ no_proxy: e.target.value })}
+          />
+        </div>
+
+        <div className="space-y-2">
+          <Label>{t('network.caBundle')}</Label>
+          <div className="flex items-center gap-2">
+            <Input
+              value={form.ca_bundle_path ?? ''}
+              placeholder="/etc/ssl/corp-ca.pem"
+              onChange={(e) => update({ ca_bundle_path: e.target.value })}
+            />
+            <Button variant="outline" size="sm" onClick={browseForCaBundle}>
+              <FolderOpen className="mr-2 h-4 w-4" />
+              {t('cliProxy.browse')}
+            </Button>
+          </div>
+        </div>
+
+        <div className="grid grid-cols-2 gap-4">
+          <div className="space-y-2">
+            <Label>{t('network.connectTimeout')}</Label>
+            <Input
+              type="number"
+              min={1}
+              value={form.connect_timeout_secs}
+              onChange={(e) => update({ connect_timeout_secs: Number(e.target.value) })}
+            />
+          </div>
+          <div className="space-y-2">
+            <Label>{t('network.readTimeout')}</Label>
+            <Input
+              type="number"
+              min={1}
+              value={form.read_timeout_secs}
+              onChange={(e) => update({ read_timeout_secs: Number(e.target.value) })}
+            />
+          </div>
+        </div>
+
+        <div className="space-y-2">
+          <Label>{t('network.userAgent')}</Label>
+          <Input
+            value={form.user_agent ?? ''}
+            placeholder="ZeroLimit"
+            onChange={(e) => update({ user_agent: e.target.value })}
+          />
+        </div>
+
+        <div className="space-y-2">
+          <Label>{t('network.mirrors')}</Label>
+          <textarea
+            value={mirrorsText}
+            rows={3}
+            placeholder={'https://ghproxy.example.com/\nhttps://artifactory.example.com/api/github/{path}'}
+            onChange={(e) => setMirrorsText(e.target.value)}
+            className="w-full rounded-md border border-input bg-transparent px-3 py-2 text-sm font-mono shadow-xs outline-none placeholder:text-muted-foreground focus-visible:border-ring focus-visible:ring-ring/50 focus-visible:ring-[3px]"
+          />
+          <p className="text-xs text-muted-foreground">{t('network.mirrorsDesc')}</p>
+        </div>
+
+        <div className="flex justify-end">
+          <Button size="sm" onClick={handleSave} disabled={saving}>
+            {saving ? <Loader2 className="mr-2 h-4 w-4 animate-spin" /> : <Save className="mr-2 h-4 w-4" />}
+            {t('network.save')}
+          </Button>
+        </div>
+      </CardContent>
+    </Card>
+  );
+}
@@ -0,0 +1,234 @@
+/**
+ * Desktop notifications: usage thresholds per provider or model, resets, expired tokens and quiet hours
+ */
+
+import { useEffect, useState } from 'react';
+import { useTranslation } from 'react-i18next';
+import { Bell, Plus, Trash2, Save, Loader2 } from 'lucide-react';
+import { toast } from 'sonner';
+import { useNotificationsStore, type AlertSettings, type ThresholdRule } from '@/features/settings/notifications.store';
+import type { QuotaProviderId } from '@/types';
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from '@/shared/components/ui/card';
+import { Button } from '@/shared/components/ui/button';
+import { Input } from '@/shared/components/ui/input';
+import { Label } from '@/shared/components/ui/label';
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from '@/shared/components/ui/select';
+
+const PROVIDERS: { key: QuotaProviderId; name: string }[] = [
+  { key: 'antigravity', name: 'Antigravity' },
+  { key: 'codex', name: 'Codex' },
+  { key: 'gemini-cli', name: 'Gemini CLI' },
+  { key: 'kiro', name: 'Kiro' },
+  { key: 'copilot', name: 'GitHub Copilot' },
+  { key: 'anthropic', name: 'Claude' },
+];
+
+const parseThresholds = (text: string) =>
+  text
+    .split(',')
+    .map((part) => Number(part.trim()))
+    .filter((value) => Number.isInteger(value) && value > 0);
+
+/** `HH:MM:SS` from the backend to `HH:MM` for a time input, and back */
+const toInputTime = (time: string) => time.slice(0, 5);
+const fromInputTime = (time: string) => (time.length === 5 ? `${time}:00` : time);
+
+/** Comma separated percentages, parsed when the field loses focus */
+function ThresholdsInput({ value, onChange }: { value: number[]; onChange: (value: number[]) => void }) {
+  const [text, setText] = useState(value.join(', '));
+
+  useEffect(() => {
+    setText(value.join(', '));
+  }, [value]);
+
+  return (
+    <Input
+      value={text}
+      placeholder="75, 90, 100"
+      onChange={(e) => setText(e.target.value)}
+      onBlur={() => onChange(parseThresholds(text))}
+    />
+  );
+}
+
+function Switch({ checked, onChange }: { checked: boolean; onChange: (checked: boolean) => void }) {
+  return (
+    <button
+      type="button"
+      role="switch"
+      aria-checked={checked}
+      onClick={() => onChange(!checked)}
+      className={`relative inline-flex h-6 w-11 shrink-0 items-center rounded-full transition-colors ${
+        checked ? 'bg-primary' : 'bg-muted'
+      }`}
+    >
+      <span
+        className={`inline-block h-4 w-4 transform rounded-full bg-white transition-transform ${
+          checked ? 'translate-x-6' : 'translate-x-1'
+        }`}
+      />
+    </button>
+  );
+}
+
+export function NotificationsCard() {
+  const { t } = useTranslation();
+  const { settings, saving, fetchSettings, saveSettings } = useNotificationsStore();
+  const [form, setForm] = useState<AlertSettings | null>(settings);
+
+  useEffect(() => {
+    fetchSettings().catch((err) => console.error('Failed to load notification settings:', err));
+  }, [fetchSettings]);
+
+  useEffect(() => {
+    setForm(settings);
+  }, [settings]);
+
+  if (!form) return null;
+
+  const update = (patch: Partial<AlertSettings>) => setForm({ ...form, ...patch });
+
+  const updateRule = (index: number, patch: Partial<ThresholdRule>) =>
+    update({ rules: form.rules.map((rule, i) => (i === index ? { ...rule, ...patch } : rule)) });
+
+  const addRule = () =>
+    update({ rules: [...form.rules, { provider: 'anthropic', model: null, thresholds: form.thresholds }] });
+
+  const removeRule = (index: number) => update({ rules: form.rules.filter((_, i) => i !== index) });
+
+  const updateQuietHours = (patch: { start?: string; end?: string }) => {
+    const current = form.quiet_hours ?? { start: '22:00:00', end: '07:00:00' };
+    update({ quiet_hours: { ...current, ...patch } });
+  };
+
+  const handleSave = async () => {
+    try {
+      await saveSettings(form);
+      toast.success(t('notifications.saved'));
+    } catch (err: any) {
+      toast.error(`${t('notifications.error')}: ${err?.message || err || 'Unknown error'}`);
+    }
+  };
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="flex items-center gap-2">
+          <Bell className="h-5 w-5" />
+          {t('notifications.title')}
+        </CardTitle>
+        <CardDescription>{t('notifications.description')}</CardDescription>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <div className="flex items-center justify-between">
+          <div className="space-y-0.5">
+            <Label>{t('notifications.enabled')}</Label>
+            <p className="text-xs text-muted-foreground">{t('notifications.enabledDesc')}</p>
+          </div>
+          <Switch checked={form.enabled} onChange={(enabled) => update({ enabled })} />
+        </div>
+
+        <div className="space-y-2">
+          <Label>{t('notifications.thresholds')}</Label>
+          <ThresholdsInput value={form.thresholds} onChange={(thresholds) => update({ thresholds })} />
+          <p className="text-xs text-muted-foreground">{t('notifications.thresholdsDesc')}</p>
+        </div>
+
+        <div className="space-y-2">
+          <Label>{t('notifications.rules')}</Label>
+          {form.rules.map((rule, index) => (
+            <div key={index} className="grid grid-cols-[10rem_1fr_1fr_auto] gap-2">
+              <Select
+                value={rule.provider}
+                onValueChange={(value) => updateRule(index, { provider: value as QuotaProviderId })}
+              >
+                <SelectTrigger className="w-full">
+                  <SelectValue />
+                </SelectTrigger>
+                <SelectContent>
+                  {PROVIDERS.map(({ key, name }) => (
+                    <SelectItem key={key} value={key}>
+                      {name}
+                    </SelectItem>
+                  ))}
+                </SelectContent>
+              </Select>
+              <Input
+                value={rule.model ?? ''}
+                placeholder={t('notifications.allModels')}
+                onChange={(e) => updateRule(index, { model: e.target.value.trim() ? e.target.value : null })}
+              />
+              <ThresholdsInput value={rule.thresholds} onChange={(thresholds) => updateRule(index, { thresholds })} />
+              <Button size="icon" variant="ghost" onClick={() => removeRule(index)} title={t('notifications.removeRule')}>
+                <Trash2 className="h-4 w-4" />
+              </Button>
+            </div>
+          ))}
+          <Button size="sm" variant="outline" onClick={addRule}>
+            <Plus className="mr-2 h-4 w-4" />
+            {t('notifications.addRule')}
+          </Button>
+        </div>
+
+        <div className="flex items-center justify-between">
+          <div className="space-y-0.5">
+            <Label>{t('notifications.reset')}</Label>
+            <p className="text-xs text-muted-foreground">{t('notifications.resetDesc')}</p>
+          </div>
+          <Switch checked={form.notify_reset} onChange={(notify_reset) => update({ notify_reset })} />
+        </div>
+
+        <div className="flex items-center justify-between">
+          <div className="space-y-0.5">
+            <Label>{t('notifications.tokenExpired')}</Label>
+            <p className="text-xs text-muted-foreground">{t('notifications.tokenExpiredDesc')}</p>
+          </div>
+          <Switch
+            checked={form.notify_token_expired}
+            onChange={(notify_token_expired) => update({ notify_token_expired })}
+          />
+        </div>
+
+        <div className="space-y-2">
+          <div className="flex items-center justify-between">
+            <div className="space-y-0.5">
+              <Label>{t('notifications.quietHours')}</Label>
+              <p className="text-xs text-muted-foreground">{t('notifications.quietHoursDesc')}</p>
+            </div>
+            <Switch
+              checked={form.quiet_hours !== null}
+              onChange={(checked) => (checked ? updateQuietHours({}) : update({ quiet_hours: null }))}
+            />
+          </div>
+          {form.quiet_hours && (
+            <div className="grid grid-cols-2 gap-4">
+              <div className="space-y-1">
+                <span className="text-xs text-muted-foreground">{t('notifications.quietFrom')}</span>
+                <Input
+                  type="time"
+                  value={toInputTime(form.quiet_hours.start)}
+                  onChange={(e) => e.target.value && updateQuietHours({ start: fromInputTime(e.target.value) })}
+                />
+              </div>
+              <div className="space-y-1">
+                <span className="text-xs text-muted-foreground">{t('notifications.quietTo')}</span>
+                <Input
+                  type="time"
+                  value={toInputTime(form.quiet_hours.end)}
+                  onChange={(e) => e.target.value && updateQuietHours({ end: fromInputTime(e.target.value) })}
+                />
+              </div>
+            </div>
+          )}
+        </div>
+
+        <div className="flex justify-end">
+          <Button size="sm" onClick={handleSave} disabled={saving}>
+            {saving ? <Loader2 className="mr-2 h-4 w-4 animate-spin" /> : <Save className="mr-2 h-4 w-4" />}
+            {t('network.save')}
+          </Button>
+        </div>
+      </CardContent>
+    </Card>
+  );
+}
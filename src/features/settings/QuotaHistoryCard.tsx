@@ -0,0 +1,120 @@
+/**
+ * Local quota history: retention and compaction of stored snapshots
+ */
+
+import { useEffect, useState } from 'react';
+import { useTranslation } from 'react-i18next';
+import { History, Save, Loader2 } from 'lucide-react';
+import { toast } from 'sonner';
+import { useQuotaHistoryStore, type QuotaHistorySettings } from '@/features/settings/quotaHistory.store';
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from '@/shared/components/ui/card';
+import { Button } from '@/shared/components/ui/button';
+import { Input } from '@/shared/components/ui/input';
+import { Label } from '@/shared/components/ui/label';
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from '@/shared/components/ui/select';
+
+export function QuotaHistoryCard() {
+  const { t } = useTranslation();
+  const { settings, saving, fetchSettings, saveSettings } = useQuotaHistoryStore();
+  const [form, setForm] = useState<QuotaHistorySettings | null>(settings);
+
+  useEffect(() => {
+    fetchSettings().catch((err) => console.error('Failed to load quota history settings:', err));
+  }, [fetchSettings]);
+
+  useEffect(() => {
+    setForm(settings);
+  }, [settings]);
+
+  if (!form) return null;
+
+  const update = (patch: Partial<QuotaHistorySettings>) => setForm({ ...form, ...patch });
+
+  const handleSave = async () => {
+    try {
+      await saveSettings(form);
+      toast.success(t('quotaHistory.saved'));
+    } catch (err: any) {
+      toast.error(`${t('quotaHistory.error')}: ${err?.message || err || 'Unknown error'}`);
+    }
+  };
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="flex items-center gap-2">
+          <History className="h-5 w-5" />
+          {t('quotaHistory.title')}
+        </CardTitle>
+        <CardDescription>{t('quotaHistory.description')}</CardDescription>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <div className="flex items-center justify-between">
+          <div className="space-y-0.5">
+            <Label>{t('quotaHistory.enabled')}</Label>
+            <p className="text-xs text-muted-foreground">{t('quotaHistory.enabledDesc')}</p>
+          </div>
+          <button
+            type="button"
+            role="switch"
+            aria-checked={form.enabled}
+            onClick={() => update({ enabled: !form.enabled })}
+            className={`relative inline-flex h-6 w-11 items-center rounded-full transition-colors ${
+              form.enabled ? 'bg-primary' : 'bg-muted'
+            }`}
+          >
+            <span
+              className={`inline-block h-4 w-4 transform rounded-full bg-white transition-transform ${
+                form.enabled ? 'translate-x-6' : 'translate-x-1'
+              }`}
+            />
+          </button>
+        </div>
+
+        <div className="grid grid-cols-3 gap-4">
+          <div className="space-y-2">
+            <Label>{t('quotaHistory.retention')}</Label>
+            <Input
+              type="number"
+              min={0}
+              value={form.retention_days}
+              onChange={(e) => update({ retention_days: Number(e.target.value) })}
+            />
+          </div>
+          <div className="space-y-2">
+            <Label>{t('quotaHistory.compactAfter')}</Label>
+            <Input
+              type="number"
+              min={0}
+              value={form.compact_after_days}
+              onChange={(e) => update({ compact_after_days: Number(e.target.value) })}
+            />
+          </div>
+          <div className="space-y-2">
+            <Label>{t('quotaHistory.compactBucket')}</Label>
+            <Select
+              value={form.compact_bucket}
+              onValueChange={(value) => update({ compact_bucket: value as QuotaHistorySettings['compact_bucket'] })}
+            >
+              <SelectTrigger className="w-full">
+                <SelectValue />
+              </SelectTrigger>
+              <SelectContent>
+                <SelectItem value="hour">{t('quotaHistory.hour')}</SelectItem>
+                <SelectItem value="day">{t('quotaHistory.day')}</SelectItem>
+              </SelectContent>
+            </Select>
+          </div>
+        </div>
+        <p className="text-xs text-muted-foreground">{t('quotaHistory.retentionDesc')}</p>
+
+        <div className="flex justify-end">
+          <Button size="sm" onClick={handleSave} disabled={saving}>
+            {saving ? <Loader2 className="mr-2 h-4 w-4 animate-spin" /> : <Save className="mr-2 h-4 w-4" />}
+            {t('network.save')}
+          </Button>
+        </div>
+      </CardContent>
+    </Card>
+  );
+}
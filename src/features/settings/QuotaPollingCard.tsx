@@ -0,0 +1,163 @@
+/**
+ * Background quota refresh: interval, per-provider overrides and error pausing
+ */
+
+import { useEffect, useState } from 'react';
+import { useTranslation } from 'react-i18next';
+import { Timer, Save, Loader2 } from 'lucide-react';
+import { toast } from 'sonner';
+import { useQuotaPollingStore, type QuotaPollSettings } from '@/features/settings/quotaPolling.store';
+import type { QuotaProviderId } from '@/types';
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from '@/shared/components/ui/card';
+import { Button } from '@/shared/components/ui/button';
+import { Input } from '@/shared/components/ui/input';
+import { Label } from '@/shared/components/ui/label';
+
+const PROVIDERS: { key: QuotaProviderId; name: string }[] = [
+  { key: 'antigravity', name: 'Antigravity' },
+  { key: 'codex', name: 'Codex' },
+  { key: 'gemini-cli', name: 'Gemini CLI' },
+  { key: 'kiro', name: 'Kiro' },
+  { key: 'copilot', name: 'GitHub Copilot' },
+  { key: 'anthropic', name: 'Claude' },
+];
+
+export function QuotaPollingCard() {
+  const { t } = useTranslation();
+  const { settings, saving, fetchSettings, saveSettings } = useQuotaPollingStore();
+  const [form, setForm] = useState<QuotaPollSettings | null>(settings);
+
+  useEffect(() => {
+    fetchSettings().catch((err) => console.error('Failed to load quota polling settings:', err));
+  }, [fetchSettings]);
+
+  useEffect(() => {
+    setForm(settings);
+  }, [settings]);
+
+  if (!form) return null;
+
+  const update = (patch: Partial<QuotaPollSettings>) => setForm({ ...form, ...patch });
+
+  const updateProviderInterval = (provider: QuotaProviderId, value: string) => {
+    const provider_intervals = { ...form.provider_intervals };
+    if (value.trim()) {
+      provider_intervals[provider] = Number(value);
+    } else {
+      delete provider_intervals[provider];
+    }
+    update({ provider_intervals });
+  };
+
+  const handleSave = async () => {
+    try {
+      await saveSettings(form);
+      toast.success(t('quotaPolling.saved'));
+    } catch (err: any) {
+      toast.error(`${t('quotaPolling.error')}: ${err?.message || err || 'Unknown error'}`);
+    }
+  };
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="flex items-center gap-2">
+          <Timer className="h-5 w-5" />
+          {t('quotaPolling.title')}
+        </CardTitle>
+        <CardDescription>{t('quotaPolling.description')}</CardDescription>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <div className="flex items-center justify-between">
+          <div className="space-y-0.5">
+            <Label>{t('quotaPolling.enabled')}</Label>
+            <p className="text-xs text-muted-foreground">{t('quotaPolling.enabledDesc')}</p>
+          </div>
+          <button
+            type="button"
+            role="switch"
+            aria-checked={form.enabled}
+            onClick={() => update({ enabled: !form.enabled })}
+            className={`relative inline-flex h-6 w-11 items-center rounded-full transition-colors ${
+              form.enabled ? 'bg-primary' : 'bg-muted'
+            }`}
+          >
+            <span
+              className={`inline-block h-4 w-4 transform rounded-full bg-white transition-transform ${
+                form.enabled ? 'translate-x-6' : 'translate-x-1'
+              }`}
+            />
+          </button>
+        </div>
+
+        <div className="grid grid-cols-2 gap-4">
+          <div className="space-y-2">
+            <Label>{t('quotaPolling.interval')}</Label>
+            <Input
+              type="number"
+              min={60}
+              value={form.interval_secs}
+              onChange={(e) => update({ interval_secs: Number(e.target.value) })}
+            />
+          </div>
+          <div className="space-y-2">
+            <Label>{t('quotaPolling.jitter')}</Label>
+            <Input
+              type="number"
+              min={0}
+              max={50}
+              value={form.jitter_percent}
+              onChange={(e) => update({ jitter_percent: Number(e.target.value) })}
+            />
+          </div>
+        </div>
+
+        <div className="space-y-2">
+          <Label>{t('quotaPolling.providerIntervals')}</Label>
+          <div className="grid grid-cols-3 gap-2">
+            {PROVIDERS.map(({ key, name }) => (
+              <div key={key} className="space-y-1">
+                <span className="text-xs text-muted-foreground">{name}</span>
+                <Input
+                  type="number"
+                  min={60}
+                  value={form.provider_intervals[key] ?? ''}
+                  placeholder={String(form.interval_secs)}
+                  onChange={(e) => updateProviderInterval(key, e.target.value)}
+                />
+              </div>
+            ))}
+          </div>
+        </div>
+
+        <div className="grid grid-cols-2 gap-4">
+          <div className="space-y-2">
+            <Label>{t('quotaPolling.maxErrors')}</Label>
+            <Input
+              type="number"
+              min={1}
+              value={form.max_errors}
+              onChange={(e) => update({ max_errors: Number(e.target.value) })}
+            />
+          </div>
+          <div className="space-y-2">
+            <Label>{t('quotaPolling.pause')}</Label>
+            <Input
+              type="number"
+              min={60}
+              value={form.pause_secs}
+              onChange={(e) => update({ pause_secs: Number(e.target.value) })}
+            />
+          </div>
+        </div>
+
+        <div className="flex justify-end">
+          <Button size="sm" onClick={handleSave} disabled={saving}>
+            {saving ? <Loader2 className="mr-2 h-4 w-4 animate-spin" /> : <Save className="mr-2 h-4 w-4" />}
+            {t('network.save')}
+          </Button>
+        </div>
+      </CardContent>
+    </Card>
+  );
+}
@@ -10,20 +10,42 @@ import { useLanguageStore } from '@/features/settings/language.store';
 import { useAuthStore } from '@/features/auth/auth.store';
 import { useCliProxyStore } from '@/features/settings/cliProxy.store';
 import { useConfigStore } from '@/features/settings/config.store';
+import { NetworkSettingsCard } from '@/features/settings/NetworkSettingsCard';
+import { QuotaPollingCard } from '@/features/settings/QuotaPollingCard';
+import { QuotaHistoryCard } from '@/features/settings/QuotaHistoryCard';
+import { NotificationsCard } from '@/features/settings/NotificationsCard';
 import { Card, CardContent, CardDescription, CardHeader, CardTitle } from '@/shared/components/ui/card';
 import { Button } from '@/shared/components/ui/button';
 import { Label } from '@/shared/components/ui/label';
-import { Sun, Moon, Monitor, LogOut, Globe, Server, FolderOpen, Play, Square, CheckCircle2, BarChart3, Loader2, FileText, RefreshCw, Download, ArrowLeftRight } from 'lucide-react';
+import { Sun, Moon, Monitor, LogOut, Globe, Server, FolderOpen, Play, Square, CheckCircle2, BarChart3, Loader2, FileText, RefreshCw, Download, ArrowLeftRight, Upload } from 'lucide-react';
 import { toast } from 'sonner';
 
+/** Tell the user what an install changed in config.yaml, if anything */
+function showConfigMergeToast() {
+  const merge = useCliProxyStore.getState().lastConfigMerge;
+  if (!merge || merge.created) return;
+  const lines = [
+    merge.added.length && `Added: ${merge.added.join(', ')}`,
+    merge.changed_defaults.length && `New defaults upstream (kept yours): ${merge.changed_defaults.join(', ')}`,
+    merge.kept.length && `Kept your values: ${merge.kept.join(', ')}`,
+    merge.obsolete.length && `No longer used: ${merge.obsolete.join(', ')}`,
+    merge.manual.length && `Needs manual update: ${merge.manual.join(', ')}`,
+  ].filter(Boolean);
+  if (!lines.length) return;
+  toast.info('config.yaml merged with the new defaults', {
+    description: [...lines, merge.backup_path && `Backup: ${merge.backup_path}`].filter(Boolean).join('\n'),
+    duration: 10000,
+  });
+}
+
 export function SettingsPage() {
   const { t } = useTranslation();
   const { theme, setTheme } = useThemeStore();
   const { language, setLanguage } = useLanguageStore();
   const { logout, connectionStatus } = useAuthStore();
   const {
-    exePath, autoStart, runInBackground, isServerRunning,
-    setAutoStart, setRunInBackground, browseForExe, startServer, stopServer,
+    exePath, autoStart, runInBackground, autoMovePort, isServerRunning,
+    setAutoStart, setRunInBackground, setAutoMovePort, browseForExe, installFromFile, startServer, stopServer,
     cliProxyLatestVersion, latestRemoteVersion, updateAvailable,
     isCheckingUpdate, isUpdating, isSwitchingVersion, checkForProxyUpdate, updateProxy, switchVersion,
     currentInstalledVersion, serverBuildDate,
@@ -112,6 +134,24 @@ export function SettingsPage() {
                 <FolderOpen className="mr-2 h-4 w-4" />
                 {t('cliProxy.browse')}
               </Button>
+              <Button
+                variant="outline"
+                size="sm"
+                onClick={async () => {
+                  try {
+                    if (await installFromFile()) {
+                      toast.success('CLI Proxy installed from file!');
+                      showConfigMergeToast();
+                    }
+                  } catch (err: any) {
+                    toast.error(`Install failed: ${err?.message || err || 'Unknown error'}`);
+                  }
+                }}
+                disabled={isUpdating || isSwitchingVersion}
+              >
+                <Upload className="mr-2 h-4 w-4" />
+                {t('cliProxy.installFromFile')}
+              </Button>
             </div>
           </div>
 
@@ -165,6 +205,31 @@ export function SettingsPage() {
             </button>
           </div>
 
+          {/* Port Conflict Toggle */}
+          <div className="flex items-center justify-between">
+            <div className="space-y-0.5">
+              <Label>{t('cliProxy.autoMovePort')}</Label>
+              <p className="text-xs text-muted-foreground">
+                {t('cliProxy.autoMovePortDesc')}
+              </p>
+            </div>
+            <button
+              type="button"
+              role="switch"
+              aria-checked={autoMovePort}
+              onClick={() => setAutoMovePort(!autoMovePort)}
+              className={`relative inline-flex h-6 w-11 items-center rounded-full transition-colors ${
+                autoMovePort ? 'bg-primary' : 'bg-muted'
+              }`}
+            >
+              <span
+                className={`inline-block h-4 w-4 transform rounded-full bg-white transition-transform ${
+                  autoMovePort ? 'translate-x-6' : 'translate-x-1'
+                }`}
+              />
+            </button>
+          </div>
+
           {/* Server Status & Controls */}
           <div className="flex items-center justify-between pt-2 border-t">
             <div className="flex items-center gap-2">
@@ -185,7 +250,7 @@ export function SettingsPage() {
                   {t('cliProxy.stop')}
                 </Button>
               ) : (
-                <Button variant="default" size="sm" onClick={startServer}>
+                <Button variant="default" size="sm" onClick={() => startServer()}>
                   <Play className="mr-2 h-4 w-4" />
                   {t('cliProxy.start')}
                 </Button>
@@ -219,6 +284,7 @@ export function SettingsPage() {
                           toast.info('Updating CLI Proxy...');
                           await updateProxy();
                           toast.success('CLI Proxy updated and restarted!');
+                          showConfigMergeToast();
                         } catch (err: any) {
                           toast.error(`Update failed: ${err?.message || 'Unknown error'}`);
                         }
@@ -279,6 +345,7 @@ export function SettingsPage() {
                       toast.info(`Switching to ${targetLabel} version...`);
                       await switchVersion();
                       toast.success(`Switched to ${targetLabel} version!`);
+                      showConfigMergeToast();
                     } catch (err: any) {
                       toast.error(`Switch failed: ${err?.message || 'Unknown error'}`);
                     }
@@ -300,6 +367,18 @@ export function SettingsPage() {
         </CardContent>
       </Card>
 
+      {/* Network Settings */}
+      <NetworkSettingsCard />
+
+      {/* Background Quota Refresh */}
+      <QuotaPollingCard />
+
+      {/* Quota History */}
+      <QuotaHistoryCard />
+
+      {/* Notifications */}
+      <NotificationsCard />
+
       {/* Usage Statistics Settings */}
       <Card>
         <CardHeader>
@@ -2,7 +2,42 @@ import { create } from 'zustand';
 import { persist } from 'zustand/middleware';
 import { open } from '@tauri-apps/plugin-dialog';
 import { invoke } from '@tauri-apps/api/core';
+import { toast } from 'sonner';
+import i18n from '@/i18n';
 import { STORAGE_KEY_CLI_PROXY } from '@/constants';
+import type { CommandError } from '@/types/api';
+
+/** Release picked by the backend for this OS and arch (`resolve_proxy_release`) */
+interface ResolvedRelease {
+  tag: string;
+  asset_name: string;
+  download_url: string;
+  checksums_url: string | null;
+}
+
+/** What an install did to the shared config.yaml (dotted key paths) */
+export interface ConfigMergeSummary {
+  created: boolean;
+  added: string[];
+  changed_defaults: string[];
+  kept: string[];
+  obsolete: string[];
+  manual: string[];
+  backup_path: string | null;
+}
+
+/** A build in the versioned install layout (`rollback_proxy`) */
+interface InstalledBuild {
+  tag: string;
+  exe_path: string;
+}
+
+/** Result of `download_and_extract_proxy` */
+interface InstallOutcome {
+  tag: string;
+  exe_path: string;
+  config: ConfigMergeSummary | null;
+}
 
 // Semantic version comparison (from Management Center)
 function parseVersionSegments(version?: string | null): number[] | null {
@@ -33,7 +68,11 @@ interface CliProxyState {
   isApiHealthy: boolean;
   autoStart: boolean;
   runInBackground: boolean;
+  /** Move the proxy to a free port without asking when its port is taken */
+  autoMovePort: boolean;
   serverPid: number | null;
+  /** API base of the proxy `startServer` last started or adopted */
+  serverApiBase: string | null;
   hasCompletedOnboarding: boolean;
   cliProxyMode: 'auto_download' | 'manual' | null;
   cliProxyVersion: 'standard' | 'plus' | null;
@@ -45,15 +84,21 @@ interface CliProxyState {
   isSwitchingVersion: boolean;
   updateAvailable: boolean;
   latestRemoteVersion: string | null;
+  lastConfigMerge: ConfigMergeSummary | null;
+  /** Mirror prefixes or `{url}`/`{path}` templates tried before GitHub */
+  downloadMirrors: string[];
   setExePath: (path: string | null) => void;
   setAutoStart: (autoStart: boolean) => void;
   setRunInBackground: (runInBackground: boolean) => void;
+  setAutoMovePort: (autoMovePort: boolean) => void;
   setHasCompletedOnboarding: (completed: boolean) => void;
   setCliProxyMode: (mode: 'auto_download' | 'manual' | null) => void;
   setCliProxyVersion: (version: 'standard' | 'plus' | null) => void;
   setCliProxyLatestVersion: (version: string | null) => void;
+  setDownloadMirrors: (mirrors: string[]) => void;
   browseForExe: () => Promise<string | null>;
-  startServer: () => Promise<boolean>;
+  installFromFile: () => Promise<boolean>;
+  startServer: (options?: { autoPort?: boolean }) => Promise<boolean>;
   stopServer: () => Promise<void>;
   checkServerStatus: () => Promise<boolean>;
   checkApiHealth: (apiBase?: string) => Promise<boolean>;
@@ -70,7 +115,9 @@ export const useCliProxyStore = create<CliProxyState>()(
       isApiHealthy: false,
       autoStart: false,
       runInBackground: false,
+      autoMovePort: false,
       serverPid: null,
+      serverApiBase: null,
       hasCompletedOnboarding: false,
       cliProxyMode: null,
       cliProxyVersion: null,
@@ -82,17 +129,22 @@ export const useCliProxyStore = create<CliProxyState>()(
       isSwitchingVersion: false,
       updateAvailable: false,
       latestRemoteVersion: null,
+      lastConfigMerge: null,
+      downloadMirrors: [],
 
       setExePath: (path) => set({ exePath: path }),
 
       setAutoStart: (autoStart) => set({ autoStart }),
 
+      setAutoMovePort: (autoMovePort) => set({ autoMovePort }),
+
       setRunInBackground: (runInBackground) => {
         set({ runInBackground });
         invoke('set_run_in_background', { enabled: runInBackground }).catch(console.error);
       },
 
       setHasCompletedOnboarding: (hasCompletedOnboarding) => set({ hasCompletedOnboarding }),
+      setDownloadMirrors: (downloadMirrors) => set({ downloadMirrors }),
       setCliProxyMode: (cliProxyMode) => set({ cliProxyMode }),
       setCliProxyVersion: (cliProxyVersion) => set({ cliProxyVersion }),
       setCliProxyLatestVersion: (cliProxyLatestVersion) => set({ cliProxyLatestVersion }),
@@ -118,22 +170,79 @@ export const useCliProxyStore = create<CliProxyState>()(
         }
       },
 
-      startServer: async () => {
+      installFromFile: async () => {
+        const file = await open({
+          multiple: false,
+          filters: [
+            { name: 'Proxy release', extensions: ['zip', 'gz', 'tgz', 'exe'] },
+            { name: 'All files', extensions: ['*'] },
+          ],
+        });
+        if (!file || typeof file !== 'string') return false;
+
         const { exePath, isServerRunning } = get();
+        set({ isUpdating: true, lastConfigMerge: null });
+        try {
+          if (isServerRunning) await get().stopServer();
+          const sep = exePath?.includes('\\') ? '\\' : '/';
+          const lastSep = exePath ? exePath.lastIndexOf(sep) : -1;
+          const targetDir = exePath && lastSep > 0 ? exePath.substring(0, lastSep) : null;
+
+          const outcome = await invoke<InstallOutcome>('install_proxy_from_file', { path: file, targetDir });
+          set({
+            exePath: outcome.exe_path,
+            lastConfigMerge: outcome.config,
+            cliProxyVersion: outcome.exe_path.toLowerCase().includes('plus') ? 'plus' : 'standard',
+          });
+          if (isServerRunning) await get().startServer();
+          return true;
+        } finally {
+          set({ isUpdating: false });
+        }
+      },
+
+      startServer: async (options) => {
+        const { exePath, isServerRunning, autoMovePort } = get();
 
         if (!exePath || isServerRunning) {
           return false;
         }
 
         try {
-          const pid = await invoke<number>('start_cli_proxy', { exePath });
+          const info = await invoke<{
+            pid: number;
+            port: number;
+            api_base: string;
+            port_changed: boolean;
+          }>('start_cli_proxy', { exePath, autoPort: options?.autoPort ?? autoMovePort });
           set({
             isServerRunning: true,
-            serverPid: pid
+            serverPid: info.pid,
+            serverApiBase: info.api_base,
           });
+
+          // The configured port was taken, so the backend moved the proxy to a free one
+          if (info.port_changed) {
+            const { useAuthStore } = await import('@/features/auth/auth.store');
+            const { apiClient } = await import('@/services/api/client');
+            const { managementKey } = useAuthStore.getState();
+            useAuthStore.setState({ apiBase: info.api_base });
+            apiClient.setConfig({ apiBase: info.api_base, managementKey });
+          }
           return true;
         } catch (err) {
           console.error('Failed to start server:', err);
+          const error = err as CommandError;
+          // Only move to another port when the user agrees to it
+          if (error?.kind === 'port_in_use') {
+            toast.error(i18n.t('cliProxy.portInUse', { port: error.port, holder: error.holder }), {
+              action: {
+                label: i18n.t('cliProxy.useFreePort'),
+                onClick: () => get().startServer({ autoPort: true }),
+              },
+              duration: 10000,
+            });
+          }
           return false;
         }
       },
@@ -235,7 +344,7 @@ export const useCliProxyStore = create<CliProxyState>()(
       updateProxy: async () => {
         const { cliProxyVersion, latestRemoteVersion, isServerRunning } = get();
         if (!latestRemoteVersion) throw new Error('No update version info available. Check for updates first.');
-        set({ isUpdating: true });
+        set({ isUpdating: true, lastConfigMerge: null });
         try {
           // 1. Stop server if running
           if (isServerRunning) {
@@ -246,25 +355,8 @@ export const useCliProxyStore = create<CliProxyState>()(
           // 2. Auto-detect version from exe filename (cli-proxy-api-plus.exe = plus)
           const version = (get().exePath || '').toLowerCase().includes('plus') ? 'plus' : (cliProxyVersion || 'standard');
           set({ cliProxyVersion: version }); // Persist detected version
-          const repo = version === 'plus' ? 'CLIProxyAPIPlus' : 'CLIProxyAPI';
-          const res = await fetch(`https://api.github.com/repos/router-for-me/${repo}/releases/latest`, {
-            headers: { 'User-Agent': 'CLIProxyAPI' },
-          });
-          if (!res.ok) throw new Error('Failed to fetch release from GitHub');
-          const data = await res.json();
-
-          // 3. Find the right asset
-          const { type } = await import('@tauri-apps/plugin-os');
-          const osType = await type();
-          let osName = 'windows';
-          if (osType === 'macos') osName = 'darwin';
-          if (osType === 'linux') osName = 'linux';
-          const searchString = `${osName}_`;
-          const asset = data.assets?.find((a: any) =>
-            a.name.toLowerCase().includes(searchString) &&
-            (a.name.endsWith('.zip') || a.name.endsWith('.tar.gz') || a.name.endsWith('.tgz'))
-          );
-          if (!asset?.browser_download_url) throw new Error('No compatible asset found for your OS');
+          // 3. Resolve the release and the asset for this OS and arch
+          const release = await invoke<ResolvedRelease>('resolve_proxy_release', { edition: version });
 
           // 4. Download and extract to the same directory as the current exe
           const currentExePath = get().exePath;
@@ -277,21 +369,43 @@ export const useCliProxyStore = create<CliProxyState>()(
               targetDir = currentExePath.substring(0, lastSep);
             }
           }
-          const exePath = await invoke<string>('download_and_extract_proxy', {
-            url: asset.browser_download_url,
+          const outcome = await invoke<InstallOutcome>('download_and_extract_proxy', {
+            url: release.download_url,
             targetDir,
+            tag: release.tag,
+            verify: release.checksums_url ? { checksums_url: release.checksums_url } : null,
+            mirrors: get().downloadMirrors,
           });
-          if (exePath) {
+          if (outcome.exe_path) {
             set({
-              exePath,
+              exePath: outcome.exe_path,
+              lastConfigMerge: outcome.config,
               cliProxyLatestVersion: latestRemoteVersion,
               cliProxyVersion: version,
               updateAvailable: false,
             });
           }
 
-          // 5. Restart server
+          // 5. Restart server, rolling back to the previous version if the new one never comes up
           await get().startServer();
+          const { useAuthStore } = await import('@/features/auth/auth.store');
+          const readiness = await invoke<{ status: string }>('wait_for_proxy_ready', {
+            apiBase: useAuthStore.getState().apiBase || 'http://localhost:8317',
+          });
+          if (readiness.status !== 'ready') {
+            await get().stopServer();
+            const previous = await invoke<InstalledBuild>('rollback_proxy', { targetDir });
+            // Describe the build that runs again, so the update stays on offer
+            set({
+              exePath: previous.exe_path,
+              cliProxyVersion: previous.exe_path.toLowerCase().includes('plus') ? 'plus' : 'standard',
+              cliProxyLatestVersion: previous.tag,
+              currentInstalledVersion: previous.tag.replace(/^v/i, ''),
+              updateAvailable: (compareVersions(latestRemoteVersion, previous.tag) ?? 0) > 0,
+            });
+            await get().startServer();
+            throw new Error(`Updated proxy failed its health check (${readiness.status}); rolled back to ${previous.tag}`);
+          }
           return true;
         } catch (err) {
           console.error('Update failed:', err);
@@ -302,33 +416,32 @@ export const useCliProxyStore = create<CliProxyState>()(
       },
 
       switchVersion: async () => {
-        const { exePath, isServerRunning } = get();
+        const { exePath } = get();
         if (!exePath) throw new Error('No executable path configured.');
 
-        set({ isSwitchingVersion: true });
+        set({ isSwitchingVersion: true, lastConfigMerge: null });
         try {
           const currentIsPlus = exePath.toLowerCase().includes('plus');
           const targetVersion = currentIsPlus ? 'standard' : 'plus';
 
-          if (isServerRunning) {
-            await get().stopServer();
-          }
+          const sep = exePath.includes('\\') ? '\\' : '/';
+          const lastSep = exePath.lastIndexOf(sep);
+          const targetDir = lastSep > 0 ? exePath.substring(0, lastSep) : null;
 
-          // Force-kill any remaining CLI proxy process by exe name
-          const exeName = exePath.split(/[\\/]/).pop() || '';
-          if (exeName) {
-            try {
-              const { Command } = await import('@tauri-apps/plugin-shell');
-              await Command.create('taskkill', ['/F', '/T', '/IM', exeName]).execute();
-            } catch { /* ignore if already dead */ }
-          }
-          await new Promise(r => setTimeout(r, 2000));
+          // Always stop: the backend also reaps any orphan it started earlier
+          await get().stopServer();
           const existingExe = await invoke<string | null>('find_alternate_proxy_exe', {
             currentExePath: exePath,
             targetVersion,
           });
 
           if (existingExe) {
+            // Builds of the versioned layout also become the current version
+            const installed = await invoke<{ id: string; exe_path: string }[]>('list_installed_proxies', { targetDir });
+            const build = installed.find((b) => b.exe_path === existingExe);
+            if (build) {
+              await invoke('activate_installed_proxy', { id: build.id, targetDir });
+            }
             set({
               exePath: existingExe,
               cliProxyVersion: targetVersion === 'plus' ? 'plus' : 'standard',
@@ -337,37 +450,20 @@ export const useCliProxyStore = create<CliProxyState>()(
             return true;
           }
 
-          const repo = targetVersion === 'plus' ? 'CLIProxyAPIPlus' : 'CLIProxyAPI';
-          const res = await fetch(`https://api.github.com/repos/router-for-me/${repo}/releases/latest`, {
-            headers: { 'User-Agent': 'CLIProxyAPI' },
-          });
-          if (!res.ok) throw new Error('Failed to fetch release from GitHub');
-          const data = await res.json();
-
-          const { type } = await import('@tauri-apps/plugin-os');
-          const osType = await type();
-          let osName = 'windows';
-          if (osType === 'macos') osName = 'darwin';
-          if (osType === 'linux') osName = 'linux';
-          const searchString = `${osName}_`;
-          const asset = data.assets?.find((a: any) =>
-            a.name.toLowerCase().includes(searchString) &&
-            (a.name.endsWith('.zip') || a.name.endsWith('.tar.gz') || a.name.endsWith('.tgz'))
-          );
-          if (!asset?.browser_download_url) throw new Error('No compatible asset found for your OS');
-
-          const sep = exePath.includes('\\') ? '\\' : '/';
-          const lastSep = exePath.lastIndexOf(sep);
-          const targetDir = lastSep > 0 ? exePath.substring(0, lastSep) : null;
+          const release = await invoke<ResolvedRelease>('resolve_proxy_release', { edition: targetVersion });
 
-          const newExePath = await invoke<string>('download_and_extract_proxy', {
-            url: asset.browser_download_url,
+          const outcome = await invoke<InstallOutcome>('download_and_extract_proxy', {
+            url: release.download_url,
             targetDir,
+            tag: release.tag,
+            verify: release.checksums_url ? { checksums_url: release.checksums_url } : null,
+            mirrors: get().downloadMirrors,
           });
 
-          if (newExePath) {
+          if (outcome.exe_path) {
             set({
-              exePath: newExePath,
+              exePath: outcome.exe_path,
+              lastConfigMerge: outcome.config,
               cliProxyVersion: targetVersion === 'plus' ? 'plus' : 'standard',
             });
           }
@@ -388,6 +484,7 @@ export const useCliProxyStore = create<CliProxyState>()(
         exePath: state.exePath,
         autoStart: state.autoStart,
         runInBackground: state.runInBackground,
+        autoMovePort: state.autoMovePort,
         hasCompletedOnboarding: state.hasCompletedOnboarding,
         cliProxyMode: state.cliProxyMode,
         cliProxyVersion: state.cliProxyVersion,
@@ -395,6 +492,7 @@ export const useCliProxyStore = create<CliProxyState>()(
         currentInstalledVersion: state.currentInstalledVersion,
         serverBuildDate: state.serverBuildDate,
         latestRemoteVersion: state.latestRemoteVersion,
+        downloadMirrors: state.downloadMirrors,
       }),
     }
   )
@@ -0,0 +1,39 @@
+import { create } from 'zustand';
+import { invoke } from '@tauri-apps/api/core';
+
+/** Outbound HTTP settings used by downloads, update checks and health checks */
+export interface HttpSettings {
+  proxy_url: string | null;
+  no_proxy: string | null;
+  ca_bundle_path: string | null;
+  connect_timeout_secs: number;
+  read_timeout_secs: number;
+  user_agent: string | null;
+}
+
+interface NetworkState {
+  settings: HttpSettings | null;
+  saving: boolean;
+  fetchSettings: () => Promise<void>;
+  saveSettings: (settings: HttpSettings) => Promise<void>;
+}
+
+export const useNetworkStore = create<NetworkState>((set) => ({
+  settings: null,
+  saving: false,
+
+  fetchSettings: async () => {
+    const settings = await invoke<HttpSettings>('get_http_settings');
+    set({ settings });
+  },
+
+  saveSettings: async (settings) => {
+    set({ saving: true });
+    try {
+      await invoke('set_http_settings', { settings });
+      set({ settings });
+    } finally {
+      set({ saving: false });
+    }
+  },
+}));
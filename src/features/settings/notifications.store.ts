@@ -0,0 +1,48 @@
+import { create } from 'zustand';
+import { invoke } from '@tauri-apps/api/core';
+import type { QuotaProviderId } from '@/types';
+
+/** Thresholds of one provider, or of one of its models when `model` is set */
+export interface ThresholdRule {
+  provider: QuotaProviderId;
+  model: string | null;
+  thresholds: number[];
+}
+
+/** Native notification rules, checked by the backend against every fresh quota snapshot */
+export interface AlertSettings {
+  enabled: boolean;
+  thresholds: number[];
+  rules: ThresholdRule[];
+  notify_reset: boolean;
+  notify_token_expired: boolean;
+  /** Local times as `HH:MM:SS` */
+  quiet_hours: { start: string; end: string } | null;
+}
+
+interface NotificationsState {
+  settings: AlertSettings | null;
+  saving: boolean;
+  fetchSettings: () => Promise<void>;
+  saveSettings: (settings: AlertSettings) => Promise<void>;
+}
+
+export const useNotificationsStore = create<NotificationsState>((set) => ({
+  settings: null,
+  saving: false,
+
+  fetchSettings: async () => {
+    const settings = await invoke<AlertSettings>('get_alert_settings');
+    set({ settings });
+  },
+
+  saveSettings: async (settings) => {
+    set({ saving: true });
+    try {
+      await invoke('set_alert_settings', { settings });
+      set({ settings });
+    } finally {
+      set({ saving: false });
+    }
+  },
+}));
@@ -0,0 +1,54 @@
+import { create } from 'zustand';
+import { invoke } from '@tauri-apps/api/core';
+import type { QuotaHistoryBucket, QuotaHistoryPoint, QuotaProviderId } from '@/types';
+
+/** Retention and compaction of the local quota history database */
+export interface QuotaHistorySettings {
+  enabled: boolean;
+  retention_days: number;
+  compact_after_days: number;
+  compact_bucket: Exclude<QuotaHistoryBucket, 'raw'>;
+}
+
+interface QuotaHistoryState {
+  settings: QuotaHistorySettings | null;
+  saving: boolean;
+  fetchSettings: () => Promise<void>;
+  saveSettings: (settings: QuotaHistorySettings) => Promise<void>;
+}
+
+export const useQuotaHistoryStore = create<QuotaHistoryState>((set) => ({
+  settings: null,
+  saving: false,
+
+  fetchSettings: async () => {
+    const settings = await invoke<QuotaHistorySettings>('get_quota_history_settings');
+    set({ settings });
+  },
+
+  saveSettings: async (settings) => {
+    set({ saving: true });
+    try {
+      await invoke('set_quota_history_settings', { settings });
+      set({ settings });
+    } finally {
+      set({ saving: false });
+    }
+  },
+}));
+
+/** Quota of an account between `from` and `to` (default now), one point per model and bucket */
+export function queryQuotaHistory(
+  provider: QuotaProviderId,
+  account: string,
+  range: { from: Date; to?: Date },
+  options: { model?: string; bucket?: QuotaHistoryBucket } = {}
+): Promise<QuotaHistoryPoint[]> {
+  return invoke<QuotaHistoryPoint[]>('query_quota_history', {
+    provider,
+    account,
+    model: options.model ?? null,
+    range: { from: range.from.toISOString(), to: range.to?.toISOString() ?? null },
+    bucket: options.bucket ?? null,
+  });
+}
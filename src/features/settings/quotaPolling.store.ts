@@ -0,0 +1,40 @@
+import { create } from 'zustand';
+import { invoke } from '@tauri-apps/api/core';
+import type { QuotaProviderId } from '@/types';
+
+/** Background quota refresh, which keeps running while the window is hidden */
+export interface QuotaPollSettings {
+  enabled: boolean;
+  interval_secs: number;
+  provider_intervals: Partial<Record<QuotaProviderId, number>>;
+  jitter_percent: number;
+  max_errors: number;
+  pause_secs: number;
+}
+
+interface QuotaPollingState {
+  settings: QuotaPollSettings | null;
+  saving: boolean;
+  fetchSettings: () => Promise<void>;
+  saveSettings: (settings: QuotaPollSettings) => Promise<void>;
+}
+
+export const useQuotaPollingStore = create<QuotaPollingState>((set) => ({
+  settings: null,
+  saving: false,
+
+  fetchSettings: async () => {
+    const settings = await invoke<QuotaPollSettings>('get_quota_poll_settings');
+    set({ settings });
+  },
+
+  saveSettings: async (settings) => {
+    set({ saving: true });
+    try {
+      await invoke('set_quota_poll_settings', { settings });
+      set({ settings });
+    } finally {
+      set({ saving: false });
+    }
+  },
+}));
@@ -3,5 +3,4 @@ export * from './apiCall';
 export * from './config.service';
 export * from './auth.service';
 export * from './oauth.service';
-export * from './quota.service';
 export * from './usage.service';
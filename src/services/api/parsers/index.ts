@@ -1,6 +0,0 @@
-export { parseAntigravityModels } from './antigravity.parser';
-export { parseCodexUsage } from './codex.parser';
-export { parseGeminiCliQuota } from './gemini.parser';
-export { parseKiroQuota } from './kiro.parser';
-export { parseCopilotQuota } from './copilot.parser';
-export { parseClaudeUsage } from './claude.parser';
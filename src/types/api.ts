@@ -69,3 +69,9 @@ export interface RawApiCallResponse {
   headers?: Record<string, string[]>;
   body?: unknown;
 }
+
+// Error rejected by Tauri commands; `message` is always set
+export type CommandError =
+  | { kind: 'general'; message: string }
+  | { kind: 'port_in_use'; message: string; port: number; holder: string }
+  | { kind: 'verification_failed'; message: string; file: string; reason: string };
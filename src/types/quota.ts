@@ -5,43 +5,30 @@ export interface QuotaModel {
   percentage: number;
   resetTime?: string;
   displayValue?: string;
+  /** Set when the quota is forecast to run out before it resets */
+  exhaustion?: { at: string; confidence: number };
 }
 
-export interface AntigravityQuotaResult {
-  models: QuotaModel[];
-  error?: string;
-}
+/** Provider ids accepted by the `fetch_quota` command */
+export type QuotaProviderId = 'anthropic' | 'antigravity' | 'codex' | 'gemini-cli' | 'kiro' | 'copilot';
 
-export interface ClaudeQuotaResult {
-  models: QuotaModel[];
-  email?: string;
-  error?: string;
+export interface QuotaWindow {
+  name: string;
+  remaining_percent: number;
+  resets_at: string | null;
+  detail: string | null;
 }
 
-export interface CodexQuotaResult {
-  plan?: string;
-  limits: Array<{ name: string; percentage: number; resetTime?: string }>;
-  error?: string;
-}
+export type QuotaErrorKind = 'token_expired' | 'access_denied' | 'rate_limited' | 'invalid_response' | 'request';
 
-export interface GeminiCliQuotaResult {
-  buckets: Array<{ modelId: string; percentage: number; resetTime?: string }>;
-  error?: string;
-}
-
-export interface KiroQuotaResult {
-  models: QuotaModel[];
-  plan?: string;
-  email?: string;
-  tokenExpiresAt?: string;
-  error?: string;
-}
-
-export interface CopilotQuotaResult {
-  models: QuotaModel[];
-  plan?: string;
-  username?: string;
-  error?: string;
+export interface QuotaSnapshot {
+  provider: QuotaProviderId;
+  auth_index: string;
+  fetched_at: string;
+  plan: string | null;
+  email: string | null;
+  windows: QuotaWindow[];
+  error: { kind: QuotaErrorKind; message: string } | null;
 }
 
 export interface FileQuota {
@@ -54,7 +41,7 @@ export interface FileQuota {
   originalFile?: AuthFile;
   models?: QuotaModel[];
   plan?: string;
-  limits?: Array<{ name: string; percentage: number; resetTime?: string }>;
+  limits?: QuotaModel[];
   email?: string;
 }
 
@@ -63,3 +50,27 @@ export interface ProviderSection {
   displayName: string;
   files: FileQuota[];
 }
+
+export type QuotaHistoryBucket = 'raw' | 'hour' | 'day';
+
+/** One bucket of one model from `query_quota_history` */
+export interface QuotaHistoryPoint {
+  model: string;
+  at: string;
+  remaining_percent: number;
+  min_percent: number;
+  resets_at: string | null;
+}
+
+/** Forecast of one model from `forecast_quota` */
+export interface QuotaForecast {
+  provider: QuotaProviderId;
+  account: string;
+  model: string;
+  remaining_percent: number;
+  burn_rate: number | null;
+  predicted_exhaustion_at: string | null;
+  resets_at: string | null;
+  resets_first: boolean | null;
+  confidence: number;
+}